@@ -0,0 +1,330 @@
+//! Analysis of tournament matches.
+//!
+//! A [`MatchAnalyzer`] takes the games of a match, each with its
+//! [`MatchScore`]s, and works out each game's team totals and winner, the
+//! running series score, and each player's averages.
+//!
+//! Players are grouped into teams by a roster mapping user IDs to team names.
+//! Players not in the roster are grouped by their team colour in team modes,
+//! and otherwise each player is their own team, named by their user ID.
+//!
+//! A game is won by the team with the highest total for its
+//! [`ScoringType`]: the sum of scores for score and ScoreV2, the mean
+//! accuracy for accuracy, and the sum of max combos for combo. Warmups and
+//! aborted games are shown but not counted, by the rules in [`Rules`].
+//!
+//! # Examples
+//!
+//! Analyze a match between two teams, with the first game as a warmup:
+//!
+//! ```rust
+//! use osu::analysis::{MatchAnalyzer, Rules};
+//! use std::collections::HashMap;
+//!
+//! # let games = vec![];
+//! let mut roster = HashMap::new();
+//! roster.insert(124493, "South Korea".to_owned());
+//! roster.insert(2558286, "Poland".to_owned());
+//!
+//! let rules = Rules {
+//!     warmups: 1,
+//!     ..Rules::default()
+//! };
+//! let analysis = MatchAnalyzer::new(roster, rules).analyze(&games);
+//!
+//! for (team, wins) in &analysis.series {
+//!     println!("{}: {}", team, wins);
+//! }
+//! ```
+//!
+//! [`MatchAnalyzer`]: struct.MatchAnalyzer.html
+//! [`MatchScore`]: ../struct.MatchScore.html
+//! [`Rules`]: struct.Rules.html
+//! [`ScoringType`]: ../enum.ScoringType.html
+
+use std::collections::{BTreeMap, HashMap};
+use ::pp::{self, Score};
+use ::{Game, MatchScore, ScoringType, TeamType};
+
+/// The rules for which games of a match count.
+#[derive(Clone, Debug)]
+pub struct Rules {
+    /// The number of counted games at the start of the match that are
+    /// warmups.
+    pub warmups: usize,
+    /// The IDs of other games to ignore, such as replayed picks.
+    pub ignored: Vec<i64>,
+    /// Whether to ignore aborted games, which have no scores or no end time.
+    pub ignore_aborted: bool,
+    /// Whether to count the scores of players that failed.
+    pub count_failed: bool,
+}
+
+impl Default for Rules {
+    /// Counts every game that was not aborted, including failed scores.
+    fn default() -> Rules {
+        Rules {
+            warmups: 0,
+            ignored: vec![],
+            ignore_aborted: true,
+            count_failed: true,
+        }
+    }
+}
+
+/// Whether a game counted towards the match.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GameStatus {
+    Counted,
+    Warmup,
+    Aborted,
+    Ignored,
+}
+
+/// A team's totals in a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TeamTotal {
+    pub team: String,
+    /// The IDs of the team's players in the game, in slot order.
+    pub players: Vec<i64>,
+    pub score: i64,
+    /// The mean accuracy of the team's players, from `0.0` to `1.0`.
+    pub accuracy: f64,
+    pub combo: i64,
+}
+
+/// The analysis of a game.
+#[derive(Clone, Debug)]
+pub struct GameAnalysis {
+    pub game_id: i64,
+    pub beatmap_id: i64,
+    pub status: GameStatus,
+    /// The teams' totals, best first by the game's scoring type.
+    pub teams: Vec<TeamTotal>,
+    /// The winning team, unless the game was not counted, had fewer than two
+    /// teams, or was tied.
+    pub winner: Option<String>,
+    /// The series score after the game.
+    pub series: BTreeMap<String, u32>,
+}
+
+/// A player's averages over the counted games they played.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerStats {
+    pub user_id: i64,
+    /// The team of the player in their last counted game.
+    pub team: String,
+    pub games: u32,
+    pub average_score: f64,
+    /// The average accuracy, from `0.0` to `1.0`.
+    pub average_accuracy: f64,
+    pub average_combo: f64,
+}
+
+/// The analysis of a match.
+#[derive(Clone, Debug)]
+pub struct MatchAnalysis {
+    /// The analysis of each game, in the order given.
+    pub games: Vec<GameAnalysis>,
+    /// The number of counted games won by each team.
+    pub series: BTreeMap<String, u32>,
+    /// The averages of each player, by user ID.
+    pub players: Vec<PlayerStats>,
+}
+
+impl MatchAnalysis {
+    /// The team with the most wins, unless tied.
+    pub fn leader(&self) -> Option<&str> {
+        leader(self.series.iter().map(|(team, &wins)| (team.as_str(), wins)))
+    }
+}
+
+/// Analyzes matches by a roster and rules.
+#[derive(Clone, Debug, Default)]
+pub struct MatchAnalyzer {
+    /// The team of each player, by user ID.
+    pub roster: HashMap<i64, String>,
+    pub rules: Rules,
+}
+
+impl MatchAnalyzer {
+    /// Creates an analyzer with a roster mapping user IDs to team names.
+    pub fn new(roster: HashMap<i64, String>, rules: Rules) -> MatchAnalyzer {
+        MatchAnalyzer { roster, rules }
+    }
+
+    /// Analyzes the games of a match in the order they were played, each
+    /// with its scores.
+    ///
+    /// The scores are given separately as [`Game::scores`] does not hold
+    /// the team, slot and pass of each score.
+    ///
+    /// [`Game::scores`]: ../struct.Game.html#structfield.scores
+    pub fn analyze(&self, games: &[(Game, Vec<MatchScore>)]) -> MatchAnalysis {
+        let mut series = BTreeMap::new();
+        let mut players = BTreeMap::<i64, (String, PlayerTotals)>::new();
+        let mut warmups = 0;
+        let mut analyses = vec![];
+
+        for (game, scores) in games {
+            let aborted = scores.is_empty() || game.end_time.is_empty();
+            let status = if self.rules.ignored.contains(&game.game_id) {
+                GameStatus::Ignored
+            } else if aborted && self.rules.ignore_aborted {
+                GameStatus::Aborted
+            } else if warmups < self.rules.warmups {
+                warmups += 1;
+
+                GameStatus::Warmup
+            } else {
+                GameStatus::Counted
+            };
+
+            let scores = scores.iter()
+                .filter(|score| self.rules.count_failed || score.pass != 0)
+                .collect::<Vec<_>>();
+            let teams = self.teams(game, &scores);
+            let winner = match status {
+                GameStatus::Counted => winner(game.scoring_type, &teams),
+                _ => None,
+            };
+
+            if status == GameStatus::Counted {
+                for team in &teams {
+                    series.entry(team.team.clone()).or_insert(0);
+                }
+
+                if let Some(ref winner) = winner {
+                    *series.entry(winner.clone()).or_insert(0) += 1;
+                }
+
+                for score in &scores {
+                    let entry = players.entry(score.user_id).or_default();
+                    entry.0 = self.team(game, score);
+                    entry.1.add(game, score);
+                }
+            }
+
+            analyses.push(GameAnalysis {
+                game_id: game.game_id,
+                beatmap_id: game.beatmap_id,
+                status,
+                teams,
+                winner,
+                series: series.clone(),
+            });
+        }
+
+        MatchAnalysis {
+            games: analyses,
+            series,
+            players: players.into_iter().map(|(user_id, (team, totals))| {
+                let games = totals.games.max(1) as f64;
+
+                PlayerStats {
+                    user_id,
+                    team,
+                    games: totals.games,
+                    average_score: totals.score as f64 / games,
+                    average_accuracy: totals.accuracy / games,
+                    average_combo: totals.combo as f64 / games,
+                }
+            }).collect(),
+        }
+    }
+
+    /// The team of a player in a game.
+    pub fn team(&self, game: &Game, score: &MatchScore) -> String {
+        if let Some(team) = self.roster.get(&score.user_id) {
+            return team.clone();
+        }
+
+        match (game.team_type, score.team) {
+            (TeamType::TeamVs, 1) | (TeamType::TagTeamVs, 1) => "Blue".to_owned(),
+            (TeamType::TeamVs, 2) | (TeamType::TagTeamVs, 2) => "Red".to_owned(),
+            _ => score.user_id.to_string(),
+        }
+    }
+
+    fn teams(&self, game: &Game, scores: &[&MatchScore]) -> Vec<TeamTotal> {
+        let mut teams = Vec::<TeamTotal>::new();
+        let mut sorted = scores.to_vec();
+        sorted.sort_by_key(|score| score.slot);
+
+        for score in sorted {
+            let name = self.team(game, score);
+            let accuracy = pp::accuracy(game.play_mode, &Score::from(score));
+
+            match teams.iter_mut().find(|team| team.team == name) {
+                Some(team) => {
+                    team.players.push(score.user_id);
+                    team.score += score.score;
+                    team.accuracy += accuracy;
+                    team.combo += score.max_combo;
+                },
+                None => teams.push(TeamTotal {
+                    team: name,
+                    players: vec![score.user_id],
+                    score: score.score,
+                    accuracy,
+                    combo: score.max_combo,
+                }),
+            }
+        }
+
+        for team in &mut teams {
+            team.accuracy /= team.players.len() as f64;
+        }
+
+        teams.sort_by(|a, b| compare(game.scoring_type, b, a));
+
+        teams
+    }
+}
+
+#[derive(Debug, Default)]
+struct PlayerTotals {
+    games: u32,
+    score: i64,
+    accuracy: f64,
+    combo: i64,
+}
+
+impl PlayerTotals {
+    fn add(&mut self, game: &Game, score: &MatchScore) {
+        self.games += 1;
+        self.score += score.score;
+        self.accuracy += pp::accuracy(game.play_mode, &Score::from(score));
+        self.combo += score.max_combo;
+    }
+}
+
+fn compare(scoring_type: ScoringType, a: &TeamTotal, b: &TeamTotal) -> ::std::cmp::Ordering {
+    match scoring_type {
+        ScoringType::Score | ScoringType::ScoreV2 => a.score.cmp(&b.score),
+        ScoringType::Accuracy => a.accuracy.partial_cmp(&b.accuracy).unwrap_or(::std::cmp::Ordering::Equal),
+        ScoringType::Combo => a.combo.cmp(&b.combo),
+    }
+}
+
+/// The winner of teams sorted best first, unless the best two are tied.
+fn winner(scoring_type: ScoringType, teams: &[TeamTotal]) -> Option<String> {
+    match (teams.first(), teams.get(1)) {
+        (Some(first), Some(second)) if compare(scoring_type, first, second) == ::std::cmp::Ordering::Greater => {
+            Some(first.team.clone())
+        },
+        _ => None,
+    }
+}
+
+/// The team with the most wins, unless the most wins are tied.
+fn leader<'a, I: Iterator<Item = (&'a str, u32)>>(teams: I) -> Option<&'a str> {
+    let mut teams = teams.collect::<Vec<_>>();
+    teams.sort_by_key(|&(_, wins)| ::std::cmp::Reverse(wins));
+
+    match (teams.first(), teams.get(1)) {
+        (Some(&(_, most)), Some(&(_, next))) if most == next => None,
+        (Some(&(team, _)), _) => Some(team),
+        (None, _) => None,
+    }
+}
@@ -0,0 +1,197 @@
+//! Tracking changes to users' top plays.
+//!
+//! A [`BestTracker`] keeps the last snapshot of each user's top plays from
+//! `get_user_best` in a [`BestStorage`], and compares each new snapshot
+//! against it to find plays that have entered the top plays, the plays they
+//! displaced, and the change in weighted pp.
+//!
+//! The API does not give top plays an ID, so plays are identified by their
+//! beatmap, mods, score and date.
+//!
+//! Snapshots should be retrieved with a limit of [`LIMIT`], as plays beyond
+//! the limit are treated as displaced.
+//!
+//! # Examples
+//!
+//! Announce new top plays, given a snapshot retrieved with
+//! `get_user_best`:
+//!
+//! ```rust
+//! use osu::best::{BestTracker, MemoryStorage};
+//! use osu::{GetBeatmapUser, Performance, PlayMode};
+//!
+//! # fn plays() -> Vec<Performance> { vec![] }
+//! let mut tracker = BestTracker::new(MemoryStorage::default());
+//! let user = GetBeatmapUser::UserId(124493);
+//!
+//! let diff = tracker.update(&user, PlayMode::Standard, plays()).unwrap();
+//!
+//! for new in &diff.new {
+//!     println!("New #{} top play, {:+.0}pp", new.index + 1, diff.pp_gain);
+//! }
+//! ```
+//!
+//! [`BestStorage`]: trait.BestStorage.html
+//! [`BestTracker`]: struct.BestTracker.html
+//! [`LIMIT`]: constant.LIMIT.html
+
+use std::collections::HashMap;
+use ::{GetBeatmapUser, Performance, PlayMode, Result};
+
+/// The maximum number of top plays that the API returns, to be given to
+/// [`GetUserBestRequest::limit`].
+///
+/// [`GetUserBestRequest::limit`]: ../builder/struct.GetUserBestRequest.html#method.limit
+pub const LIMIT: u16 = 100;
+
+/// The factor by which the pp of each successive top play is weighted.
+const WEIGHT: f64 = 0.95;
+
+/// Storage of the last snapshot of each user's top plays in a mode.
+///
+/// Implement this to keep snapshots in a database or file, so that changes
+/// can be tracked across restarts.
+pub trait BestStorage {
+    /// Loads the last snapshot of a user's top plays, if there is one.
+    fn load(&self, user: &GetBeatmapUser, mode: PlayMode) -> Result<Option<Vec<Performance>>>;
+
+    /// Saves a snapshot of a user's top plays, replacing the last one.
+    fn save(&mut self, user: &GetBeatmapUser, mode: PlayMode, plays: &[Performance]) -> Result<()>;
+}
+
+/// Storage of snapshots in memory, which is lost when dropped.
+///
+/// Users are stored as given, so a user given by ID is stored separately
+/// from the same user given by username.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    pub snapshots: HashMap<(GetBeatmapUser, PlayMode), Vec<Performance>>,
+}
+
+impl BestStorage for MemoryStorage {
+    fn load(&self, user: &GetBeatmapUser, mode: PlayMode) -> Result<Option<Vec<Performance>>> {
+        Ok(self.snapshots.get(&(user.clone(), mode)).cloned())
+    }
+
+    fn save(&mut self, user: &GetBeatmapUser, mode: PlayMode, plays: &[Performance]) -> Result<()> {
+        self.snapshots.insert((user.clone(), mode), plays.to_vec());
+
+        Ok(())
+    }
+}
+
+/// Tracks changes to users' top plays.
+#[derive(Clone, Debug)]
+pub struct BestTracker<S: BestStorage> {
+    storage: S,
+}
+
+impl<S: BestStorage> BestTracker<S> {
+    /// Creates a tracker keeping its snapshots in the given storage.
+    pub fn new(storage: S) -> BestTracker<S> {
+        BestTracker { storage }
+    }
+
+    /// The storage of the tracker's snapshots.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Consumes the tracker, returning its storage.
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// Compares a new snapshot of a user's top plays, in the order returned
+    /// by `get_user_best`, against the last one, and saves it in its place.
+    ///
+    /// The first snapshot of a user is saved without reporting any changes.
+    ///
+    /// Returns an error if the storage fails.
+    pub fn update(&mut self, user: &GetBeatmapUser, mode: PlayMode, plays: Vec<Performance>)
+        -> Result<BestDiff> {
+        let diff = match self.storage.load(user, mode)? {
+            Some(previous) => BestDiff::new(&previous, &plays),
+            None => BestDiff::default(),
+        };
+
+        self.storage.save(user, mode, &plays)?;
+
+        Ok(diff)
+    }
+}
+
+/// The changes between two snapshots of a user's top plays.
+#[derive(Clone, Debug, Default)]
+pub struct BestDiff {
+    /// The plays that have entered the top plays, best first.
+    pub new: Vec<NewBest>,
+    /// The plays that have left the top plays, either because they were
+    /// improved upon or because they fell below the limit.
+    pub displaced: Vec<Performance>,
+    /// The change in the weighted sum of the top plays' pp.
+    ///
+    /// This does not include bonus pp, and differs from the change in the
+    /// user's total pp if the user has more plays than the snapshots hold.
+    pub pp_gain: f64,
+}
+
+impl BestDiff {
+    /// Compares two snapshots of top plays, each in the order returned by
+    /// `get_user_best`.
+    pub fn new(previous: &[Performance], current: &[Performance]) -> BestDiff {
+        let new = current.iter().enumerate()
+            .filter(|&(_, play)| !previous.iter().any(|old| same_play(old, play)))
+            .map(|(index, play)| NewBest {
+                index,
+                performance: play.clone(),
+                replaced: previous.iter()
+                    .find(|old| old.beatmap_id == play.beatmap_id && !current.iter().any(|cur| same_play(cur, old)))
+                    .cloned(),
+            })
+            .collect();
+        let displaced = previous.iter()
+            .filter(|old| !current.iter().any(|play| same_play(play, old)))
+            .cloned()
+            .collect();
+
+        BestDiff {
+            new,
+            displaced,
+            pp_gain: weighted_pp(current) - weighted_pp(previous),
+        }
+    }
+
+    /// Whether the top plays have not changed.
+    pub fn is_empty(&self) -> bool {
+        self.new.is_empty() && self.displaced.is_empty()
+    }
+}
+
+/// A play that has entered a user's top plays.
+#[derive(Clone, Debug)]
+pub struct NewBest {
+    /// The play's position in the top plays, starting from 0.
+    pub index: usize,
+    pub performance: Performance,
+    /// The user's previous top play on the same beatmap, if the play
+    /// improved upon it.
+    pub replaced: Option<Performance>,
+}
+
+/// Whether two top plays are the same, by their beatmap, mods, score and
+/// date.
+pub fn same_play(a: &Performance, b: &Performance) -> bool {
+    a.beatmap_id == b.beatmap_id
+        && a.enabled_mods == b.enabled_mods
+        && a.score == b.score
+        && a.date == b.date
+}
+
+/// The weighted sum of the pp of top plays, sorted best first.
+pub fn weighted_pp(plays: &[Performance]) -> f64 {
+    plays.iter()
+        .zip((0..).map(|idx| WEIGHT.powi(idx)))
+        .map(|(play, weight)| play.pp * weight)
+        .sum()
+}
@@ -4,6 +4,7 @@
 use serde_json;
 use std::collections::BTreeMap;
 use super::GetBeatmapUser;
+use ::link::Link;
 use ::model::*;
 
 enum GetBeatmapType {
@@ -63,6 +64,27 @@ impl<'a> GetBeatmapsRequest<'a> {
         GetBeatmapsRequest(self.0)
     }
 
+    /// Filter by the target of a [`Link`].
+    ///
+    /// Beatmap and beatmap set links filter by their IDs, including
+    /// conversions to the link's mode if it has one. User links filter by the
+    /// beatmaps that the user created, and match links have no effect.
+    ///
+    /// [`Link`]: ../link/enum.Link.html
+    pub fn link(self, link: &Link) -> Self {
+        let request = match *link {
+            Link::Beatmap { beatmap_id, .. } => self.beatmap_id(beatmap_id),
+            Link::BeatmapSet { beatmapset_id, .. } => self.beatmap_set_id(beatmapset_id),
+            Link::Match { .. } => return self,
+            Link::User { ref user, .. } => self.user(user.clone()),
+        };
+
+        match link.mode() {
+            Some(mode) => request.mode(mode).include_converted(true),
+            None => request,
+        }
+    }
+
     /// The amount of results to return.
     ///
     /// Defaults to `500`. Maximum is `500`.
@@ -280,6 +302,19 @@ impl<'a> GetUserRequest<'a> {
         GetUserRequest(self.0)
     }
 
+    /// Specify the mode of a [`Link`], if it has one.
+    ///
+    /// The user itself is given by [`Link::user`].
+    ///
+    /// [`Link`]: ../link/enum.Link.html
+    /// [`Link::user`]: ../link/enum.Link.html#method.user
+    pub fn link(self, link: &Link) -> Self {
+        match link.mode() {
+            Some(mode) => self.mode(mode),
+            None => self,
+        }
+    }
+
     /// Specify the mode to filter results by.
     pub fn mode(mut self, mode: PlayMode) -> Self {
         if let Ok(mode) = serde_json::to_string(&mode) {
@@ -0,0 +1,196 @@
+//! Looking up many users at once.
+//!
+//! The API only retrieves one user per request, so looking up many users is
+//! done with a number of requests in flight at a time, each waiting for a
+//! shared [`RateLimiter`]. Each user's result is kept separate as a
+//! [`UserLookup`], so that one failure does not lose the others, and users
+//! that do not exist are told apart from requests that failed.
+//!
+//! Users can be looked up with any client through [`lookup_users`], or with
+//! [`get_users`] for hyper and [`get_users_blocking`] for reqwest.
+//!
+//! # Examples
+//!
+//! Look up users with a client of your own, four at a time:
+//!
+//! ```rust
+//! use osu::bulk::{self, UserLookup};
+//!
+//! let users = vec![124493u64.into(), "peppy".to_owned().into()];
+//! let results = bulk::lookup_users(users, 4, |_user| Ok(vec![]));
+//!
+//! for (user, lookup) in results {
+//!     match lookup {
+//!         UserLookup::Found(found) => println!("{}: {}pp", found.username, found.pp_raw),
+//!         UserLookup::NotFound => println!("{:?} does not exist", user),
+//!         UserLookup::Failed(why) => println!("{:?} could not be retrieved: {:?}", user, why),
+//!     }
+//! }
+//! ```
+//!
+//! [`RateLimiter`]: ../ratelimit/struct.RateLimiter.html
+//! [`UserLookup`]: enum.UserLookup.html
+//! [`get_users`]: fn.get_users.html
+//! [`get_users_blocking`]: fn.get_users_blocking.html
+//! [`lookup_users`]: fn.lookup_users.html
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use ::{Error, GetBeatmapUser, Result, User};
+
+#[cfg(any(feature = "reqwest", all(feature = "hyper", feature = "tokio-core")))]
+use ::ratelimit::RateLimiter;
+#[cfg(any(feature = "reqwest", all(feature = "hyper", feature = "tokio-core")))]
+use ::PlayMode;
+
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use futures::{Future, Stream, future, stream};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use hyper::client::{Client, Connect};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use hyper::Error as HyperError;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use tokio_core::reactor::{Handle, Timeout};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use ::bridge::hyper::OsuHyperRequester;
+
+#[cfg(feature = "reqwest")]
+use reqwest::Client as ReqwestClient;
+#[cfg(feature = "reqwest")]
+use ::bridge::reqwest::OsuReqwestRequester;
+
+/// The result of looking up a user.
+#[derive(Debug)]
+pub enum UserLookup {
+    Found(User),
+    /// The user does not exist, or is restricted.
+    NotFound,
+    /// The request for the user failed.
+    Failed(Error),
+}
+
+impl UserLookup {
+    /// Interprets the result of a `get_user` request.
+    pub fn from_result(result: Result<Vec<User>>) -> UserLookup {
+        match result {
+            Ok(users) => users.into_iter().next().map_or(UserLookup::NotFound, UserLookup::Found),
+            Err(why) => UserLookup::Failed(why),
+        }
+    }
+
+    /// The user, if they were found.
+    pub fn found(self) -> Option<User> {
+        match self {
+            UserLookup::Found(user) => Some(user),
+            _ => None,
+        }
+    }
+
+    /// Whether the user does not exist.
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn is_not_found(&self) -> bool {
+        match *self {
+            UserLookup::NotFound => true,
+            _ => false,
+        }
+    }
+}
+
+/// Looks up users on up to `concurrency` threads at a time, retrieving each
+/// with the given function, such as a call to `get_user`.
+///
+/// Returns each user with their result, in the order given. Panics if
+/// `fetch` panics for any user.
+pub fn lookup_users<F>(users: Vec<GetBeatmapUser>, concurrency: usize, fetch: F)
+    -> Vec<(GetBeatmapUser, UserLookup)>
+    where F: Fn(&GetBeatmapUser) -> Result<Vec<User>> + Send + Sync + 'static {
+    let count = users.len();
+    let queue = Arc::new(Mutex::new(users.into_iter().enumerate().collect::<VecDeque<_>>()));
+    let results = Arc::new(Mutex::new(Vec::with_capacity(count)));
+    let fetch = Arc::new(fetch);
+
+    let workers = (0..concurrency.max(1).min(count)).map(|_| {
+        let (queue, results, fetch) = (queue.clone(), results.clone(), fetch.clone());
+
+        thread::spawn(move || loop {
+            let next = queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).pop_front();
+            let (idx, user) = match next {
+                Some(next) => next,
+                None => break,
+            };
+            let lookup = UserLookup::from_result(fetch(&user));
+
+            results.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push((idx, user, lookup));
+        })
+    }).collect::<Vec<_>>();
+
+    for worker in workers {
+        worker.join().expect("a user lookup panicked");
+    }
+
+    let mut results = results.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).drain(..).collect::<Vec<_>>();
+    results.sort_by_key(|&(idx, _, _)| idx);
+
+    results.into_iter().map(|(_, user, lookup)| (user, lookup)).collect()
+}
+
+/// Looks up users in a mode with up to `concurrency` requests in flight at
+/// a time, each waiting for the rate limiter.
+///
+/// Resolves to each user with their result, in the order given. The future
+/// itself never fails.
+///
+/// Requires the `hyper-support` feature.
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+pub fn get_users<B, C>(
+    client: Client<C, B>,
+    key: String,
+    users: Vec<GetBeatmapUser>,
+    mode: PlayMode,
+    concurrency: usize,
+    limiter: Arc<RateLimiter>,
+    handle: &Handle,
+) -> Box<Future<Item = Vec<(GetBeatmapUser, UserLookup)>, Error = Error>>
+    where B: Stream<Error = HyperError> + 'static,
+          B::Item: AsRef<[u8]>,
+          C: Connect {
+    let handle = handle.clone();
+
+    Box::new(stream::iter_ok::<_, Error>(users)
+        .map(move |user| {
+            let (client, key, requested) = (client.clone(), key.clone(), user.clone());
+
+            future::result(Timeout::new(limiter.reserve(), &handle))
+                .flatten()
+                .from_err()
+                .and_then(move |_| client.get_user(&key, requested, |req| req.mode(mode)))
+                .then(move |result| Ok((user, UserLookup::from_result(result))))
+        })
+        .buffered(concurrency.max(1))
+        .collect())
+}
+
+/// Looks up users in a mode on up to `concurrency` threads at a time, each
+/// request waiting for the rate limiter.
+///
+/// Returns each user with their result, in the order given.
+///
+/// Requires the `reqwest-support` feature.
+#[cfg(feature = "reqwest")]
+pub fn get_users_blocking(
+    client: &ReqwestClient,
+    key: &str,
+    users: Vec<GetBeatmapUser>,
+    mode: PlayMode,
+    concurrency: usize,
+    limiter: Arc<RateLimiter>,
+) -> Vec<(GetBeatmapUser, UserLookup)> {
+    let (client, key) = (client.clone(), key.to_owned());
+
+    lookup_users(users, concurrency, move |user| {
+        limiter.wait();
+
+        client.get_user(&key, user.clone(), |req| req.mode(mode))
+    })
+}
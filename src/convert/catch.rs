@@ -0,0 +1,269 @@
+use ::hitobject::HitObjectKind;
+use ::osz::Difficulty;
+use ::slider::{Slider, SliderEventKind};
+use ::Mods;
+use super::LegacyRandom;
+
+/// The time between two points of a juice stream over which tiny droplets
+/// are placed, in milliseconds.
+const TINY_DROPLET_THRESHOLD: f64 = 80.0;
+
+/// The longest time between tiny droplets, in milliseconds.
+const TINY_DROPLET_SPACING: f64 = 100.0;
+
+/// The longest time between bananas, in milliseconds.
+const BANANA_SPACING: f64 = 100.0;
+
+/// The seed of the random numbers that objects are offset with.
+const RANDOM_SEED: i32 = 1337;
+
+/// The width of the playfield in osu!pixels.
+const PLAYFIELD_WIDTH: f64 = 512.0;
+
+/// A catch hit object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CatchObject {
+    /// The time in milliseconds.
+    pub time: f64,
+    /// The horizontal position on the playfield, from `0` to `512`.
+    pub x: f64,
+    pub kind: CatchKind,
+}
+
+impl CatchObject {
+    /// Whether the object is a fruit or droplet, each of which adds one to
+    /// the combo.
+    pub fn adds_combo(&self) -> bool {
+        self.kind == CatchKind::Fruit || self.kind == CatchKind::Droplet
+    }
+}
+
+/// The kinds of catch hit object.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CatchKind {
+    Fruit,
+    /// A slider tick.
+    Droplet,
+    /// A droplet between the points of a juice stream, which only counts
+    /// towards accuracy.
+    TinyDroplet,
+    /// A banana from a spinner, which only counts towards score.
+    Banana,
+}
+
+/// Converts a difficulty's hit objects to catch, in the order of the objects
+/// that they come from.
+///
+/// Circles become fruits. Sliders become juice streams of a fruit on the
+/// head, each repeat and the tail, a droplet on each tick and tiny droplets
+/// between them. Spinners become banana showers.
+///
+/// Bananas and tiny droplets are placed at random offsets, and HardRock
+/// moves fruits that are close in time further apart, using a
+/// [`LegacyRandom`] with the game's seed.
+///
+/// [`LegacyRandom`]: struct.LegacyRandom.html
+pub fn catch(difficulty: &Difficulty, mods: Mods) -> Vec<CatchObject> {
+    let mut offsets = Offsets {
+        random: LegacyRandom::new(RANDOM_SEED),
+        hard_rock: mods.contains(Mods::HARD_ROCK),
+        last_position: None,
+        last_time: 0.0,
+    };
+    let mut objects = vec![];
+
+    for (object, slider) in difficulty.hit_objects.iter().zip(difficulty.sliders()) {
+        match object.kind {
+            HitObjectKind::Circle => objects.push(CatchObject {
+                time: object.time,
+                x: offsets.fruit(object.position.x, object.time),
+                kind: CatchKind::Fruit,
+            }),
+            HitObjectKind::Slider { ref control_points, .. } => {
+                if let Some(ref slider) = slider {
+                    juice_stream(slider, &mut offsets.random, &mut objects);
+                }
+
+                // The game takes the last control point as the end of the
+                // stream, rather than the end of its path.
+                offsets.last_position = control_points.last().map(|point| point.x);
+                offsets.last_time = object.time;
+            },
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => {
+                let mut spacing = end_time - object.time;
+
+                while spacing > BANANA_SPACING {
+                    spacing /= 2.0;
+                }
+
+                if spacing <= 0.0 {
+                    continue;
+                }
+
+                let mut time = object.time;
+
+                while time <= end_time {
+                    objects.push(CatchObject {
+                        time,
+                        x: offsets.random.next_f64() * PLAYFIELD_WIDTH,
+                        kind: CatchKind::Banana,
+                    });
+
+                    // The game also draws each banana's type, rotation and
+                    // colour.
+                    for _ in 0..3 {
+                        offsets.random.next_u32();
+                    }
+
+                    time += spacing;
+                }
+            },
+        }
+    }
+
+    objects
+}
+
+/// The random offsets of objects, and the last fruit that HardRock offsets
+/// the next from.
+struct Offsets {
+    random: LegacyRandom,
+    hard_rock: bool,
+    last_position: Option<f64>,
+    last_time: f64,
+}
+
+impl Offsets {
+    /// The position of a fruit, which HardRock moves further from the last
+    /// if they are close in time, or randomly if they are in the same place.
+    fn fruit(&mut self, x: f64, time: f64) -> f64 {
+        if !self.hard_rock {
+            return x;
+        }
+
+        let last_position = match self.last_position {
+            Some(last_position) => last_position,
+            None => {
+                self.last_position = Some(x);
+                self.last_time = time;
+
+                return x;
+            },
+        };
+
+        let position_difference = x - last_position;
+        // The game measures the time in whole milliseconds.
+        let time_difference = (time - self.last_time) as i32;
+
+        if time_difference > 1000 {
+            self.last_position = Some(x);
+            self.last_time = time;
+
+            return x;
+        }
+
+        if position_difference == 0.0 {
+            let right = self.random.next_bool();
+            let offset = (self.random.next_f64() * (f64::from(time_difference) / 4.0).max(0.0)).min(20.0);
+
+            return if right && x + offset <= PLAYFIELD_WIDTH || !right && x - offset < 0.0 {
+                x + offset
+            } else {
+                x - offset
+            };
+        }
+
+        let mut position = x;
+
+        if position_difference.abs() < f64::from(time_difference / 3) {
+            let moved = position + position_difference;
+
+            if position_difference > 0.0 && moved < PLAYFIELD_WIDTH || position_difference < 0.0 && moved > 0.0 {
+                position = moved;
+            }
+        }
+
+        self.last_position = Some(position);
+        self.last_time = time;
+
+        position
+    }
+}
+
+#[allow(clippy::manual_clamp)]
+fn juice_stream(slider: &Slider, random: &mut LegacyRandom, objects: &mut Vec<CatchObject>) {
+    let span_duration = slider.span_duration();
+    let mut points = slider.events().iter().map(|event| {
+        let kind = match event.kind {
+            SliderEventKind::Tick => CatchKind::Droplet,
+            _ => CatchKind::Fruit,
+        };
+
+        (Some(kind), event.time, event.progress)
+    }).collect::<Vec<_>>();
+
+    // The last tick of the slider places no object, but tiny droplets are
+    // placed up to it rather than the tail.
+    let last_span_start = slider.start_time + f64::from(slider.slides - 1) * span_duration;
+    let last_tick_time = slider.legacy_last_tick_time();
+    let span_progress = if span_duration > 0.0 {
+        (last_tick_time - last_span_start) / span_duration
+    } else {
+        0.0
+    };
+    let last_tick_progress = if slider.slides % 2 == 1 {
+        span_progress
+    } else {
+        1.0 - span_progress
+    };
+
+    let tail = points.len() - 1;
+    points.insert(tail, (None, last_tick_time, last_tick_progress));
+
+    let mut last: Option<(f64, f64)> = None;
+
+    for (kind, time, progress) in points {
+        if let Some((last_time, last_progress)) = last {
+            let since_last = time - last_time;
+
+            if since_last > TINY_DROPLET_THRESHOLD {
+                let mut spacing = since_last;
+
+                while spacing > TINY_DROPLET_SPACING {
+                    spacing /= 2.0;
+                }
+
+                let mut offset = spacing;
+
+                while offset < since_last {
+                    let progress = last_progress + offset / since_last * (progress - last_progress);
+                    let x = slider.path.position_at(progress).x;
+                    let jitter = f64::from(random.next_in(-20, 20)).max(-x).min(PLAYFIELD_WIDTH - x);
+
+                    objects.push(CatchObject {
+                        time: last_time + offset,
+                        x: x + jitter,
+                        kind: CatchKind::TinyDroplet,
+                    });
+
+                    offset += spacing;
+                }
+            }
+        }
+
+        last = Some((time, progress));
+
+        if let Some(kind) = kind {
+            objects.push(CatchObject {
+                time,
+                x: slider.path.position_at(progress).x,
+                kind,
+            });
+
+            // The game also draws each droplet's rotation.
+            if kind == CatchKind::Droplet {
+                random.next_u32();
+            }
+        }
+    }
+}
@@ -0,0 +1,1110 @@
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
+use ::hitobject::{HitObject, HitObjectKind, Position};
+use ::osz::Difficulty;
+use ::slider::Slider;
+use ::timing::Timing;
+use ::Mods;
+use super::{BASE_SCORING_DISTANCE, CLAP, FINISH, WHISTLE};
+
+/// The number of recent notes that the density of notes is measured over.
+const MAX_NOTES_FOR_DENSITY: usize = 7;
+
+/// The key mods, which choose the number of keys of a converted beatmap.
+const KEY_MODS: [(Mods, u32); 9] = [
+    (Mods::KEY1, 1),
+    (Mods::KEY2, 2),
+    (Mods::KEY3, 3),
+    (Mods::KEY4, 4),
+    (Mods::KEY5, 5),
+    (Mods::KEY6, 6),
+    (Mods::KEY7, 7),
+    (Mods::KEY8, 8),
+    (Mods::KEY9, 9),
+];
+
+bitflags! {
+    /// The kinds of pattern that an object can be converted to.
+    struct PatternType: u32 {
+        const FORCE_STACK = 1;
+        const FORCE_NOT_STACK = 1 << 1;
+        const KEEP_SINGLE = 1 << 2;
+        const LOW_PROBABILITY = 1 << 3;
+        const GATHERED = 1 << 7;
+        const MIRROR = 1 << 8;
+        const REVERSE = 1 << 9;
+        const CYCLE = 1 << 10;
+        const STAIR = 1 << 11;
+        const REVERSE_STAIR = 1 << 12;
+    }
+}
+
+/// The random number generator that the game converts beatmaps to mania
+/// and places catch objects with.
+///
+/// This is a xorshift generator, so it gives the same numbers for a seed on
+/// every platform.
+///
+/// # Examples
+///
+/// ```rust
+/// use osu::convert::LegacyRandom;
+///
+/// let mut random = LegacyRandom::new(1337);
+/// let column = random.next_in(0, 7);
+///
+/// assert!(column >= 0 && column < 7);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+    /// The bits left over from the last number drawn for a boolean.
+    bit_buffer: u32,
+    bit_index: u32,
+}
+
+impl LegacyRandom {
+    pub fn new(seed: i32) -> LegacyRandom {
+        LegacyRandom {
+            x: seed as u32,
+            y: 842_502_087,
+            z: 3_579_807_591,
+            w: 273_326_509,
+            bit_buffer: 0,
+            bit_index: 32,
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+
+        self.w
+    }
+
+    /// A random integer from `0` up to and including `i32::MAX`.
+    pub fn next_i32(&mut self) -> i32 {
+        (self.next_u32() & 0x7FFF_FFFF) as i32
+    }
+
+    /// A random number from `0` up to, but not including, `1`.
+    pub fn next_f64(&mut self) -> f64 {
+        f64::from(self.next_i32()) / (f64::from(i32::MAX) + 1.0)
+    }
+
+    /// A random boolean, which uses one bit of a number at a time.
+    pub fn next_bool(&mut self) -> bool {
+        if self.bit_index == 32 {
+            self.bit_buffer = self.next_u32();
+            self.bit_index = 1;
+        } else {
+            self.bit_buffer >>= 1;
+            self.bit_index += 1;
+        }
+
+        self.bit_buffer & 1 == 1
+    }
+
+    /// A random integer from `lower` up to, but not including, `upper`.
+    pub fn next_in(&mut self, lower: i32, upper: i32) -> i32 {
+        (f64::from(lower) + self.next_f64() * f64::from(upper - lower)) as i32
+    }
+}
+
+/// A mania note or hold note.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ManiaObject {
+    /// The column, from `0`.
+    pub column: u32,
+    /// The time in milliseconds.
+    pub time: f64,
+    /// The time that a hold note ends, or `None` for a note.
+    pub end_time: Option<f64>,
+}
+
+/// The number of keys that a difficulty is played with in mania.
+///
+/// Difficulties made for mania have their circle size as the number of
+/// keys. Converted beatmaps are played with the number of keys of a key mod,
+/// or otherwise with a number chosen from their ratio of sliders and spinners
+/// to circles, their circle size and their overall difficulty.
+#[allow(clippy::manual_clamp)]
+pub fn mania_keys(difficulty: &Difficulty, mods: Mods) -> u32 {
+    let circle_size = round(single(difficulty.diff_size));
+
+    if difficulty.mode == 3 {
+        return circle_size.max(1.0) as u32;
+    }
+
+    if let Some(&(_, keys)) = KEY_MODS.iter().find(|&&(key_mod, _)| mods.contains(key_mod)) {
+        return keys;
+    }
+
+    let overall_difficulty = round(single(difficulty.diff_overall));
+    let objects = &difficulty.hit_objects;
+    let long_objects = objects.iter().filter(|object| !object.is_circle()).count();
+    let long_ratio = f64::from(long_objects as f32 / objects.len() as f32);
+
+    if long_ratio < 0.2 {
+        7
+    } else if long_ratio < 0.3 || circle_size >= 5.0 {
+        if overall_difficulty > 5.0 { 7 } else { 6 }
+    } else if long_ratio > 0.6 {
+        if overall_difficulty > 4.0 { 5 } else { 4 }
+    } else {
+        (overall_difficulty as u32 + 1).max(4).min(7)
+    }
+}
+
+/// Converts a difficulty's hit objects to mania, ordered by time.
+///
+/// Notes are placed in patterns chosen by the time and distance from the
+/// objects before them, their hitsounds and how difficult the beatmap is,
+/// using a [`LegacyRandom`] seeded from the difficulty settings. Sliders
+/// become hold notes or several notes, and spinners become a hold note.
+///
+/// [`LegacyRandom`]: struct.LegacyRandom.html
+pub fn mania(difficulty: &Difficulty, mods: Mods) -> Vec<ManiaObject> {
+    let keys = mania_keys(difficulty, mods);
+    let sliders = difficulty.sliders();
+    let mut objects = vec![];
+
+    if difficulty.mode == 3 {
+        for (object, slider) in difficulty.hit_objects.iter().zip(&sliders) {
+            objects.push(ManiaObject {
+                column: object.column(keys),
+                time: object.time,
+                end_time: match object.kind {
+                    HitObjectKind::Circle => None,
+                    HitObjectKind::Slider { .. } => slider.as_ref().map(Slider::end_time),
+                    HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => Some(end_time),
+                },
+            });
+        }
+
+        return objects;
+    }
+
+    let mut converter = Converter::new(difficulty, keys);
+
+    for (object, slider) in difficulty.hit_objects.iter().zip(&sliders) {
+        objects.extend(converter.convert(object, slider.as_ref()));
+    }
+
+    // Sorting is stable, so objects at the same time keep the order of
+    // their patterns.
+    objects.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+    objects
+}
+
+/// Rounds to the nearest integer, with halves rounded to the even one, as
+/// the game does.
+fn round(value: f64) -> f64 {
+    if (value - value.trunc()).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        value.round()
+    }
+}
+
+/// The game keeps difficulty settings in single precision.
+fn single(value: f64) -> f64 {
+    f64::from(value as f32)
+}
+
+/// The notes generated for an object.
+#[derive(Clone, Debug, Default)]
+struct Pattern {
+    objects: Vec<ManiaObject>,
+}
+
+impl Pattern {
+    fn has(&self, column: i32) -> bool {
+        self.objects.iter().any(|object| object.column as i32 == column)
+    }
+
+    /// The number of columns with an object.
+    fn column_count(&self) -> i32 {
+        self.objects.iter().map(|object| object.column).collect::<HashSet<_>>().len() as i32
+    }
+
+    fn add(&mut self, column: i32, time: f64, end_time: f64) {
+        self.objects.push(ManiaObject {
+            column: column.max(0) as u32,
+            time,
+            end_time: if end_time == time { None } else { Some(end_time) },
+        });
+    }
+}
+
+/// Finds a column from `initial` that `valid` accepts, trying each column
+/// given by `next` until one is.
+///
+/// Falls back to `initial` if no column from `lower` up to `upper` is valid,
+/// where the game would search forever.
+fn find_column<N, V>(random: &mut LegacyRandom, initial: i32, lower: i32, upper: i32, mut next: N, valid: V) -> i32
+    where N: FnMut(&mut LegacyRandom, i32) -> i32, V: Fn(i32) -> bool {
+    if valid(initial) || !(lower..upper).any(&valid) {
+        return initial;
+    }
+
+    let mut column = initial;
+
+    loop {
+        column = next(random, column);
+
+        if valid(column) {
+            return column;
+        }
+    }
+}
+
+/// Finds a column from `initial` that `valid` accepts, trying random columns
+/// from `lower` up to `upper`.
+fn available_column<V>(random: &mut LegacyRandom, initial: i32, lower: i32, upper: i32, valid: V) -> i32
+    where V: Fn(i32) -> bool {
+    find_column(random, initial, lower, upper, |random, _| random.next_in(lower, upper), valid)
+}
+
+/// The timing of a slider as converted, which is split into a segment for
+/// each span.
+struct SliderSegments {
+    end_time: f64,
+    /// The duration of each segment in milliseconds.
+    duration: f64,
+    spans: i32,
+}
+
+impl SliderSegments {
+    /// The hitsound flags of the node at the start of the segment at a time.
+    fn edge_sound(&self, object: &HitObject, time: f64) -> u8 {
+        let node = if self.duration == 0.0 {
+            0.0
+        } else {
+            (time - object.time) / self.duration
+        };
+
+        object.edge_sound(node as usize)
+    }
+}
+
+/// Converts each object in turn, keeping the pattern, position and density
+/// of the objects before it.
+struct Converter {
+    timing: Timing,
+    slider_multiplier: f64,
+    random: LegacyRandom,
+    keys: i32,
+    /// The first column that notes are randomly placed in, which skips the
+    /// special column of 8 keys.
+    random_start: i32,
+    /// How difficult the beatmap is from its settings and object density,
+    /// up to `12`.
+    conversion_difficulty: f64,
+    previous: Pattern,
+    previous_times: VecDeque<f64>,
+    density: f64,
+    last_time: f64,
+    last_position: Position,
+}
+
+impl Converter {
+    #[allow(clippy::manual_clamp)]
+    fn new(difficulty: &Difficulty, keys: u32) -> Converter {
+        let seed = round(f64::from(difficulty.diff_drain as f32 + difficulty.diff_size as f32)) as i32 * 20
+            + (single(difficulty.diff_overall) * 41.2) as i32
+            + round(single(difficulty.diff_approach)) as i32;
+
+        let objects = &difficulty.hit_objects;
+        let mut drain_time = match (objects.first(), objects.last()) {
+            (Some(first), Some(last)) => last.time - first.time,
+            _ => 0.0,
+        };
+        drain_time -= difficulty.breaks.iter().map(|&(start, end)| end - start).sum::<f64>();
+
+        if drain_time == 0.0 {
+            drain_time = 10_000.0;
+        }
+
+        let approach_rate = (difficulty.diff_approach as f32).max(4.0).min(7.0);
+        let conversion_difficulty = (f64::from(difficulty.diff_drain as f32 + approach_rate) / 1.5
+            + objects.len() as f64 / drain_time * 9.0) / 38.0 * 5.0 / 1.15;
+
+        Converter {
+            timing: difficulty.timing(),
+            slider_multiplier: difficulty.slider_multiplier,
+            random: LegacyRandom::new(seed),
+            keys: keys as i32,
+            random_start: if keys == 8 { 1 } else { 0 },
+            conversion_difficulty: conversion_difficulty.min(12.0),
+            previous: Pattern::default(),
+            previous_times: VecDeque::with_capacity(MAX_NOTES_FOR_DENSITY),
+            density: f64::from(i32::MAX),
+            last_time: 0.0,
+            last_position: Position::default(),
+        }
+    }
+
+    fn convert(&mut self, object: &HitObject, slider: Option<&Slider>) -> Vec<ManiaObject> {
+        match object.kind {
+            HitObjectKind::Slider { slides, .. } => {
+                let spans = slides.max(1);
+                let beat_length = self.timing.beat_length_at(object.time).unwrap_or(1000.0)
+                    / self.timing.slider_velocity_at(object.time);
+                let distance = slider.map_or(0.0, |slider| slider.path.length()) * f64::from(spans);
+                let velocity = BASE_SCORING_DISTANCE * self.slider_multiplier / beat_length;
+                let end_time = object.time + distance / velocity;
+                let segments = SliderSegments {
+                    end_time,
+                    duration: (end_time - object.time) / f64::from(spans),
+                    spans: spans as i32,
+                };
+
+                let mut time = object.time;
+
+                while time - 1e-7 <= end_time {
+                    self.record(time, object.position);
+
+                    if segments.duration <= 0.0 {
+                        break;
+                    }
+
+                    time += segments.duration;
+                }
+
+                let pattern = self.slider_pattern(object, &segments);
+                self.previous = pattern.clone();
+
+                pattern.objects
+            },
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => {
+                self.record(end_time, Position::new(256.0, 192.0));
+
+                // Spinners do not become the pattern that the next object
+                // follows.
+                self.spinner_pattern(object, end_time).objects
+            },
+            HitObjectKind::Circle => {
+                self.add_density(object.time);
+
+                let pattern = self.circle_pattern(object);
+                self.previous = pattern.clone();
+                self.last_time = object.time;
+                self.last_position = object.position;
+
+                pattern.objects
+            },
+        }
+    }
+
+    /// Records a note's time and position for the patterns of the objects
+    /// after it.
+    fn record(&mut self, time: f64, position: Position) {
+        self.last_time = time;
+        self.last_position = position;
+        self.add_density(time);
+    }
+
+    fn add_density(&mut self, time: f64) {
+        if self.previous_times.len() == MAX_NOTES_FOR_DENSITY {
+            self.previous_times.pop_front();
+        }
+
+        self.previous_times.push_back(time);
+
+        if let (Some(first), Some(last)) = (self.previous_times.front(), self.previous_times.back()) {
+            self.density = (last - first) / self.previous_times.len() as f64;
+        }
+    }
+
+    /// The column for a horizontal position, where the special column of 8
+    /// keys is only used if `allow_special` is set.
+    #[allow(clippy::manual_clamp)]
+    fn column_of(&self, x: f64, allow_special: bool) -> i32 {
+        let x = x as f32;
+
+        if allow_special && self.keys == 8 {
+            return ((x / (512.0 / 7.0)).floor() as i32).max(0).min(6) + 1;
+        }
+
+        ((x / (512.0 / self.keys as f32)).floor() as i32).max(0).min(self.keys - 1)
+    }
+
+    /// A random number of notes from `1` to `6`, with `p2` being the
+    /// probability of at least two and so on.
+    fn note_count(&mut self, p2: f64, p3: f64, p4: f64, p5: f64, p6: f64) -> i32 {
+        let value = self.random.next_f64();
+
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn circle_pattern(&mut self, object: &HitObject) -> Pattern {
+        let beat_length = self.timing.beat_length_at(object.time).unwrap_or(1000.0);
+        let position_separation = (object.position - self.last_position).length();
+        let time_separation = object.time - self.last_time;
+
+        let mut convert_type = if time_separation <= 80.0 {
+            // More than 187 BPM.
+            PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE
+        } else if time_separation <= 95.0 {
+            // More than 157 BPM. The game keeps the direction of stairs
+            // between objects, but never changes it from upwards.
+            PatternType::FORCE_NOT_STACK | PatternType::KEEP_SINGLE | PatternType::STAIR
+        } else if time_separation <= 105.0 {
+            // More than 140 BPM.
+            PatternType::FORCE_NOT_STACK | PatternType::LOW_PROBABILITY
+        } else if time_separation <= 125.0 {
+            // More than 120 BPM.
+            PatternType::FORCE_NOT_STACK
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            // A stream of more than 111 BPM.
+            PatternType::CYCLE | PatternType::KEEP_SINGLE
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            // A stream of more than 100 BPM.
+            PatternType::FORCE_STACK | PatternType::LOW_PROBABILITY
+        } else if position_separation < 20.0 && self.density >= beat_length / 2.5 {
+            // A stream of low density.
+            PatternType::REVERSE | PatternType::LOW_PROBABILITY
+        } else if self.density < beat_length / 2.5 || self.timing.kiai_at(object.time) {
+            // High density.
+            PatternType::empty()
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        if !convert_type.contains(PatternType::KEEP_SINGLE) {
+            if object.hit_sound & FINISH != 0 && self.keys != 8 {
+                convert_type |= PatternType::MIRROR;
+            } else if object.hit_sound & CLAP != 0 {
+                convert_type |= PatternType::GATHERED;
+            }
+        }
+
+        let (keys, random_start) = (self.keys, self.random_start);
+        let mut pattern = Pattern::default();
+
+        if keys == 1 {
+            pattern.add(0, object.time, object.time);
+
+            return pattern;
+        }
+
+        let previous_count = self.previous.objects.len();
+        let last_column = self.previous.objects.first().map_or(0, |object| object.column as i32);
+
+        if convert_type.contains(PatternType::REVERSE) && previous_count > 0 {
+            // The columns of the previous pattern, mirrored.
+            for column in random_start..keys {
+                if self.previous.has(column) {
+                    pattern.add(random_start + keys - column - 1, object.time, object.time);
+                }
+            }
+
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::CYCLE)
+            && previous_count == 1
+            // Avoid overloading the special column of 8 keys.
+            && (keys != 8 || last_column != 0)
+            && (keys % 2 == 0 || last_column != keys / 2) {
+            pattern.add(random_start + keys - last_column - 1, object.time, object.time);
+
+            return pattern;
+        }
+
+        if convert_type.contains(PatternType::FORCE_STACK) && previous_count > 0 {
+            // The columns of the previous pattern.
+            for column in random_start..keys {
+                if self.previous.has(column) {
+                    pattern.add(column, object.time, object.time);
+                }
+            }
+
+            return pattern;
+        }
+
+        if previous_count == 1 {
+            if convert_type.contains(PatternType::STAIR) {
+                let column = if last_column + 1 == keys { random_start } else { last_column + 1 };
+                pattern.add(column, object.time, object.time);
+
+                return pattern;
+            }
+
+            if convert_type.contains(PatternType::REVERSE_STAIR) {
+                let column = if last_column - 1 == random_start - 1 { keys - 1 } else { last_column - 1 };
+                pattern.add(column, object.time, object.time);
+
+                return pattern;
+            }
+        }
+
+        if convert_type.contains(PatternType::KEEP_SINGLE) {
+            return self.random_circle_notes(object, convert_type, 1);
+        }
+
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if convert_type.contains(PatternType::MIRROR) {
+            return if self.conversion_difficulty > 6.5 {
+                self.mirrored_pattern(object, convert_type, 0.12, 0.38, 0.12)
+            } else if self.conversion_difficulty > 4.0 {
+                self.mirrored_pattern(object, convert_type, 0.12, 0.17, 0.0)
+            } else {
+                self.mirrored_pattern(object, convert_type, 0.12, 0.0, 0.0)
+            };
+        }
+
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.random_pattern(object, convert_type, 0.78, 0.42, 0.0, 0.0)
+            } else {
+                self.random_pattern(object, convert_type, 1.0, 0.62, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.random_pattern(object, convert_type, 0.35, 0.08, 0.0, 0.0)
+            } else {
+                self.random_pattern(object, convert_type, 0.52, 0.15, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.0 {
+            if low_probability {
+                self.random_pattern(object, convert_type, 0.18, 0.0, 0.0, 0.0)
+            } else {
+                self.random_pattern(object, convert_type, 0.45, 0.0, 0.0, 0.0)
+            }
+        } else {
+            self.random_pattern(object, convert_type, 0.0, 0.0, 0.0, 0.0)
+        }
+    }
+
+    /// Places notes in random columns, or in the columns after the object's
+    /// own if they are gathered.
+    fn random_circle_notes(&mut self, object: &HitObject, convert_type: PatternType, count: i32) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let allow_stacking = !convert_type.contains(PatternType::FORCE_NOT_STACK);
+        let gathered = convert_type.contains(PatternType::GATHERED);
+        let count = if allow_stacking {
+            count
+        } else {
+            count.min(keys - random_start - self.previous.column_count())
+        };
+
+        let previous = &self.previous;
+        let mut pattern = Pattern::default();
+        let mut column = self.column_of(object.position.x, true);
+
+        for _ in 0..count {
+            column = find_column(&mut self.random, column, random_start, keys, |random, last| {
+                if !gathered {
+                    random.next_in(random_start, keys)
+                } else if last + 1 == keys {
+                    random_start
+                } else {
+                    last + 1
+                }
+            }, |column| !pattern.has(column) && (allow_stacking || !previous.has(column)));
+
+            pattern.add(column, object.time, object.time);
+        }
+
+        pattern
+    }
+
+    /// Whether a note is also placed in the special column of 8 keys.
+    fn has_special_column(object: &HitObject) -> bool {
+        object.hit_sound & CLAP != 0 && object.hit_sound & FINISH != 0
+    }
+
+    fn random_pattern(
+        &mut self,
+        object: &HitObject,
+        convert_type: PatternType,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+        mut p5: f64,
+    ) -> Pattern {
+        match self.keys {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            },
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+                p5 = 0.0;
+            },
+            4 => {
+                p2 = p2.min(0.23);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+                p5 = 0.0;
+            },
+            5 => {
+                p3 = p3.min(0.15);
+                p4 = p4.min(0.03);
+                p5 = 0.0;
+            },
+            _ => {},
+        }
+
+        if object.hit_sound & CLAP != 0 {
+            p2 = 1.0;
+        }
+
+        let count = self.note_count(p2, p3, p4, p5, 0.0);
+        let mut pattern = self.random_circle_notes(object, convert_type, count);
+
+        if self.random_start > 0 && Converter::has_special_column(object) {
+            pattern.add(0, object.time, object.time);
+        }
+
+        pattern
+    }
+
+    /// Places notes in pairs mirrored about the centre.
+    fn mirrored_pattern(
+        &mut self,
+        object: &HitObject,
+        convert_type: PatternType,
+        mut centre_probability: f64,
+        mut p2: f64,
+        mut p3: f64,
+    ) -> Pattern {
+        if convert_type.contains(PatternType::FORCE_NOT_STACK) {
+            return self.random_pattern(object, convert_type, 0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let (keys, random_start) = (self.keys, self.random_start);
+
+        match keys {
+            2 => {
+                centre_probability = 0.0;
+                p2 = 0.0;
+                p3 = 0.0;
+            },
+            3 => {
+                centre_probability = centre_probability.min(0.03);
+                p2 = 0.0;
+                p3 = 0.0;
+            },
+            4 => {
+                centre_probability = 0.0;
+                p2 = (p2 * 2.0).min(0.2);
+                p3 = 0.0;
+            },
+            5 => {
+                centre_probability = centre_probability.min(0.03);
+                p3 = 0.0;
+            },
+            6 => {
+                centre_probability = 0.0;
+                p2 = (p2 * 2.0).min(0.5);
+                p3 = (p3 * 2.0).min(0.15);
+            },
+            _ => {},
+        }
+
+        let centre_value = self.random.next_f64();
+        let count = self.note_count(p2, p3, 0.0, 0.0, 0.0);
+        let add_to_centre = keys % 2 != 0 && count != 3 && centre_value > 1.0 - centre_probability;
+
+        let column_limit = (if keys % 2 == 0 { keys } else { keys - 1 }) / 2;
+        let mut pattern = Pattern::default();
+        let mut column = self.random.next_in(random_start, column_limit);
+
+        for _ in 0..count {
+            column = available_column(&mut self.random, column, random_start, column_limit, |column| {
+                !pattern.has(column)
+            });
+
+            pattern.add(column, object.time, object.time);
+            pattern.add(random_start + keys - column - 1, object.time, object.time);
+        }
+
+        if add_to_centre {
+            pattern.add(keys / 2, object.time, object.time);
+        }
+
+        if random_start > 0 && Converter::has_special_column(object) {
+            pattern.add(0, object.time, object.time);
+        }
+
+        pattern
+    }
+
+    fn spinner_pattern(&mut self, object: &HitObject, end_time: f64) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        // Short spinners become notes.
+        let end_time = if end_time - object.time >= 100.0 { end_time } else { object.time };
+        let mut pattern = Pattern::default();
+
+        let column = if keys == 8 && object.hit_sound & FINISH != 0 && end_time - object.time < 1000.0 {
+            0
+        } else {
+            let previous = &self.previous;
+            let column = self.random.next_in(random_start, keys);
+
+            if previous.column_count() == keys {
+                column
+            } else {
+                available_column(&mut self.random, column, random_start, keys, |column| !previous.has(column))
+            }
+        };
+
+        pattern.add(column, object.time, end_time);
+
+        pattern
+    }
+
+    fn slider_pattern(&mut self, object: &HitObject, segments: &SliderSegments) -> Pattern {
+        let mut convert_type = if self.timing.kiai_at(object.time) {
+            PatternType::empty()
+        } else {
+            PatternType::LOW_PROBABILITY
+        };
+
+        if self.keys == 1 {
+            let mut pattern = Pattern::default();
+            pattern.add(0, object.time, segments.end_time);
+
+            return pattern;
+        }
+
+        if segments.spans > 1 {
+            if segments.duration <= 90.0 {
+                return self.random_holds(segments, object.time, 1);
+            }
+
+            if segments.duration <= 120.0 {
+                convert_type |= PatternType::FORCE_NOT_STACK;
+
+                return self.random_notes(object, segments, convert_type, segments.spans + 1);
+            }
+
+            if segments.duration <= 160.0 {
+                return self.stair(object, segments);
+            }
+
+            if segments.duration <= 200.0 && self.conversion_difficulty > 3.0 {
+                return self.random_multiple_notes(object, segments);
+            }
+
+            if segments.end_time - object.time >= 4000.0 {
+                return self.n_random_holds(object, segments, convert_type, 0.23, 0.0, 0.0);
+            }
+
+            if segments.duration > 400.0 && segments.spans < self.keys - 1 - self.random_start {
+                return self.tiled_holds(object, segments, convert_type);
+            }
+
+            return self.hold_and_notes(object, segments, convert_type);
+        }
+
+        if segments.duration <= 110.0 {
+            if self.previous.column_count() < self.keys {
+                convert_type |= PatternType::FORCE_NOT_STACK;
+            } else {
+                convert_type.remove(PatternType::FORCE_NOT_STACK);
+            }
+
+            let count = if segments.duration < 80.0 { 1 } else { 2 };
+
+            return self.random_notes(object, segments, convert_type, count);
+        }
+
+        let low_probability = convert_type.contains(PatternType::LOW_PROBABILITY);
+
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.n_random_holds(object, segments, convert_type, 0.78, 0.3, 0.0)
+            } else {
+                self.n_random_holds(object, segments, convert_type, 0.85, 0.36, 0.03)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.n_random_holds(object, segments, convert_type, 0.43, 0.08, 0.0)
+            } else {
+                self.n_random_holds(object, segments, convert_type, 0.56, 0.18, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.5 {
+            if low_probability {
+                self.n_random_holds(object, segments, convert_type, 0.3, 0.0, 0.0)
+            } else {
+                self.n_random_holds(object, segments, convert_type, 0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            self.n_random_holds(object, segments, convert_type, 0.17, 0.0, 0.0)
+        } else {
+            self.n_random_holds(object, segments, convert_type, 0.27, 0.0, 0.0)
+        }
+    }
+
+    /// Hold notes in random columns that each last the whole slider,
+    /// avoiding the previous pattern while there are free columns.
+    fn random_holds(&mut self, segments: &SliderSegments, time: f64, count: i32) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let previous = &self.previous;
+        let usable_columns = keys - random_start - previous.column_count();
+        let mut pattern = Pattern::default();
+        let mut column = self.random.next_in(random_start, keys);
+
+        for _ in 0..usable_columns.min(count) {
+            column = available_column(&mut self.random, column, random_start, keys, |column| {
+                !pattern.has(column) && !previous.has(column)
+            });
+
+            pattern.add(column, time, segments.end_time);
+        }
+
+        for _ in 0..count - usable_columns {
+            column = available_column(&mut self.random, column, random_start, keys, |column| !pattern.has(column));
+
+            pattern.add(column, time, segments.end_time);
+        }
+
+        pattern
+    }
+
+    /// A note on each node of the slider, each in a different column to the
+    /// last.
+    fn random_notes(
+        &mut self,
+        object: &HitObject,
+        segments: &SliderSegments,
+        convert_type: PatternType,
+        count: i32,
+    ) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let previous = &self.previous;
+        let mut pattern = Pattern::default();
+        let mut column = self.column_of(object.position.x, true);
+
+        if convert_type.contains(PatternType::FORCE_NOT_STACK) && previous.column_count() < keys {
+            column = available_column(&mut self.random, column, random_start, keys, |column| !previous.has(column));
+        }
+
+        let mut last_column = column;
+        let mut time = object.time;
+
+        for _ in 0..count {
+            pattern.add(column, time, time);
+
+            column = available_column(&mut self.random, column, random_start, keys, |column| column != last_column);
+            last_column = column;
+            time += segments.duration;
+        }
+
+        pattern
+    }
+
+    /// A note on each node of the slider, stepping across the columns and
+    /// turning back at the edges.
+    fn stair(&mut self, object: &HitObject, segments: &SliderSegments) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let mut pattern = Pattern::default();
+        let mut column = self.column_of(object.position.x, true);
+        let mut increasing = self.random.next_f64() > 0.5;
+        let mut time = object.time;
+
+        for _ in 0..=segments.spans {
+            pattern.add(column, time, time);
+            time += segments.duration;
+
+            if increasing {
+                if column >= keys - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+
+        pattern
+    }
+
+    /// One or two notes on each node of the slider.
+    fn random_multiple_notes(&mut self, object: &HitObject, segments: &SliderSegments) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let legacy = if (4..=8).contains(&keys) { 1 } else { 0 };
+        let interval = self.random.next_in(1, keys - legacy);
+        let mut pattern = Pattern::default();
+        let mut column = self.column_of(object.position.x, true);
+        let mut time = object.time;
+
+        for _ in 0..=segments.spans {
+            pattern.add(column, time, time);
+
+            column += interval;
+
+            if column >= keys - random_start {
+                column = column - keys - random_start + legacy;
+            }
+
+            column += random_start;
+
+            // Two keys would have too many doubles in a row.
+            if keys > 2 {
+                pattern.add(column, time, time);
+            }
+
+            column = self.random.next_in(random_start, keys);
+            time += segments.duration;
+        }
+
+        pattern
+    }
+
+    /// A random number of hold notes that each last the whole slider.
+    fn n_random_holds(
+        &mut self,
+        object: &HitObject,
+        segments: &SliderSegments,
+        convert_type: PatternType,
+        mut p2: f64,
+        mut p3: f64,
+        mut p4: f64,
+    ) -> Pattern {
+        match self.keys {
+            2 => {
+                p2 = 0.0;
+                p3 = 0.0;
+                p4 = 0.0;
+            },
+            3 => {
+                p2 = p2.min(0.1);
+                p3 = 0.0;
+                p4 = 0.0;
+            },
+            4 => {
+                p2 = p2.min(0.3);
+                p3 = p3.min(0.04);
+                p4 = 0.0;
+            },
+            5 => {
+                p2 = p2.min(0.34);
+                p3 = p3.min(0.1);
+                p4 = p4.min(0.03);
+            },
+            _ => {},
+        }
+
+        let doubles = |sound: u8| sound & (CLAP | FINISH) != 0;
+
+        if !convert_type.contains(PatternType::LOW_PROBABILITY)
+            && (doubles(object.hit_sound) || doubles(segments.edge_sound(object, object.time))) {
+            p2 = 1.0;
+        }
+
+        let count = self.note_count(p2, p3, p4, 0.0, 0.0);
+
+        self.random_holds(segments, object.time, count)
+    }
+
+    /// Hold notes that start on successive nodes of the slider and all end
+    /// with it.
+    fn tiled_holds(&mut self, object: &HitObject, segments: &SliderSegments, convert_type: PatternType) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let previous = &self.previous;
+        let mut pattern = Pattern::default();
+        let mut column = self.column_of(object.position.x, true);
+        let mut time = object.time;
+
+        if convert_type.contains(PatternType::FORCE_NOT_STACK) && previous.column_count() < keys {
+            column = available_column(&mut self.random, column, random_start, keys, |column| !previous.has(column));
+        }
+
+        for _ in 0..segments.spans.min(keys) {
+            column = available_column(&mut self.random, column, random_start, keys, |column| !pattern.has(column));
+
+            pattern.add(column, time, segments.end_time);
+            time += segments.duration;
+        }
+
+        pattern
+    }
+
+    /// A hold note that lasts the whole slider, with notes beside it on each
+    /// node.
+    fn hold_and_notes(&mut self, object: &HitObject, segments: &SliderSegments, convert_type: PatternType) -> Pattern {
+        let (keys, random_start) = (self.keys, self.random_start);
+        let previous = &self.previous;
+        let mut pattern = Pattern::default();
+        let mut hold_column = self.column_of(object.position.x, true);
+
+        if convert_type.contains(PatternType::FORCE_NOT_STACK) && previous.column_count() < keys {
+            hold_column = available_column(&mut self.random, hold_column, random_start, keys, |column| {
+                !previous.has(column)
+            });
+        }
+
+        pattern.add(hold_column, object.time, segments.end_time);
+
+        let mut column = self.random.next_in(random_start, keys);
+        let count = if self.conversion_difficulty > 6.5 {
+            self.note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 4.0 {
+            self.note_count(if keys < 6 { 0.12 } else { 0.45 }, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 2.5 {
+            self.note_count(if keys < 6 { 0.0 } else { 0.24 }, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let count = count.min(keys - 1);
+
+        // The head only has notes beside it if it has a hitsound.
+        let ignore_head = segments.edge_sound(object, object.time) & (WHISTLE | FINISH | CLAP) == 0;
+        let mut time = object.time;
+
+        for _ in 0..=segments.spans {
+            if !(ignore_head && time == object.time) {
+                let mut row = Pattern::default();
+
+                for _ in 0..count {
+                    column = available_column(&mut self.random, column, random_start, keys, |column| {
+                        column != hold_column && !row.has(column)
+                    });
+
+                    row.add(column, time, time);
+                }
+
+                pattern.objects.extend(row.objects);
+            }
+
+            time += segments.duration;
+        }
+
+        pattern
+    }
+}
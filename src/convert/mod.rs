@@ -0,0 +1,69 @@
+//! Conversion of osu!standard beatmaps to taiko, catch and mania.
+//!
+//! The game plays a standard beatmap in the other modes by converting its hit
+//! objects, and these conversions are what the API rates when
+//! [`include_converted`] is set. Each follows the game's own converter, so a
+//! beatmap always converts to the same objects. Conversion to mania places
+//! notes with the game's [`LegacyRandom`] generator, seeded from the
+//! difficulty settings.
+//!
+//! The game only converts standard beatmaps, but each function also takes a
+//! difficulty of another mode. A mania difficulty passed to [`mania`] keeps
+//! its own columns and hold notes, and a taiko difficulty passed to
+//! [`taiko`] keeps its short sliders as drum rolls. Catch difficulties are
+//! stored with the same objects as standard ones, so [`catch`] places them
+//! the same way. Any other difficulty is converted as if it were standard.
+//!
+//! Requires the `osz-support` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use osu::convert;
+//! use osu::osz::Difficulty;
+//! use osu::Mods;
+//! use std::fs;
+//!
+//! let bytes = fs::read("Horizon - Flare (Jade Harley) [Normal].osu").unwrap();
+//! let difficulty = Difficulty::parse("Normal.osu", &bytes).unwrap();
+//!
+//! let keys = convert::mania_keys(&difficulty, Mods::empty());
+//! let notes = convert::mania(&difficulty, Mods::empty());
+//!
+//! println!("{} notes over {} keys", notes.len(), keys);
+//! ```
+//!
+//! [`LegacyRandom`]: struct.LegacyRandom.html
+//! [`catch`]: fn.catch.html
+//! [`mania`]: fn.mania.html
+//! [`taiko`]: fn.taiko.html
+//! [`include_converted`]: ../builder/struct.GetBeatmapsRequest.html#method.include_converted
+
+mod catch;
+mod mania;
+mod taiko;
+
+pub use self::catch::{catch, CatchKind, CatchObject};
+pub use self::mania::{mania, mania_keys, LegacyRandom, ManiaObject};
+pub use self::taiko::{taiko, TaikoKind, TaikoObject};
+
+/// The hitsound flags of a hit object.
+const WHISTLE: u8 = 2;
+const FINISH: u8 = 4;
+const CLAP: u8 = 8;
+
+/// The distance that a slider travels in a beat at a slider velocity and
+/// `SliderMultiplier` of `1`, in osu!pixels.
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+
+/// Scales a difficulty setting from `0` to `10` to a range, with `5` giving
+/// `mid`.
+fn difficulty_range(value: f64, min: f64, mid: f64, max: f64) -> f64 {
+    if value > 5.0 {
+        mid + (max - mid) * (value - 5.0) / 5.0
+    } else if value < 5.0 {
+        mid - (mid - min) * (5.0 - value) / 5.0
+    } else {
+        mid
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Ordering;
+use ::hitobject::{HitObject, HitObjectKind};
+use ::osz::Difficulty;
+use ::slider::Slider;
+use ::timing::Timing;
+use super::{BASE_SCORING_DISTANCE, CLAP, FINISH, WHISTLE, difficulty_range};
+
+/// How much faster drum rolls scroll than the sliders they come from.
+const VELOCITY_MULTIPLIER: f64 = 1.4;
+
+/// The number of hits needed per second of a swell, at an overall
+/// difficulty of `5`.
+const SWELL_HIT_MULTIPLIER: f64 = 1.65;
+
+/// A taiko hit object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TaikoObject {
+    /// The time in milliseconds.
+    pub time: f64,
+    /// Whether the object is a big one, which can be hit with both hands.
+    pub strong: bool,
+    pub kind: TaikoKind,
+}
+
+impl TaikoObject {
+    /// Whether the object is a centre or rim hit, each of which adds one to
+    /// the combo.
+    pub fn is_hit(&self) -> bool {
+        self.kind == TaikoKind::Centre || self.kind == TaikoKind::Rim
+    }
+}
+
+/// The parameters of each kind of taiko hit object.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaikoKind {
+    /// A hit on the centre of the drum, or don.
+    Centre,
+    /// A hit on the rim of the drum, or kat.
+    Rim,
+    DrumRoll {
+        end_time: f64,
+    },
+    /// A spinner, which is hit a number of times before it ends.
+    Swell {
+        end_time: f64,
+        required_hits: u32,
+    },
+}
+
+/// Converts a difficulty's hit objects to taiko.
+///
+/// Circles become centre hits, or rim hits if they have a whistle or clap,
+/// and are strong if they have a finish. Sliders become drum rolls, except
+/// that sliders of converted beatmaps shorter than two beats become a hit on
+/// each tick. Spinners become swells. Objects at the same time are merged
+/// into one strong object.
+pub fn taiko(difficulty: &Difficulty) -> Vec<TaikoObject> {
+    let timing = difficulty.timing();
+    let converted = difficulty.mode != 1;
+    let mut objects: Vec<TaikoObject> = vec![];
+
+    for object in &difficulty.hit_objects {
+        match object.kind {
+            HitObjectKind::Circle => objects.push(hit(object.time, object.hit_sound)),
+            HitObjectKind::Slider { slides, .. } => {
+                convert_slider(object, slides, difficulty, &timing, converted, &mut objects);
+            },
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => {
+                let hit_multiplier = difficulty_range(difficulty.diff_overall, 3.0, 5.0, 7.5) * SWELL_HIT_MULTIPLIER;
+                let required_hits = ((end_time - object.time) / 1000.0 * hit_multiplier).max(1.0);
+
+                objects.push(TaikoObject {
+                    time: object.time,
+                    strong: false,
+                    kind: TaikoKind::Swell {
+                        end_time,
+                        required_hits: required_hits as u32,
+                    },
+                });
+            },
+        }
+    }
+
+    // Sorting is stable, so the first of the objects at a time is kept.
+    objects.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+    let mut merged: Vec<TaikoObject> = Vec::with_capacity(objects.len());
+
+    for object in objects {
+        match merged.last_mut() {
+            Some(ref mut first) if first.time == object.time => {
+                if let TaikoKind::Swell { .. } = first.kind {
+                    continue;
+                }
+
+                first.strong = true;
+            },
+            _ => merged.push(object),
+        }
+    }
+
+    merged
+}
+
+fn hit(time: f64, hit_sound: u8) -> TaikoObject {
+    TaikoObject {
+        time,
+        strong: hit_sound & FINISH != 0,
+        kind: if hit_sound & (WHISTLE | CLAP) != 0 {
+            TaikoKind::Rim
+        } else {
+            TaikoKind::Centre
+        },
+    }
+}
+
+fn convert_slider(
+    object: &HitObject,
+    slides: u32,
+    difficulty: &Difficulty,
+    timing: &Timing,
+    converted: bool,
+    objects: &mut Vec<TaikoObject>,
+) {
+    let length = match Slider::new(object, timing, difficulty.slider_multiplier, difficulty.slider_tick_rate) {
+        Some(slider) => slider.path.length(),
+        None => return,
+    };
+    let spans = f64::from(slides.max(1));
+    let beat_length = timing.beat_length_at(object.time).unwrap_or(1000.0)
+        / timing.slider_velocity_at(object.time);
+
+    // Drum rolls are longer and faster than the sliders they come from, so
+    // they last as long.
+    let distance = length * spans * VELOCITY_MULTIPLIER;
+    let velocity = BASE_SCORING_DISTANCE * difficulty.slider_multiplier * VELOCITY_MULTIPLIER / beat_length;
+    let duration = distance / velocity;
+    let tick_spacing = (beat_length / difficulty.slider_tick_rate).min(duration / spans);
+
+    if converted && tick_spacing > 0.0 && duration < 2.0 * beat_length {
+        let nodes = slides.max(1) as usize + 1;
+        let mut time = object.time;
+        let mut node = 0;
+
+        while time <= object.time + duration + tick_spacing / 8.0 {
+            objects.push(hit(time, object.edge_sound(node)));
+
+            node = (node + 1) % nodes;
+            time += tick_spacing;
+        }
+    } else {
+        objects.push(TaikoObject {
+            time: object.time,
+            strong: object.hit_sound & FINISH != 0,
+            kind: TaikoKind::DrumRoll {
+                end_time: object.time + duration,
+            },
+        });
+    }
+}
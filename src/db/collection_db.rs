@@ -0,0 +1,168 @@
+use std::io::{Read, Write};
+use super::{Reader, Writer};
+use ::{Beatmap, Performance, Result};
+
+/// The version written by [`CollectionDb::new`], which any current client
+/// accepts.
+///
+/// [`CollectionDb::new`]: struct.CollectionDb.html#method.new
+const DEFAULT_VERSION: i32 = 20_191_106;
+
+/// The contents of a `collection.db` file: named collections of beatmaps.
+#[derive(Clone, Debug)]
+pub struct CollectionDb {
+    /// The client version that wrote the file, as a `YYYYMMDD` date.
+    pub version: i32,
+    pub collections: Vec<Collection>,
+}
+
+impl CollectionDb {
+    /// Creates a database of collections to be written.
+    pub fn new(collections: Vec<Collection>) -> CollectionDb {
+        CollectionDb {
+            version: DEFAULT_VERSION,
+            collections,
+        }
+    }
+
+    /// Reads a `collection.db` file.
+    ///
+    /// Returns [`Error::Parse`] if the file is malformed, or [`Error::Io`] if
+    /// it ends early.
+    ///
+    /// [`Error::Io`]: ../error/enum.Error.html#variant.Io
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn read<R: Read>(reader: R) -> Result<CollectionDb> {
+        let mut reader = Reader::new(reader);
+
+        let version = reader.i32()?;
+        let count = reader.i32()?;
+        let mut collections = Vec::with_capacity(count.max(0) as usize);
+
+        for _ in 0..count {
+            let name = reader.string()?;
+            let count = reader.i32()?;
+            let mut beatmap_md5s = Vec::with_capacity(count.max(0) as usize);
+
+            for _ in 0..count {
+                beatmap_md5s.push(reader.string()?);
+            }
+
+            collections.push(Collection { name, beatmap_md5s });
+        }
+
+        Ok(CollectionDb { version, collections })
+    }
+
+    /// Writes the collections in the `collection.db` format.
+    pub fn write<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = Writer::new(writer);
+
+        writer.i32(self.version)?;
+        writer.i32(self.collections.len() as i32)?;
+
+        for collection in &self.collections {
+            writer.string(&collection.name)?;
+            writer.i32(collection.beatmap_md5s.len() as i32)?;
+
+            for md5 in &collection.beatmap_md5s {
+                writer.string(md5)?;
+            }
+        }
+
+        writer.flush()
+    }
+}
+
+/// A named collection of beatmaps, identified by the MD5 hashes of their
+/// `.osu` files.
+#[derive(Clone, Debug, Default)]
+pub struct Collection {
+    pub name: String,
+    pub beatmap_md5s: Vec<String>,
+}
+
+impl Collection {
+    /// Creates an empty collection.
+    pub fn new<S: Into<String>>(name: S) -> Collection {
+        Collection {
+            name: name.into(),
+            beatmap_md5s: vec![],
+        }
+    }
+
+    /// Creates a collection of beatmaps, such as those retrieved by
+    /// [`get_beatmaps`], using their [`Beatmap::file_md5`].
+    ///
+    /// [`Beatmap::file_md5`]: ../struct.Beatmap.html#structfield.file_md5
+    /// [`get_beatmaps`]: ../bridge/hyper/trait.OsuHyperRequester.html#tymethod.get_beatmaps
+    pub fn from_beatmaps<S: Into<String>>(name: S, beatmaps: &[Beatmap]) -> Collection {
+        let mut collection = Collection::new(name);
+
+        for beatmap in beatmaps {
+            collection.push(&beatmap.file_md5);
+        }
+
+        collection
+    }
+
+    /// Creates a collection of the beatmaps that performances, such as those
+    /// retrieved by [`get_user_best`], were set on.
+    ///
+    /// As performances only refer to beatmaps by ID, the beatmaps must also
+    /// be given to look up their hashes. Performances on beatmaps that are
+    /// not given are skipped; their IDs can be found with [`missing`].
+    ///
+    /// [`get_user_best`]: ../bridge/hyper/trait.OsuHyperRequester.html#tymethod.get_user_best
+    /// [`missing`]: #method.missing
+    pub fn from_performances<S: Into<String>>(
+        name: S,
+        performances: &[Performance],
+        beatmaps: &[Beatmap],
+    ) -> Collection {
+        let mut collection = Collection::new(name);
+
+        for performance in performances {
+            let found = beatmaps.iter().find(|b| b.beatmap_id == performance.beatmap_id);
+
+            if let Some(beatmap) = found {
+                collection.push(&beatmap.file_md5);
+            }
+        }
+
+        collection
+    }
+
+    /// The IDs of the beatmaps of performances that are not in the given
+    /// beatmaps, and so cannot be added by [`from_performances`].
+    ///
+    /// [`from_performances`]: #method.from_performances
+    pub fn missing(performances: &[Performance], beatmaps: &[Beatmap]) -> Vec<i64> {
+        let mut missing = vec![];
+
+        for performance in performances {
+            let id = performance.beatmap_id;
+
+            if !beatmaps.iter().any(|b| b.beatmap_id == id) && !missing.contains(&id) {
+                missing.push(id);
+            }
+        }
+
+        missing
+    }
+
+    /// Whether the collection contains a beatmap.
+    pub fn contains(&self, beatmap: &Beatmap) -> bool {
+        self.beatmap_md5s.iter().any(|md5| md5.eq_ignore_ascii_case(&beatmap.file_md5))
+    }
+
+    /// Adds a beatmap by its MD5 hash, unless it is already in the
+    /// collection.
+    pub fn push<S: AsRef<str>>(&mut self, md5: S) {
+        let md5 = md5.as_ref();
+
+        if !self.beatmap_md5s.iter().any(|existing| existing.eq_ignore_ascii_case(md5)) {
+            self.beatmap_md5s.push(md5.to_owned());
+        }
+    }
+}
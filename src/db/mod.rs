@@ -0,0 +1,202 @@
+//! Readers for the local databases of the osu! stable client, and a writer
+//! for its collections.
+//!
+//! These are binary files in the client's installation directory, written in
+//! little-endian byte order with .NET-style strings and dates. Dates are
+//! converted to the `YYYY-MM-DD HH:MM:SS` format used by the API's models.
+//!
+//! Reading and writing are unbuffered, so wrap files in a `BufReader` or
+//! `BufWriter`.
+
+mod collection_db;
+mod osu_db;
+mod scores_db;
+
+pub use self::collection_db::{Collection, CollectionDb};
+pub use self::osu_db::{BeatmapEntry, OsuDb, Permissions, StarRating, TimingPoint};
+pub use self::scores_db::{BeatmapScores, LocalScore, ScoresDb};
+
+use std::io::{Read, Write};
+use ::{Error, Result};
+
+/// The number of .NET ticks at the Unix epoch.
+const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+const TICKS_PER_SECOND: i64 = 10_000_000;
+
+struct Reader<R> {
+    inner: R,
+}
+
+impl<R: Read> Reader<R> {
+    fn new(inner: R) -> Reader<R> {
+        Reader { inner }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        let mut buf = vec![0; len];
+        self.inner.read_exact(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    fn u64(&mut self, len: usize) -> Result<u64> {
+        let mut buf = [0; 8];
+        self.inner.read_exact(&mut buf[..len])?;
+
+        Ok(buf.iter().rev().fold(0, |acc, &byte| acc << 8 | u64::from(byte)))
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.u64(1)? as u8)
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        Ok(self.u64(2)? as i16)
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(self.u64(4)? as i32)
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(self.u64(8)? as i64)
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_bits(self.u64(4)? as u32))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_bits(self.u64(8)?))
+    }
+
+    fn uleb128(&mut self) -> Result<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.u8()?;
+
+            if shift >= 64 {
+                return Err(Error::Parse("string length is too long".to_owned()));
+            }
+
+            value |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads a string, which is either absent or a ULEB128 length followed by
+    /// UTF-8 bytes. Absent strings are read as empty.
+    fn string(&mut self) -> Result<String> {
+        match self.u8()? {
+            0x00 => Ok(String::new()),
+            0x0b => {
+                let len = self.uleb128()? as usize;
+                let bytes = self.bytes(len)?;
+
+                String::from_utf8(bytes)
+                    .map_err(|_| Error::Parse("string is not valid UTF-8".to_owned()))
+            },
+            other => Err(Error::Parse(format!("invalid string marker 0x{:02x}", other))),
+        }
+    }
+
+    fn date(&mut self) -> Result<String> {
+        Ok(format_ticks(self.i64()?))
+    }
+
+    /// Reads a date that is zero when unset.
+    fn optional_date(&mut self) -> Result<Option<String>> {
+        match self.i64()? {
+            0 => Ok(None),
+            ticks => Ok(Some(format_ticks(ticks))),
+        }
+    }
+}
+
+struct Writer<W> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+    fn new(inner: W) -> Writer<W> {
+        Writer { inner }
+    }
+
+    fn u64(&mut self, value: u64, len: usize) -> Result<()> {
+        let mut buf = [0; 8];
+
+        for (idx, byte) in buf.iter_mut().enumerate() {
+            *byte = (value >> (idx * 8)) as u8;
+        }
+
+        self.inner.write_all(&buf[..len]).map_err(From::from)
+    }
+
+    fn i32(&mut self, value: i32) -> Result<()> {
+        self.u64(u64::from(value as u32), 4)
+    }
+
+    fn uleb128(&mut self, mut value: u64) -> Result<()> {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                return self.u64(u64::from(byte), 1);
+            }
+
+            self.u64(u64::from(byte | 0x80), 1)?;
+        }
+    }
+
+    fn string(&mut self, value: &str) -> Result<()> {
+        self.u64(0x0b, 1)?;
+        self.uleb128(value.len() as u64)?;
+        self.inner.write_all(value.as_bytes()).map_err(From::from)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(From::from)
+    }
+}
+
+/// Formats .NET ticks, which count 100ns intervals since 0001-01-01, as a
+/// `YYYY-MM-DD HH:MM:SS` date.
+fn format_ticks(ticks: i64) -> String {
+    let seconds = (ticks - UNIX_EPOCH_TICKS) / TICKS_PER_SECOND;
+    let days = seconds.div_euclid(86_400);
+    let secs_of_day = seconds.rem_euclid(86_400);
+
+    // Converts days since the Unix epoch to a civil date, from Howard
+    // Hinnant's `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
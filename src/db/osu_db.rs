@@ -0,0 +1,374 @@
+use std::io::Read;
+use super::Reader;
+use ::{Approval, Error, Mods, PlayMode, Result};
+
+/// The first version with floating point difficulty settings and cached star
+/// ratings.
+const VERSION_FLOAT_DIFFICULTY: i32 = 20_140_609;
+
+/// The first version without a byte size before each beatmap entry.
+const VERSION_NO_ENTRY_SIZE: i32 = 20_191_106;
+
+/// The contents of an `osu!.db` file: the client's beatmap library.
+#[derive(Clone, Debug)]
+pub struct OsuDb {
+    /// The client version that wrote the file, as a `YYYYMMDD` date.
+    pub version: i32,
+    pub folder_count: i32,
+    pub account_unlocked: bool,
+    /// When the account will be unlocked, if it is locked.
+    pub account_unlock_date: Option<String>,
+    pub player_name: String,
+    pub beatmaps: Vec<BeatmapEntry>,
+    pub permissions: Permissions,
+}
+
+impl OsuDb {
+    /// Reads an `osu!.db` file.
+    ///
+    /// Returns [`Error::Parse`] if the file is malformed, or [`Error::Io`] if
+    /// it ends early.
+    ///
+    /// [`Error::Io`]: ../error/enum.Error.html#variant.Io
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn read<R: Read>(reader: R) -> Result<OsuDb> {
+        let mut reader = Reader::new(reader);
+
+        let version = reader.i32()?;
+        let folder_count = reader.i32()?;
+        let account_unlocked = reader.bool()?;
+        let account_unlock_date = reader.optional_date()?;
+        let player_name = reader.string()?;
+        let count = reader.i32()?;
+
+        let mut beatmaps = Vec::with_capacity(count.max(0) as usize);
+
+        for _ in 0..count {
+            beatmaps.push(BeatmapEntry::read(&mut reader, version)?);
+        }
+
+        let permissions = Permissions::from_bits_truncate(reader.i32()?);
+
+        Ok(OsuDb {
+            version,
+            folder_count,
+            account_unlocked,
+            account_unlock_date,
+            player_name,
+            beatmaps,
+            permissions,
+        })
+    }
+}
+
+bitflags! {
+    /// The permissions of the account logged into the client.
+    pub struct Permissions: i32 {
+        const NORMAL = 1;
+        const MODERATOR = 1 << 1;
+        const SUPPORTER = 1 << 2;
+        const FRIEND = 1 << 3;
+        const PEPPY = 1 << 4;
+        const WORLD_CUP_STAFF = 1 << 5;
+    }
+}
+
+/// A beatmap in the client's library.
+///
+/// Field names and types match those of [`Beatmap`] where they correspond.
+///
+/// [`Beatmap`]: ../struct.Beatmap.html
+#[derive(Clone, Debug)]
+pub struct BeatmapEntry {
+    pub artist: String,
+    pub artist_unicode: String,
+    pub title: String,
+    pub title_unicode: String,
+    pub creator: String,
+    pub version: String,
+    pub audio_file: String,
+    pub file_md5: String,
+    /// The name of the `.osu` file within the beatmap's folder.
+    pub file_name: String,
+    /// The ranked status, if the beatmap is submitted.
+    ///
+    /// The client does not distinguish between pending, WIP and graveyarded
+    /// beatmaps, which are all given as [`Approval::Pending`].
+    ///
+    /// [`Approval::Pending`]: ../enum.Approval.html#variant.Pending
+    pub approved: Option<Approval>,
+    pub circle_count: i16,
+    pub slider_count: i16,
+    pub spinner_count: i16,
+    pub last_update: String,
+    pub diff_approach: f64,
+    pub diff_size: f64,
+    pub diff_drain: f64,
+    pub diff_overall: f64,
+    pub slider_velocity: f64,
+    /// The star ratings cached by the client for each mode and mod
+    /// combination.
+    ///
+    /// Only stored by clients since 2014-06-09.
+    pub star_ratings: Vec<StarRating>,
+    /// The drain time in seconds.
+    pub hit_length: i64,
+    /// The total time in milliseconds.
+    pub total_time: i64,
+    /// The time in milliseconds that the song preview starts at.
+    pub preview_time: i32,
+    pub timing_points: Vec<TimingPoint>,
+    pub beatmap_id: i64,
+    pub beatmapset_id: i64,
+    pub thread_id: i32,
+    /// The best grades achieved per mode, as ranks like `"S"` or `"XH"`.
+    pub grade_standard: Option<String>,
+    pub grade_taiko: Option<String>,
+    pub grade_catch: Option<String>,
+    pub grade_mania: Option<String>,
+    pub local_offset: i16,
+    pub stack_leniency: f32,
+    pub mode: i64,
+    pub source: String,
+    /// A list of tags, separated by spaces.
+    pub tags: String,
+    pub online_offset: i16,
+    pub title_font: String,
+    pub unplayed: bool,
+    pub last_played: Option<String>,
+    pub osz2: bool,
+    pub folder_name: String,
+    pub last_checked: Option<String>,
+    pub ignore_beatmap_sound: bool,
+    pub ignore_beatmap_skin: bool,
+    pub disable_storyboard: bool,
+    pub disable_video: bool,
+    pub visual_override: bool,
+    pub mania_scroll_speed: u8,
+}
+
+impl BeatmapEntry {
+    /// The cached star rating for a mode and mod combination.
+    ///
+    /// The client only caches ratings for combinations of the mods that
+    /// affect difficulty, so other mods should be removed first.
+    pub fn stars(&self, mode: PlayMode, mods: Mods) -> Option<f64> {
+        self.star_ratings.iter()
+            .find(|rating| rating.mode == mode && rating.mods == mods)
+            .map(|rating| rating.stars)
+    }
+
+    fn read<R: Read>(reader: &mut Reader<R>, version: i32) -> Result<BeatmapEntry> {
+        if version < VERSION_NO_ENTRY_SIZE {
+            reader.i32()?;
+        }
+
+        let artist = reader.string()?;
+        let artist_unicode = reader.string()?;
+        let title = reader.string()?;
+        let title_unicode = reader.string()?;
+        let creator = reader.string()?;
+        let difficulty = reader.string()?;
+        let audio_file = reader.string()?;
+        let file_md5 = reader.string()?;
+        let file_name = reader.string()?;
+        let approved = match reader.u8()? {
+            2 => Some(Approval::Pending),
+            4 => Some(Approval::Ranked),
+            5 => Some(Approval::Approved),
+            6 => Some(Approval::Qualified),
+            7 => Some(Approval::Loved),
+            _ => None,
+        };
+        let circle_count = reader.i16()?;
+        let slider_count = reader.i16()?;
+        let spinner_count = reader.i16()?;
+        let last_update = reader.date()?;
+
+        let mut settings = [0.0; 4];
+
+        for setting in &mut settings {
+            *setting = if version < VERSION_FLOAT_DIFFICULTY {
+                f64::from(reader.u8()?)
+            } else {
+                f64::from(reader.f32()?)
+            };
+        }
+
+        let slider_velocity = reader.f64()?;
+        let mut star_ratings = vec![];
+
+        if version >= VERSION_FLOAT_DIFFICULTY {
+            let modes = [
+                PlayMode::Standard,
+                PlayMode::Taiko,
+                PlayMode::CatchTheBeat,
+                PlayMode::Mania,
+            ];
+
+            for &mode in &modes {
+                let count = reader.i32()?;
+
+                for _ in 0..count {
+                    star_ratings.push(read_star_rating(reader, mode)?);
+                }
+            }
+        }
+
+        let hit_length = i64::from(reader.i32()?);
+        let total_time = i64::from(reader.i32()?);
+        let preview_time = reader.i32()?;
+
+        let count = reader.i32()?;
+        let mut timing_points = Vec::with_capacity(count.max(0) as usize);
+
+        for _ in 0..count {
+            timing_points.push(TimingPoint {
+                beat_length: reader.f64()?,
+                offset: reader.f64()?,
+                uninherited: reader.bool()?,
+            });
+        }
+
+        let beatmap_id = i64::from(reader.i32()?);
+        let beatmapset_id = i64::from(reader.i32()?);
+        let thread_id = reader.i32()?;
+        let grade_standard = grade(reader.u8()?);
+        let grade_taiko = grade(reader.u8()?);
+        let grade_catch = grade(reader.u8()?);
+        let grade_mania = grade(reader.u8()?);
+        let local_offset = reader.i16()?;
+        let stack_leniency = reader.f32()?;
+        let mode = i64::from(reader.u8()?);
+        let source = reader.string()?;
+        let tags = reader.string()?;
+        let online_offset = reader.i16()?;
+        let title_font = reader.string()?;
+        let unplayed = reader.bool()?;
+        let last_played = reader.optional_date()?;
+        let osz2 = reader.bool()?;
+        let folder_name = reader.string()?;
+        let last_checked = reader.optional_date()?;
+        let ignore_beatmap_sound = reader.bool()?;
+        let ignore_beatmap_skin = reader.bool()?;
+        let disable_storyboard = reader.bool()?;
+        let disable_video = reader.bool()?;
+        let visual_override = reader.bool()?;
+
+        if version < VERSION_FLOAT_DIFFICULTY {
+            reader.i16()?;
+        }
+
+        // Last modification time, which duplicates `last_update`.
+        reader.i32()?;
+        let mania_scroll_speed = reader.u8()?;
+
+        Ok(BeatmapEntry {
+            artist,
+            artist_unicode,
+            title,
+            title_unicode,
+            creator,
+            version: difficulty,
+            audio_file,
+            file_md5,
+            file_name,
+            approved,
+            circle_count,
+            slider_count,
+            spinner_count,
+            last_update,
+            diff_approach: settings[0],
+            diff_size: settings[1],
+            diff_drain: settings[2],
+            diff_overall: settings[3],
+            slider_velocity,
+            star_ratings,
+            hit_length,
+            total_time,
+            preview_time,
+            timing_points,
+            beatmap_id,
+            beatmapset_id,
+            thread_id,
+            grade_standard,
+            grade_taiko,
+            grade_catch,
+            grade_mania,
+            local_offset,
+            stack_leniency,
+            mode,
+            source,
+            tags,
+            online_offset,
+            title_font,
+            unplayed,
+            last_played,
+            osz2,
+            folder_name,
+            last_checked,
+            ignore_beatmap_sound,
+            ignore_beatmap_skin,
+            disable_storyboard,
+            disable_video,
+            visual_override,
+            mania_scroll_speed,
+        })
+    }
+}
+
+/// A star rating cached by the client.
+#[derive(Clone, Copy, Debug)]
+pub struct StarRating {
+    pub mode: PlayMode,
+    pub mods: Mods,
+    pub stars: f64,
+}
+
+/// A timing point of a beatmap in the client's library.
+#[derive(Clone, Copy, Debug)]
+pub struct TimingPoint {
+    /// The duration of a beat in milliseconds for uninherited points, or the
+    /// negative inverse slider velocity multiplier as a percentage for
+    /// inherited points.
+    pub beat_length: f64,
+    /// The time in milliseconds that the timing point starts at.
+    pub offset: f64,
+    pub uninherited: bool,
+}
+
+/// Reads a star rating, stored as an integer-double pair, or integer-float
+/// pair in newer versions, with each value preceded by its type.
+fn read_star_rating<R: Read>(reader: &mut Reader<R>, mode: PlayMode) -> Result<StarRating> {
+    if reader.u8()? != 0x08 {
+        return Err(Error::Parse("invalid star rating mods marker".to_owned()));
+    }
+
+    let mods = Mods::from_bits_truncate(i64::from(reader.i32()?));
+
+    let stars = match reader.u8()? {
+        0x0c => f64::from(reader.f32()?),
+        0x0d => reader.f64()?,
+        other => {
+            return Err(Error::Parse(format!("invalid star rating marker 0x{:02x}", other)));
+        },
+    };
+
+    Ok(StarRating { mode, mods, stars })
+}
+
+fn grade(value: u8) -> Option<String> {
+    let grade = match value {
+        0 => "XH",
+        1 => "SH",
+        2 => "X",
+        3 => "S",
+        4 => "A",
+        5 => "B",
+        6 => "C",
+        7 => "D",
+        _ => return None,
+    };
+
+    Some(grade.to_owned())
+}
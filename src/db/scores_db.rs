@@ -0,0 +1,193 @@
+use std::io::Read;
+use super::Reader;
+use ::{Error, Mods, PlayMode, Result};
+
+/// The first version storing online score IDs.
+const VERSION_SCORE_ID: i32 = 20_121_008;
+
+/// The first version storing online score IDs as 64-bit integers.
+const VERSION_LONG_SCORE_ID: i32 = 20_140_721;
+
+/// The mod bit set by Target Practice, which is not part of [`Mods`].
+///
+/// [`Mods`]: ../struct.Mods.html
+const TARGET_PRACTICE: i32 = 1 << 23;
+
+/// The contents of a `scores.db` file: the scores set locally, grouped by
+/// beatmap.
+#[derive(Clone, Debug)]
+pub struct ScoresDb {
+    /// The client version that wrote the file, as a `YYYYMMDD` date.
+    pub version: i32,
+    pub beatmaps: Vec<BeatmapScores>,
+}
+
+impl ScoresDb {
+    /// Reads a `scores.db` file.
+    ///
+    /// Returns [`Error::Parse`] if the file is malformed, or [`Error::Io`] if
+    /// it ends early.
+    ///
+    /// [`Error::Io`]: ../error/enum.Error.html#variant.Io
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn read<R: Read>(reader: R) -> Result<ScoresDb> {
+        let mut reader = Reader::new(reader);
+
+        let version = reader.i32()?;
+        let count = reader.i32()?;
+        let mut beatmaps = Vec::with_capacity(count.max(0) as usize);
+
+        for _ in 0..count {
+            let beatmap_md5 = reader.string()?;
+            let count = reader.i32()?;
+            let mut scores = Vec::with_capacity(count.max(0) as usize);
+
+            for _ in 0..count {
+                scores.push(LocalScore::read(&mut reader)?);
+            }
+
+            beatmaps.push(BeatmapScores { beatmap_md5, scores });
+        }
+
+        Ok(ScoresDb { version, beatmaps })
+    }
+
+    /// The local scores on a beatmap, by the MD5 hash of its `.osu` file,
+    /// such as [`Beatmap::file_md5`].
+    ///
+    /// [`Beatmap::file_md5`]: ../struct.Beatmap.html#structfield.file_md5
+    pub fn scores(&self, beatmap_md5: &str) -> &[LocalScore] {
+        self.beatmaps.iter()
+            .find(|beatmap| beatmap.beatmap_md5.eq_ignore_ascii_case(beatmap_md5))
+            .map_or(&[], |beatmap| &beatmap.scores[..])
+    }
+}
+
+/// The local scores on a beatmap.
+#[derive(Clone, Debug)]
+pub struct BeatmapScores {
+    /// The MD5 hash of the beatmap's `.osu` file.
+    pub beatmap_md5: String,
+    pub scores: Vec<LocalScore>,
+}
+
+/// A score set locally.
+///
+/// Field names and types match those of [`Performance`] where they
+/// correspond.
+///
+/// [`Performance`]: ../struct.Performance.html
+#[derive(Clone, Debug)]
+pub struct LocalScore {
+    pub mode: PlayMode,
+    /// The client version that set the score, as a `YYYYMMDD` date.
+    pub version: i32,
+    pub beatmap_md5: String,
+    pub username: String,
+    pub replay_md5: String,
+    pub count_300: i64,
+    pub count_100: i64,
+    pub count_50: i64,
+    pub count_geki: i64,
+    pub count_katu: i64,
+    pub count_miss: i64,
+    pub score: i64,
+    pub max_combo: i64,
+    pub perfect: bool,
+    pub enabled_mods: Mods,
+    pub date: String,
+    /// The LZMA-compressed replay frames, if stored alongside the score.
+    ///
+    /// The client stores replays as separate files, so this is usually
+    /// `None` for scores in `scores.db`, and set for those read with
+    /// [`from_replay`].
+    ///
+    /// [`from_replay`]: #method.from_replay
+    pub replay: Option<Vec<u8>>,
+    /// The ID of the score online, or `0` if it was not submitted.
+    pub online_score_id: i64,
+    /// The accuracy of all hits, when played with Target Practice.
+    pub target_practice_accuracy: Option<f64>,
+}
+
+impl LocalScore {
+    /// Reads a replay (`.osr`) file, which stores its score in the same
+    /// layout as `scores.db` followed by the compressed frames.
+    ///
+    /// Returns [`Error::Parse`] if the file is malformed, or [`Error::Io`] if
+    /// it ends early.
+    ///
+    /// [`Error::Io`]: ../error/enum.Error.html#variant.Io
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn from_replay<R: Read>(reader: R) -> Result<LocalScore> {
+        LocalScore::read(&mut Reader::new(reader))
+    }
+
+    fn read<R: Read>(reader: &mut Reader<R>) -> Result<LocalScore> {
+        let mode = match reader.u8()? {
+            0 => PlayMode::Standard,
+            1 => PlayMode::Taiko,
+            2 => PlayMode::CatchTheBeat,
+            3 => PlayMode::Mania,
+            other => return Err(Error::Parse(format!("invalid mode {}", other))),
+        };
+        let version = reader.i32()?;
+        let beatmap_md5 = reader.string()?;
+        let username = reader.string()?;
+        let replay_md5 = reader.string()?;
+        let count_300 = i64::from(reader.i16()? as u16);
+        let count_100 = i64::from(reader.i16()? as u16);
+        let count_50 = i64::from(reader.i16()? as u16);
+        let count_geki = i64::from(reader.i16()? as u16);
+        let count_katu = i64::from(reader.i16()? as u16);
+        let count_miss = i64::from(reader.i16()? as u16);
+        let score = i64::from(reader.i32()?);
+        let max_combo = i64::from(reader.i16()? as u16);
+        let perfect = reader.bool()?;
+        let mods = reader.i32()?;
+        // Life bar graph, which is only stored in replay files.
+        reader.string()?;
+        let date = reader.date()?;
+
+        let replay = match reader.i32()? {
+            len if len > 0 => Some(reader.bytes(len as usize)?),
+            _ => None,
+        };
+
+        let online_score_id = if version >= VERSION_LONG_SCORE_ID {
+            reader.i64()?
+        } else if version >= VERSION_SCORE_ID {
+            i64::from(reader.i32()?)
+        } else {
+            0
+        };
+
+        let target_practice_accuracy = if mods & TARGET_PRACTICE != 0 {
+            Some(reader.f64()?)
+        } else {
+            None
+        };
+
+        Ok(LocalScore {
+            mode,
+            version,
+            beatmap_md5,
+            username,
+            replay_md5,
+            count_300,
+            count_100,
+            count_50,
+            count_geki,
+            count_katu,
+            count_miss,
+            score,
+            max_combo,
+            perfect,
+            enabled_mods: Mods::from_bits_truncate(i64::from(mods)),
+            date,
+            replay,
+            online_score_id,
+            target_practice_accuracy,
+        })
+    }
+}
@@ -0,0 +1,177 @@
+use std::cmp::Ordering;
+use ::convert::{self, CatchObject};
+use ::osz::Difficulty;
+use ::pp::{self, CatchAttributes};
+use ::Mods;
+use super::{weighted_sum, Sections, Strain};
+
+const STAR_SCALING_FACTOR: f64 = 0.145;
+const SECTION_LENGTH: f64 = 750.0;
+const DECAY_WEIGHT: f64 = 0.94;
+
+const MOVEMENT_MULTIPLIER: f64 = 850.0;
+const MOVEMENT_DECAY_BASE: f64 = 0.2;
+
+/// The width of the catcher at a circle size of `5`, in osu!pixels.
+const CATCHER_SIZE: f64 = 106.75;
+/// The share of the catcher's width that players are assumed to catch with.
+const ALLOWED_CATCH_RANGE: f64 = 0.8;
+/// The radius that positions are scaled to, so that they do not depend on
+/// circle size.
+const NORMALIZED_RADIUS: f64 = 41.0;
+/// How far from a fruit a player may be and still be in position.
+const POSITIONING_ERROR: f64 = 16.0;
+const DIRECTION_CHANGE_BONUS: f64 = 12.5;
+/// How close to a hyperdash, in osu!pixels, a movement is given a bonus.
+const HYPERDASH_LENIENCY: f64 = 10.0;
+
+/// Calculates the star rating of a difficulty in catch for a set of mods,
+/// converting it if it was not made for catch.
+///
+/// The approach rate is given unmodded, as [`pp::calculate`] expects.
+///
+/// [`pp::calculate`]: ../pp/fn.calculate.html
+pub fn catch(difficulty: &Difficulty, mods: Mods) -> CatchAttributes {
+    let clock_rate = pp::clock_rate(mods);
+    let scale = 1.0 - 0.7 * (pp::circle_size(difficulty.diff_size, mods) - 5.0) / 5.0;
+    let half_catcher_width = CATCHER_SIZE * scale / 2.0;
+
+    // Only fruits and droplets add to the combo and need to be moved to.
+    let mut objects = convert::catch(difficulty, mods).into_iter()
+        .filter(CatchObject::adds_combo)
+        .collect::<Vec<_>>();
+    objects.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+    let hyperdashes = hyperdashes(&objects, half_catcher_width);
+    let mut movement = Movement {
+        strain: Strain::new(MOVEMENT_MULTIPLIER, MOVEMENT_DECAY_BASE),
+        scaling_factor: NORMALIZED_RADIUS / (half_catcher_width * ALLOWED_CATCH_RANGE),
+        last_player_position: None,
+        last_distance_moved: 0.0,
+    };
+
+    if let Some(first) = difficulty.hit_objects.first() {
+        let mut sections = Sections::new(first.time, SECTION_LENGTH, clock_rate);
+
+        for idx in 1..objects.len() {
+            let (last, current) = (&objects[idx - 1], &objects[idx]);
+            let delta_time = (current.time - last.time) / clock_rate;
+
+            sections.advance(current.time, &mut [&mut movement.strain]);
+
+            let value = movement.value(last, current, delta_time, hyperdashes[idx - 1]);
+            movement.strain.process(current.time, delta_time, value);
+        }
+
+        Sections::finish(&mut [&mut movement.strain]);
+    }
+
+    CatchAttributes {
+        stars: weighted_sum(movement.strain.peaks.clone(), DECAY_WEIGHT).sqrt() * STAR_SCALING_FACTOR,
+        approach_rate: difficulty.diff_approach,
+        max_combo: objects.len() as i64,
+    }
+}
+
+/// A hyperdash to the next object, or how much further the next object
+/// could be without one.
+#[derive(Clone, Copy, Debug)]
+enum Hyperdash {
+    To,
+    Distance(f64),
+}
+
+/// Finds the objects that need a hyperdash to reach the next object, for a
+/// catcher that moves one osu!pixel per millisecond.
+fn hyperdashes(objects: &[CatchObject], half_catcher_width: f64) -> Vec<Hyperdash> {
+    let mut hyperdashes = vec![Hyperdash::Distance(0.0); objects.len()];
+    let mut last_direction = 0;
+    let mut last_excess = half_catcher_width;
+
+    for idx in 1..objects.len() {
+        let (current, next) = (&objects[idx - 1], &objects[idx]);
+        let direction = if next.x > current.x { 1 } else { -1 };
+        // A quarter of a frame of leniency, as in the game.
+        let time_to_next = next.time - current.time - 1000.0 / 60.0 / 4.0;
+        let distance_to_next = (next.x - current.x).abs()
+            - if direction == last_direction { last_excess } else { half_catcher_width };
+        let distance_to_hyperdash = time_to_next - distance_to_next;
+
+        if distance_to_hyperdash < 0.0 {
+            hyperdashes[idx - 1] = Hyperdash::To;
+            last_excess = half_catcher_width;
+        } else {
+            hyperdashes[idx - 1] = Hyperdash::Distance(distance_to_hyperdash);
+            last_excess = distance_to_hyperdash.min(half_catcher_width);
+        }
+
+        last_direction = direction;
+    }
+
+    hyperdashes
+}
+
+/// The strain of moving the catcher, following where a player would be.
+struct Movement {
+    strain: Strain,
+    scaling_factor: f64,
+    last_player_position: Option<f64>,
+    last_distance_moved: f64,
+}
+
+impl Movement {
+    #[allow(clippy::manual_clamp)]
+    fn value(&mut self, last: &CatchObject, current: &CatchObject, delta_time: f64, hyperdash: Hyperdash) -> f64 {
+        let position = current.x * self.scaling_factor;
+        let last_player_position = self.last_player_position.unwrap_or(last.x * self.scaling_factor);
+        let mut player_position = last_player_position
+            .max(position - (NORMALIZED_RADIUS - POSITIONING_ERROR))
+            .min(position + (NORMALIZED_RADIUS - POSITIONING_ERROR));
+        let distance_moved = player_position - last_player_position;
+
+        // Strain intervals are capped at the equivalent of streaming at 600
+        // BPM.
+        let strain_time = delta_time.max(25.0);
+        let sqrt_strain = strain_time.sqrt();
+        let mut distance_addition = distance_moved.abs().powf(1.3) / 500.0;
+        let mut bonus = 0.0;
+
+        let (distance_to_hyperdash, is_hyperdash) = match hyperdash {
+            Hyperdash::To => (0.0, true),
+            Hyperdash::Distance(distance) => (distance, false),
+        };
+        let almost_hyperdash = distance_to_hyperdash <= HYPERDASH_LENIENCY;
+
+        if distance_moved.abs() > 0.1 {
+            if self.last_distance_moved.abs() > 0.1 && (distance_moved > 0.0) != (self.last_distance_moved > 0.0) {
+                let bonus_factor = distance_moved.abs().min(POSITIONING_ERROR) / POSITIONING_ERROR;
+                distance_addition += DIRECTION_CHANGE_BONUS / sqrt_strain * bonus_factor;
+
+                // Direction changes that are almost hyperdashes are harder.
+                if almost_hyperdash {
+                    bonus = 0.3 * bonus_factor;
+                }
+            }
+
+            // Every movement is worth something, which gives streams weight.
+            distance_addition += 7.5 * distance_moved.abs().min(NORMALIZED_RADIUS * 2.0)
+                / (NORMALIZED_RADIUS * 6.0) / sqrt_strain;
+        }
+
+        if almost_hyperdash {
+            if is_hyperdash {
+                // A hyperdash always lands the catcher in place.
+                player_position = position;
+            } else {
+                bonus += 1.0;
+            }
+
+            distance_addition *= 1.0 + bonus * (HYPERDASH_LENIENCY - distance_to_hyperdash) / HYPERDASH_LENIENCY;
+        }
+
+        self.last_player_position = Some(player_position);
+        self.last_distance_moved = distance_moved;
+
+        distance_addition / strain_time
+    }
+}
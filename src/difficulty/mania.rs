@@ -0,0 +1,100 @@
+use ::convert::{self, ManiaObject};
+use ::osz::Difficulty;
+use ::pp::{self, ManiaAttributes};
+use ::Mods;
+use super::{weighted_sum, Sections, Strain, DECAY_WEIGHT, SECTION_LENGTH};
+
+const STAR_SCALING_FACTOR: f64 = 0.018;
+const INDIVIDUAL_DECAY_BASE: f64 = 0.125;
+const OVERALL_DECAY_BASE: f64 = 0.3;
+
+/// Calculates the star rating of a difficulty in mania for a set of mods,
+/// converting it if it was not made for mania.
+///
+/// A strain is measured for each column as well as over all of them, and
+/// each section's peak is the overall strain's plus the highest column's.
+/// Key mods choose the number of keys of converted beatmaps. The overall
+/// difficulty is given unmodded, as [`pp::calculate`] expects.
+///
+/// [`pp::calculate`]: ../pp/fn.calculate.html
+pub fn mania(difficulty: &Difficulty, mods: Mods) -> ManiaAttributes {
+    let clock_rate = pp::clock_rate(mods);
+    let keys = convert::mania_keys(difficulty, mods) as usize;
+    let notes = convert::mania(difficulty, mods);
+
+    let mut overall = Strain::new(1.0, OVERALL_DECAY_BASE);
+    let mut individual = (0..keys).map(|_| Strain::new(1.0, INDIVIDUAL_DECAY_BASE)).collect::<Vec<_>>();
+    let mut hold_end_times = vec![0.0; keys];
+
+    if let Some(first) = notes.first() {
+        let mut sections = Sections::new(first.time, SECTION_LENGTH, clock_rate);
+
+        for pair in notes.windows(2) {
+            let (last, current) = (&pair[0], &pair[1]);
+            let delta_time = (current.time - last.time) / clock_rate;
+            let end_time = current.end_time.unwrap_or(current.time);
+            let column = current.column as usize;
+
+            {
+                let mut strains = individual.iter_mut().collect::<Vec<_>>();
+                strains.push(&mut overall);
+                sections.advance(current.time, &mut strains);
+            }
+
+            overall.process(current.time, delta_time, overall_value(current, end_time, &hold_end_times));
+
+            // Notes are slightly harder while another column is held.
+            let held = hold_end_times.iter().any(|&hold_end_time| hold_end_time > end_time);
+
+            for (idx, strain) in individual.iter_mut().enumerate() {
+                let value = match (idx == column, held) {
+                    (false, _) => 0.0,
+                    (true, false) => 2.0,
+                    (true, true) => 2.5,
+                };
+
+                strain.process(current.time, delta_time, value);
+            }
+
+            hold_end_times[column] = end_time;
+        }
+
+        let mut strains = individual.iter_mut().collect::<Vec<_>>();
+        strains.push(&mut overall);
+        Sections::finish(&mut strains);
+    }
+
+    let peaks = overall.peaks.iter().enumerate().map(|(idx, overall_peak)| {
+        individual.iter().map(|strain| strain.peaks[idx] + overall_peak).fold(0.0, f64::max)
+    }).collect::<Vec<_>>();
+
+    ManiaAttributes {
+        stars: weighted_sum(peaks, DECAY_WEIGHT) * STAR_SCALING_FACTOR,
+        overall_difficulty: difficulty.diff_overall,
+        note_count: notes.len() as i64,
+    }
+}
+
+/// The strain of a note over all columns, which is higher while other
+/// columns are held, and for hold notes that end apart from the others.
+fn overall_value(current: &ManiaObject, end_time: f64, hold_end_times: &[f64]) -> f64 {
+    let mut hold_factor = 1.0;
+    let mut hold_addition = 0.0;
+
+    for &hold_end_time in hold_end_times {
+        if current.time < hold_end_time && end_time > hold_end_time {
+            hold_addition = 1.0;
+        }
+
+        // Releasing several hold notes at once is as easy as releasing one.
+        if end_time == hold_end_time {
+            hold_addition = 0.0;
+        }
+
+        if hold_end_time > end_time {
+            hold_factor = 1.25;
+        }
+    }
+
+    (1.0 + hold_addition) * hold_factor
+}
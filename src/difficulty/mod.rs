@@ -0,0 +1,199 @@
+//! Offline star rating calculation for parsed `.osu` difficulties.
+//!
+//! These follow the difficulty calculators that were live alongside the
+//! formulas in the [`pp`] module, and give [`DifficultyAttributes`] that can
+//! be passed straight to [`pp::calculate`]. Each mode's strain is measured in
+//! sections of 400ms, or 750ms in catch, and the peak strains of all sections
+//! are summed with decreasing weights.
+//!
+//! The algorithms are the versions of early 2019, the same as the [`pp`]
+//! formulas, with the angle bonuses of the February 2019 update in
+//! osu!standard. Star ratings have been changed since, so the API's
+//! [`Beatmap::difficulty_rating`] of a ranked beatmap, which is always from
+//! the current version, can differ from what these give.
+//!
+//! Taiko, catch and mania are rated from the objects that the [`convert`]
+//! module gives, so standard beatmaps are rated as the converts that
+//! [`include_converted`] returns.
+//!
+//! Requires the `osz-support` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use osu::difficulty;
+//! use osu::osz::Difficulty;
+//! use osu::{Mods, PlayMode};
+//! use std::fs;
+//!
+//! let bytes = fs::read("Horizon - Flare (Jade Harley) [Normal].osu").unwrap();
+//! let normal = Difficulty::parse("Normal.osu", &bytes).unwrap();
+//!
+//! let attributes = difficulty::calculate(&normal, PlayMode::Mania, Mods::KEY4).unwrap();
+//! println!("{} max combo", attributes.max_combo());
+//! ```
+//!
+//! [`Beatmap::difficulty_rating`]: ../struct.Beatmap.html#structfield.difficulty_rating
+//! [`DifficultyAttributes`]: ../pp/enum.DifficultyAttributes.html
+//! [`convert`]: ../convert/index.html
+//! [`include_converted`]: ../builder/struct.GetBeatmapsRequest.html#method.include_converted
+//! [`pp`]: ../pp/index.html
+//! [`pp::calculate`]: ../pp/fn.calculate.html
+
+mod catch;
+mod mania;
+mod standard;
+mod taiko;
+
+pub use self::catch::catch;
+pub use self::mania::mania;
+pub use self::standard::standard;
+pub use self::taiko::taiko;
+
+use std::cmp::Ordering;
+use ::osz::Difficulty;
+use ::pp::DifficultyAttributes;
+use ::{Mods, PlayMode};
+
+/// The length of a strain section in milliseconds, before the clock rate.
+const SECTION_LENGTH: f64 = 400.0;
+
+/// The weight of each section's peak strain relative to the one before it.
+const DECAY_WEIGHT: f64 = 0.9;
+
+/// Calculates the difficulty attributes of a difficulty in a mode.
+///
+/// Standard difficulties are converted to every other mode, while those made
+/// for another mode are only rated in their own, and give `None` for the
+/// rest, as with [`include_converted`].
+///
+/// [`include_converted`]: ../builder/struct.GetBeatmapsRequest.html#method.include_converted
+pub fn calculate(difficulty: &Difficulty, mode: PlayMode, mods: Mods) -> Option<DifficultyAttributes> {
+    let own_mode = match difficulty.mode {
+        1 => PlayMode::Taiko,
+        2 => PlayMode::CatchTheBeat,
+        3 => PlayMode::Mania,
+        _ => PlayMode::Standard,
+    };
+
+    if own_mode != PlayMode::Standard && own_mode != mode {
+        return None;
+    }
+
+    Some(match mode {
+        PlayMode::CatchTheBeat => DifficultyAttributes::CatchTheBeat(catch(difficulty, mods)),
+        PlayMode::Mania => DifficultyAttributes::Mania(mania(difficulty, mods)),
+        PlayMode::Standard => DifficultyAttributes::Standard(standard(difficulty, mods)),
+        PlayMode::Taiko => DifficultyAttributes::Taiko(taiko(difficulty, mods)),
+    })
+}
+
+/// A strain that builds up with each object and decays over time.
+struct Strain {
+    multiplier: f64,
+    decay_base: f64,
+    current: f64,
+    section_peak: f64,
+    peaks: Vec<f64>,
+    /// The start time of the last object processed.
+    previous_time: Option<f64>,
+}
+
+impl Strain {
+    fn new(multiplier: f64, decay_base: f64) -> Strain {
+        Strain {
+            multiplier,
+            decay_base,
+            current: 1.0,
+            section_peak: 1.0,
+            peaks: vec![],
+            previous_time: None,
+        }
+    }
+
+    fn decay(&self, ms: f64) -> f64 {
+        self.decay_base.powf(ms / 1000.0)
+    }
+
+    /// Decays the strain over the time since the last object, and adds the
+    /// strain of an object.
+    fn process(&mut self, start_time: f64, delta_time: f64, value: f64) {
+        self.current *= self.decay(delta_time);
+        self.current += value * self.multiplier;
+        self.section_peak = self.section_peak.max(self.current);
+        self.previous_time = Some(start_time);
+    }
+
+    fn save_peak(&mut self) {
+        if self.previous_time.is_some() {
+            self.peaks.push(self.section_peak);
+        }
+    }
+
+    /// Starts a new section, with its peak being the strain decayed to the
+    /// section's start.
+    fn start_section(&mut self, start: f64) {
+        if let Some(time) = self.previous_time {
+            self.section_peak = self.current * self.decay(start - time);
+        }
+    }
+
+    fn difficulty(&self) -> f64 {
+        weighted_sum(self.peaks.clone(), DECAY_WEIGHT)
+    }
+}
+
+/// Sums peak strains from the highest, with each weighted by `decay_weight`
+/// relative to the one before it.
+fn weighted_sum(mut peaks: Vec<f64>, decay_weight: f64) -> f64 {
+    peaks.sort_by(|a, b| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    let mut weight = 1.0;
+    let mut difficulty = 0.0;
+
+    for peak in peaks {
+        difficulty += peak * weight;
+        weight *= decay_weight;
+    }
+
+    difficulty
+}
+
+/// The sections that strains are measured in.
+struct Sections {
+    length: f64,
+    end: f64,
+}
+
+impl Sections {
+    /// Starts sections of a length in milliseconds from the first object's
+    /// time.
+    fn new(first_time: f64, length: f64, clock_rate: f64) -> Sections {
+        let length = length * clock_rate;
+
+        Sections {
+            length,
+            end: (first_time / length).ceil() * length,
+        }
+    }
+
+    /// Saves the peak of each section that ends before an object, before
+    /// the object is processed.
+    fn advance(&mut self, start_time: f64, strains: &mut [&mut Strain]) {
+        while start_time > self.end {
+            for strain in strains.iter_mut() {
+                strain.save_peak();
+                strain.start_section(self.end);
+            }
+
+            self.end += self.length;
+        }
+    }
+
+    /// Saves the peak of the last section.
+    fn finish(strains: &mut [&mut Strain]) {
+        for strain in strains.iter_mut() {
+            strain.save_peak();
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::f64::consts::PI;
+use ::hitobject::{HitObject, Position};
+use ::osz::Difficulty;
+use ::pp::{self, StandardAttributes};
+use ::slider::{Slider, SliderEventKind};
+use ::Mods;
+use super::{Sections, Strain, SECTION_LENGTH};
+
+const DIFFICULTY_MULTIPLIER: f64 = 0.0675;
+/// The radius that distances are scaled to, so that they do not depend on
+/// circle size.
+const NORMALIZED_RADIUS: f64 = 52.0;
+
+const AIM_MULTIPLIER: f64 = 26.25;
+const AIM_DECAY_BASE: f64 = 0.15;
+const AIM_ANGLE_BONUS_BEGIN: f64 = PI / 3.0;
+const AIM_TIMING_THRESHOLD: f64 = 107.0;
+
+const SPEED_MULTIPLIER: f64 = 1400.0;
+const SPEED_DECAY_BASE: f64 = 0.3;
+const SPEED_ANGLE_BONUS_BEGIN: f64 = 5.0 * PI / 6.0;
+const SINGLE_SPACING_THRESHOLD: f64 = 125.0;
+const MIN_SPEED_BONUS: f64 = 75.0;
+const MAX_SPEED_BONUS: f64 = 45.0;
+const SPEED_BALANCING_FACTOR: f64 = 40.0;
+
+/// An object as played, with stacking and HardRock applied.
+struct Object {
+    start_time: f64,
+    is_spinner: bool,
+    position: Position,
+    /// Where the cursor is at the end of the object, following a slider
+    /// lazily within its follow circle.
+    end_position: Position,
+    /// The distance the cursor travels within a slider.
+    travel_distance: f64,
+}
+
+/// An object with its distances and times from the object before it.
+struct DifficultyObject {
+    start_time: f64,
+    is_spinner: bool,
+    jump_distance: f64,
+    travel_distance: f64,
+    /// The angle between the last two movements, if there are two.
+    angle: Option<f64>,
+    delta_time: f64,
+    /// The delta time, but at least 50ms.
+    strain_time: f64,
+}
+
+/// Calculates the star rating of an osu!standard difficulty for a set of
+/// mods, along with its aim and speed components and maximum combo.
+///
+/// The difficulty's approach rate and overall difficulty are given
+/// unmodded, as [`pp::calculate`] expects.
+///
+/// [`pp::calculate`]: ../pp/fn.calculate.html
+pub fn standard(difficulty: &Difficulty, mods: Mods) -> StandardAttributes {
+    let clock_rate = pp::clock_rate(mods);
+    let radius = 32.0 * (1.0 - 0.7 * (pp::circle_size(difficulty.diff_size, mods) - 5.0) / 5.0);
+    let sliders = difficulty.sliders();
+    let positions = difficulty.stacked_positions(mods);
+    let hard_rock = mods.contains(Mods::HARD_ROCK);
+
+    let objects = difficulty.hit_objects.iter().enumerate().map(|(idx, object)| {
+        let position = positions[idx];
+        let mut played = Object {
+            start_time: object.time,
+            is_spinner: object.is_spinner(),
+            position,
+            end_position: position,
+            travel_distance: 0.0,
+        };
+
+        if let Some(ref slider) = sliders[idx] {
+            // The offset of the stacked position from the file's position.
+            let offset = position - flip(object.position, hard_rock);
+            let path_position = |time: f64| {
+                let mut progress = (time - slider.start_time) / slider.span_duration();
+
+                progress = if progress % 2.0 >= 1.0 { 1.0 - progress % 1.0 } else { progress % 1.0 };
+
+                flip(slider.path.position_at(progress), hard_rock) + offset
+            };
+
+            lazy_travel(&mut played, slider, radius * 3.0, path_position);
+        }
+
+        played
+    }).collect::<Vec<_>>();
+
+    let mut scaling_factor = NORMALIZED_RADIUS / radius;
+
+    if radius < 30.0 {
+        scaling_factor *= 1.0 + (30.0 - radius).min(5.0) / 50.0;
+    }
+
+    let difficulty_objects = (1..objects.len()).map(|idx| {
+        let current = &objects[idx];
+        let last = &objects[idx - 1];
+        let delta_time = (current.start_time - last.start_time) / clock_rate;
+
+        let angle = if idx >= 2 {
+            let last_last = &objects[idx - 2];
+            let v1 = last_last.end_position - last.position;
+            let v2 = current.position - last.end_position;
+            let det = v1.x * v2.y - v1.y * v2.x;
+
+            Some(det.atan2(v1.dot(v2)).abs())
+        } else {
+            None
+        };
+
+        DifficultyObject {
+            start_time: current.start_time,
+            is_spinner: current.is_spinner,
+            jump_distance: if current.is_spinner {
+                0.0
+            } else {
+                (current.position * scaling_factor - last.end_position * scaling_factor).length()
+            },
+            travel_distance: last.travel_distance * scaling_factor,
+            angle,
+            delta_time,
+            strain_time: delta_time.max(50.0),
+        }
+    }).collect::<Vec<_>>();
+
+    let mut aim = Strain::new(AIM_MULTIPLIER, AIM_DECAY_BASE);
+    let mut speed = Strain::new(SPEED_MULTIPLIER, SPEED_DECAY_BASE);
+
+    if let Some(first) = objects.first() {
+        let mut sections = Sections::new(first.start_time, SECTION_LENGTH, clock_rate);
+
+        for (idx, current) in difficulty_objects.iter().enumerate() {
+            let previous = if idx > 0 { difficulty_objects.get(idx - 1) } else { None };
+            sections.advance(current.start_time, &mut [&mut aim, &mut speed]);
+
+            let (aim_value, speed_value) = if current.is_spinner {
+                (0.0, 0.0)
+            } else {
+                (aim_value(current, previous), speed_value(current))
+            };
+
+            aim.process(current.start_time, current.delta_time, aim_value);
+            speed.process(current.start_time, current.delta_time, speed_value);
+        }
+
+        Sections::finish(&mut [&mut aim, &mut speed]);
+    }
+
+    let aim_strain = aim.difficulty().sqrt() * DIFFICULTY_MULTIPLIER;
+    let speed_strain = speed.difficulty().sqrt() * DIFFICULTY_MULTIPLIER;
+
+    let count = |kind: fn(&HitObject) -> bool| {
+        difficulty.hit_objects.iter().filter(|object| kind(object)).count() as i64
+    };
+    let slider_count = count(HitObject::is_slider);
+    let slider_combo = sliders.iter().filter_map(Option::as_ref).map(Slider::max_combo).sum::<u32>();
+
+    StandardAttributes {
+        stars: aim_strain + speed_strain + (aim_strain - speed_strain).abs() / 2.0,
+        aim_strain,
+        speed_strain,
+        approach_rate: difficulty.diff_approach,
+        overall_difficulty: difficulty.diff_overall,
+        max_combo: difficulty.hit_objects.len() as i64 - slider_count + i64::from(slider_combo),
+        circle_count: count(HitObject::is_circle),
+        slider_count,
+        spinner_count: count(HitObject::is_spinner),
+    }
+}
+
+fn flip(position: Position, hard_rock: bool) -> Position {
+    if hard_rock {
+        Position::new(position.x, 384.0 - position.y)
+    } else {
+        position
+    }
+}
+
+/// Follows a slider with a cursor that only moves once the ball leaves the
+/// follow circle, through each of the slider's ticks, repeats and tail.
+fn lazy_travel<F>(object: &mut Object, slider: &Slider, follow_radius: f64, path_position: F)
+    where F: Fn(f64) -> Position {
+    for event in slider.events().iter().skip(1) {
+        let time = match event.kind {
+            SliderEventKind::Tail => slider.legacy_last_tick_time(),
+            _ => event.time,
+        };
+        let mut difference = path_position(time) - object.end_position;
+        let mut distance = difference.length();
+
+        if distance > follow_radius {
+            difference = difference.normalize();
+            distance -= follow_radius;
+            object.end_position = object.end_position + difference * distance;
+            object.travel_distance += distance;
+        }
+    }
+}
+
+fn aim_value(current: &DifficultyObject, previous: Option<&DifficultyObject>) -> f64 {
+    let mut result = 0.0;
+
+    if let (Some(previous), Some(angle)) = (previous, current.angle) {
+        if angle > AIM_ANGLE_BONUS_BEGIN {
+            let scale = 90.0;
+            let angle_bonus = ((previous.jump_distance - scale).max(0.0)
+                * (angle - AIM_ANGLE_BONUS_BEGIN).sin().powi(2)
+                * (current.jump_distance - scale).max(0.0)).sqrt();
+
+            result = 1.5 * diminishing(angle_bonus.max(0.0)) / previous.strain_time.max(AIM_TIMING_THRESHOLD);
+        }
+    }
+
+    let jump = diminishing(current.jump_distance);
+    let travel = diminishing(current.travel_distance);
+    let combined = jump + travel + (travel * jump).sqrt();
+
+    (result + combined / current.strain_time.max(AIM_TIMING_THRESHOLD)).max(combined / current.strain_time)
+}
+
+fn speed_value(current: &DifficultyObject) -> f64 {
+    let distance = SINGLE_SPACING_THRESHOLD.min(current.travel_distance + current.jump_distance);
+    let delta_time = current.delta_time.max(MAX_SPEED_BONUS);
+
+    let speed_bonus = if delta_time < MIN_SPEED_BONUS {
+        1.0 + ((MIN_SPEED_BONUS - delta_time) / SPEED_BALANCING_FACTOR).powi(2)
+    } else {
+        1.0
+    };
+
+    let mut angle_bonus = 1.0;
+
+    if let Some(angle) = current.angle {
+        if angle < SPEED_ANGLE_BONUS_BEGIN {
+            angle_bonus = 1.0 + (1.5 * (SPEED_ANGLE_BONUS_BEGIN - angle)).sin().powi(2) / 3.57;
+
+            if angle < PI / 2.0 {
+                angle_bonus = 1.28;
+
+                if distance < 90.0 && angle < PI / 4.0 {
+                    angle_bonus += (1.0 - angle_bonus) * ((90.0 - distance) / 10.0).min(1.0);
+                } else if distance < 90.0 {
+                    angle_bonus += (1.0 - angle_bonus)
+                        * ((90.0 - distance) / 10.0).min(1.0)
+                        * ((PI / 2.0 - angle) / (PI / 4.0)).sin();
+                }
+            }
+        }
+    }
+
+    (1.0 + (speed_bonus - 1.0) * 0.75)
+        * angle_bonus
+        * (0.95 + speed_bonus * (distance / SINGLE_SPACING_THRESHOLD).powf(3.5))
+        / current.strain_time
+}
+
+/// Scales distances down slightly, so that larger ones are worth less.
+fn diminishing(value: f64) -> f64 {
+    value.powf(0.99)
+}
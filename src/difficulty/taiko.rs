@@ -0,0 +1,134 @@
+use ::convert::{self, TaikoKind, TaikoObject};
+use ::osz::Difficulty;
+use ::pp::{self, TaikoAttributes};
+use ::Mods;
+use super::{Sections, Strain, SECTION_LENGTH};
+
+const STAR_SCALING_FACTOR: f64 = 0.04125;
+const DECAY_BASE: f64 = 0.3;
+
+/// How far the ratio between two gaps between hits must be from a power of
+/// two to count as a change of rhythm, in powers of two.
+const RHYTHM_CHANGE_THRESHOLD: f64 = 0.2;
+
+/// Calculates the star rating of a difficulty in taiko for a set of mods,
+/// converting it if it was not made for taiko.
+///
+/// The overall difficulty is given unmodded, as [`pp::calculate`] expects.
+///
+/// [`pp::calculate`]: ../pp/fn.calculate.html
+pub fn taiko(difficulty: &Difficulty, mods: Mods) -> TaikoAttributes {
+    let clock_rate = pp::clock_rate(mods);
+    let objects = convert::taiko(difficulty);
+    let mut strain = Strain::new(1.0, DECAY_BASE);
+
+    if let Some(first) = objects.first() {
+        let mut sections = Sections::new(first.time, SECTION_LENGTH, clock_rate);
+        let mut colour = Colour::default();
+        let mut previous_delta_time = None;
+
+        for pair in objects.windows(2) {
+            let (last, current) = (&pair[0], &pair[1]);
+            let delta_time = (current.time - last.time) / clock_rate;
+            let mut addition = 1.0;
+
+            sections.advance(current.time, &mut [&mut strain]);
+
+            if last.is_hit() && current.is_hit() && delta_time < 1000.0 {
+                if colour.changes(last, current) {
+                    addition += 0.75;
+                }
+
+                if rhythm_changes(delta_time, previous_delta_time) {
+                    addition += 1.0;
+                }
+            } else {
+                colour = Colour::default();
+            }
+
+            // Hits closer than 50ms apart are worth less, as they are played
+            // with both hands.
+            let addition_factor = if delta_time < 50.0 {
+                0.4 + 0.6 * delta_time / 50.0
+            } else {
+                1.0
+            };
+
+            strain.process(current.time, delta_time, addition_factor * addition);
+            previous_delta_time = Some(delta_time);
+        }
+
+        Sections::finish(&mut [&mut strain]);
+    }
+
+    TaikoAttributes {
+        stars: strain.difficulty() * STAR_SCALING_FACTOR,
+        overall_difficulty: difficulty.diff_overall,
+        max_combo: objects.iter().filter(|object| object.is_hit()).count() as i64,
+    }
+}
+
+/// Whether the gap before a hit is a different rhythm to the gap before it,
+/// which is any ratio less than 8 that is not close to a power of two.
+fn rhythm_changes(delta_time: f64, previous_delta_time: Option<f64>) -> bool {
+    let previous_delta_time = match previous_delta_time {
+        Some(previous) if previous != 0.0 && delta_time != 0.0 => previous,
+        _ => return false,
+    };
+    let ratio = (previous_delta_time / delta_time).max(delta_time / previous_delta_time);
+
+    if ratio >= 8.0 {
+        return false;
+    }
+
+    let difference = (ratio.ln() / 2f64.ln()) % 1.0;
+
+    difference > RHYTHM_CHANGE_THRESHOLD && difference < 1.0 - RHYTHM_CHANGE_THRESHOLD
+}
+
+/// The parity of the number of hits of one colour before a change to the
+/// other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColourSwitch {
+    Even,
+    Odd,
+}
+
+/// The hits of the same colour in a row, and how the last run ended.
+struct Colour {
+    last_switch: Option<ColourSwitch>,
+    same_count: u32,
+}
+
+impl Default for Colour {
+    fn default() -> Colour {
+        Colour {
+            last_switch: None,
+            same_count: 1,
+        }
+    }
+}
+
+impl Colour {
+    /// Whether a hit changes colour after a run of a different parity to the
+    /// run before it.
+    fn changes(&mut self, last: &TaikoObject, current: &TaikoObject) -> bool {
+        if (last.kind == TaikoKind::Rim) == (current.kind == TaikoKind::Rim) {
+            self.same_count += 1;
+
+            return false;
+        }
+
+        let switch = if self.same_count % 2 == 1 {
+            ColourSwitch::Odd
+        } else {
+            ColourSwitch::Even
+        };
+        let last_switch = self.last_switch;
+
+        self.last_switch = Some(switch);
+        self.same_count = 1;
+
+        last_switch.is_some() && last_switch != Some(switch)
+    }
+}
@@ -16,6 +16,10 @@ use std::result::Result as StdResult;
 use hyper::error::{Error as HyperError, UriError};
 #[cfg(feature = "reqwest")]
 use reqwest::Error as ReqwestError;
+#[cfg(feature = "rusqlite")]
+use rusqlite::Error as SqliteError;
+#[cfg(feature = "zip")]
+use zip::result::ZipError;
 
 /// The result type used throughout the library.
 pub type Result<T> = StdResult<T, Error>;
@@ -35,11 +39,20 @@ pub enum Error {
     Json(JsonError),
     /// A `std::io` module error
     Io(IoError),
+    /// A file format could not be parsed, with a description of what was
+    /// invalid
+    Parse(String),
     /// An error from the `reqwest` crate.
     #[cfg(feature = "reqwest")]
     Reqwest(ReqwestError),
+    /// An error from the `rusqlite` crate.
+    #[cfg(feature = "rusqlite")]
+    Sqlite(SqliteError),
     /// An error from the `hyper` crate while parsing a URI.
     Uri(UriError),
+    /// An error from the `zip` crate while reading an archive.
+    #[cfg(feature = "zip")]
+    Zip(ZipError),
 }
 
 impl From<FmtError> for Error {
@@ -74,6 +87,13 @@ impl From<ReqwestError> for Error {
     }
 }
 
+#[cfg(feature = "rusqlite")]
+impl From<SqliteError> for Error {
+    fn from(err: SqliteError) -> Error {
+        Error::Sqlite(err)
+    }
+}
+
 #[cfg(feature = "hyper")]
 impl From<UriError> for Error {
     fn from(err: UriError) -> Error {
@@ -81,6 +101,13 @@ impl From<UriError> for Error {
     }
 }
 
+#[cfg(feature = "zip")]
+impl From<ZipError> for Error {
+    fn from(err: ZipError) -> Error {
+        Error::Zip(err)
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
         f.write_str(self.description())
@@ -93,12 +120,17 @@ impl StdError for Error {
             Error::Format(ref inner) => inner.description(),
             Error::Json(ref inner) => inner.description(),
             Error::Io(ref inner) => inner.description(),
+            Error::Parse(ref inner) => inner,
             #[cfg(feature = "hyper")]
             Error::Hyper(ref inner) => inner.description(),
             #[cfg(feature = "reqwest")]
             Error::Reqwest(ref inner) => inner.description(),
+            #[cfg(feature = "rusqlite")]
+            Error::Sqlite(ref inner) => inner.description(),
             #[cfg(feature = "hyper")]
             Error::Uri(ref inner) => inner.description(),
+            #[cfg(feature = "zip")]
+            Error::Zip(ref inner) => inner.description(),
         }
     }
 }
@@ -0,0 +1,327 @@
+//! Structured parsing of user events.
+//!
+//! The API only describes a [`UserEvent`] by its `display_html`, such as:
+//!
+//! ```html
+//! <img src='/images/A_small.png'/> <b><a href='/u/124493'>name</a></b> achieved rank #37 on <a href='/b/123?m=3'>Artist - Title [Hard]</a> (osu!mania)
+//! ```
+//!
+//! [`UserEvent::kind`] parses this into an [`EventKind`], falling back to
+//! [`EventKind::Unknown`] with the raw HTML for unrecognised events.
+//!
+//! [`EventKind`]: enum.EventKind.html
+//! [`EventKind::Unknown`]: enum.EventKind.html#variant.Unknown
+//! [`UserEvent`]: ../struct.UserEvent.html
+//! [`UserEvent::kind`]: ../struct.UserEvent.html#method.kind
+
+use ::link::Link;
+use ::{Approval, GetBeatmapUser, PlayMode, UserEvent};
+
+/// What happened in a user event.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    /// A beatmap set has been submitted.
+    BeatmapSetSubmitted {
+        user: EventUser,
+        beatmapset_id: u64,
+        /// The artist and title of the set.
+        title: String,
+    },
+    /// A beatmap set has been updated by its creator.
+    BeatmapSetUpdated {
+        user: EventUser,
+        beatmapset_id: u64,
+        title: String,
+    },
+    /// A beatmap set has been ranked, approved, qualified or loved.
+    BeatmapSetApproved {
+        /// The creator of the set.
+        user: EventUser,
+        beatmapset_id: u64,
+        title: String,
+        approval: Approval,
+    },
+    /// A graveyarded beatmap set has been revived by its creator.
+    BeatmapSetRevived {
+        user: EventUser,
+        beatmapset_id: u64,
+        title: String,
+    },
+    /// A user has lost first place on a beatmap.
+    LostFirstPlace {
+        user: EventUser,
+        beatmap_id: u64,
+        /// The artist, title and version of the beatmap.
+        title: String,
+        mode: PlayMode,
+    },
+    /// A user has unlocked a medal.
+    Medal {
+        user: EventUser,
+        name: String,
+    },
+    /// A user has achieved a rank on a beatmap's leaderboard.
+    Rank {
+        user: EventUser,
+        rank: u32,
+        /// The grade of the score, such as `"S"` or `"XH"`.
+        grade: Option<String>,
+        beatmap_id: u64,
+        title: String,
+        mode: PlayMode,
+    },
+    /// A user has become a supporter.
+    Supporter {
+        user: EventUser,
+        kind: SupporterKind,
+    },
+    /// A user has changed their username.
+    UsernameChange {
+        /// The user, named by their previous username.
+        user: EventUser,
+        username: String,
+    },
+    /// An event that is not recognised, given by its raw HTML.
+    Unknown(String),
+}
+
+impl EventKind {
+    /// The user that the event is about, unless it is unknown.
+    pub fn user(&self) -> Option<&EventUser> {
+        match *self {
+            EventKind::BeatmapSetSubmitted { ref user, .. }
+                | EventKind::BeatmapSetUpdated { ref user, .. }
+                | EventKind::BeatmapSetApproved { ref user, .. }
+                | EventKind::BeatmapSetRevived { ref user, .. }
+                | EventKind::LostFirstPlace { ref user, .. }
+                | EventKind::Medal { ref user, .. }
+                | EventKind::Rank { ref user, .. }
+                | EventKind::Supporter { ref user, .. }
+                | EventKind::UsernameChange { ref user, .. } => Some(user),
+            EventKind::Unknown(_) => None,
+        }
+    }
+}
+
+/// A user linked to by an event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventUser {
+    /// The user as linked to, which is usually by ID.
+    pub user: GetBeatmapUser,
+    /// The username shown.
+    pub username: String,
+}
+
+/// How a user became a supporter.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SupporterKind {
+    /// For the first time.
+    New,
+    /// Again, after having been one before.
+    Renewed,
+    /// As a gift from another user.
+    Gift,
+}
+
+impl UserEvent {
+    /// Parses the event's [`display_html`] into what happened.
+    ///
+    /// [`display_html`]: #structfield.display_html
+    pub fn kind(&self) -> EventKind {
+        parse(&self.display_html).unwrap_or_else(|| EventKind::Unknown(self.display_html.clone()))
+    }
+}
+
+/// A link within an event's HTML.
+struct Anchor {
+    link: Option<Link>,
+    text: String,
+}
+
+fn parse(html: &str) -> Option<EventKind> {
+    let (text, anchors, images) = strip(html);
+    let text = text.trim();
+
+    let user = anchors.iter().filter_map(|anchor| match anchor.link {
+        Some(Link::User { ref user, .. }) => Some(EventUser {
+            user: user.clone(),
+            username: anchor.text.clone(),
+        }),
+        _ => None,
+    }).next()?;
+
+    let beatmap = || anchors.iter().filter_map(|anchor| {
+        anchor.link.as_ref()
+            .and_then(Link::beatmap_id)
+            .map(|id| (id, anchor.text.clone(), anchor.link.as_ref().and_then(Link::mode)))
+    }).next();
+    let beatmapset = || anchors.iter().filter_map(|anchor| match anchor.link {
+        Some(Link::BeatmapSet { beatmapset_id, .. }) => Some((beatmapset_id, anchor.text.clone())),
+        _ => None,
+    }).next();
+    let mode = |fallback: Option<PlayMode>| {
+        let mode = if text.ends_with("(osu!)") {
+            PlayMode::Standard
+        } else if text.ends_with("(Taiko)") {
+            PlayMode::Taiko
+        } else if text.ends_with("(Catch the Beat)") {
+            PlayMode::CatchTheBeat
+        } else if text.ends_with("(osu!mania)") {
+            PlayMode::Mania
+        } else {
+            return fallback;
+        };
+
+        Some(mode)
+    };
+
+    if let Some(idx) = text.find(" achieved rank #") {
+        let rest = &text[idx + " achieved rank #".len()..];
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let (beatmap_id, title, link_mode) = beatmap()?;
+        let grade = images.iter()
+            .filter_map(|src| src.rsplit('/').next())
+            .filter(|name| name.ends_with("_small.png"))
+            .map(|name| &name[..name.len() - "_small.png".len()])
+            .map(str::to_owned)
+            .next();
+
+        return Some(EventKind::Rank {
+            user,
+            rank: rest[..digits].parse().ok()?,
+            grade,
+            beatmap_id,
+            title,
+            mode: mode(link_mode)?,
+        });
+    }
+
+    if text.contains(" has lost first place on ") {
+        let (beatmap_id, title, link_mode) = beatmap()?;
+
+        return Some(EventKind::LostFirstPlace {
+            user,
+            beatmap_id,
+            title,
+            mode: mode(link_mode)?,
+        });
+    }
+
+    if let Some(idx) = text.find(" has changed their username to ") {
+        let username = text[idx + " has changed their username to ".len()..].trim_end_matches('!');
+
+        return Some(EventKind::UsernameChange {
+            user,
+            username: username.to_owned(),
+        });
+    }
+
+    if let Some(idx) = text.find(" unlocked the \"") {
+        let rest = &text[idx + " unlocked the \"".len()..];
+        let end = rest.find("\" medal")?;
+
+        return Some(EventKind::Medal {
+            user,
+            name: rest[..end].to_owned(),
+        });
+    }
+
+    let supporter = if text.contains(" has become an osu!supporter") {
+        Some(SupporterKind::New)
+    } else if text.contains(" has once again chosen to support osu!") {
+        Some(SupporterKind::Renewed)
+    } else if text.contains(" has received the gift of osu!supporter") {
+        Some(SupporterKind::Gift)
+    } else {
+        None
+    };
+
+    if let Some(kind) = supporter {
+        return Some(EventKind::Supporter { user, kind });
+    }
+
+    let (beatmapset_id, title) = beatmapset()?;
+
+    let approval = if text.ends_with(" has just been ranked!") {
+        Approval::Ranked
+    } else if text.ends_with(" has just been approved!") {
+        Approval::Approved
+    } else if text.ends_with(" has just been qualified!") {
+        Approval::Qualified
+    } else if text.ends_with(" has just been loved!") {
+        Approval::Loved
+    } else if text.contains(" has submitted a new beatmap ") {
+        return Some(EventKind::BeatmapSetSubmitted { user, beatmapset_id, title });
+    } else if text.contains(" has updated the beatmap ") {
+        return Some(EventKind::BeatmapSetUpdated { user, beatmapset_id, title });
+    } else if text.contains(" has been revived from eternal slumber") {
+        return Some(EventKind::BeatmapSetRevived { user, beatmapset_id, title });
+    } else {
+        return None;
+    };
+
+    Some(EventKind::BeatmapSetApproved {
+        user,
+        beatmapset_id,
+        title,
+        approval,
+    })
+}
+
+/// Strips the tags from HTML, returning its text, its links and the sources
+/// of its images.
+fn strip(html: &str) -> (String, Vec<Anchor>, Vec<String>) {
+    let mut text = String::new();
+    let mut anchors = vec![];
+    let mut images = vec![];
+    let mut open_anchor: Option<(Option<Link>, usize)> = None;
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode(&rest[..start]));
+
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let tag = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with("a ") {
+            let link = attribute(tag, "href").and_then(|href| href.parse().ok());
+            open_anchor = Some((link, text.len()));
+        } else if tag == "/a" {
+            if let Some((link, start)) = open_anchor.take() {
+                anchors.push(Anchor {
+                    link,
+                    text: text[start..].to_owned(),
+                });
+            }
+        } else if tag.starts_with("img ") {
+            images.extend(attribute(tag, "src"));
+        }
+    }
+
+    text.push_str(&decode(rest));
+
+    (text, anchors, images)
+}
+
+/// Retrieves the value of a quoted attribute of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!("{}=", name))? + name.len() + 1;
+    let quote = tag[start..].chars().next().filter(|&c| c == '\'' || c == '"')?;
+    let value = &tag[start + 1..];
+    let end = value.find(quote)?;
+
+    Some(decode(&value[..end]))
+}
+
+fn decode(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#039;", "'")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
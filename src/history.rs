@@ -0,0 +1,222 @@
+//! Snapshots of users' statistics over time, and the changes between them.
+//!
+//! A [`History`] takes [`Snapshot`]s of a user's statistics from `get_user`
+//! in each mode and keeps them in a [`HistoryStorage`]. The changes between
+//! any two snapshots are given by a [`UserDiff`], such as the changes over
+//! the last week with [`History::diff_since`].
+//!
+//! # Examples
+//!
+//! Take snapshots in all modes with a client of your own, and show the
+//! changes in standard:
+//!
+//! ```rust
+//! use osu::history::{History, MemoryHistory};
+//! use osu::PlayMode;
+//! use std::time::{Duration, SystemTime};
+//!
+//! let mut history = History::new(MemoryHistory::default());
+//!
+//! history.take(|_mode| Ok(vec![])).unwrap();
+//!
+//! let week_ago = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+//!
+//! if let Some(diff) = history.diff_since(124493, PlayMode::Standard, week_ago).unwrap() {
+//!     println!("{:+.2}pp, {:+} ranks", diff.pp_raw, -diff.pp_rank);
+//! }
+//! ```
+//!
+//! [`History`]: struct.History.html
+//! [`History::diff_since`]: struct.History.html#method.diff_since
+//! [`HistoryStorage`]: trait.HistoryStorage.html
+//! [`Snapshot`]: struct.Snapshot.html
+//! [`UserDiff`]: struct.UserDiff.html
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+use ::{PlayMode, Result, User};
+
+/// The modes that snapshots are taken in by [`History::take`].
+///
+/// [`History::take`]: struct.History.html#method.take
+pub const MODES: [PlayMode; 4] = [
+    PlayMode::Standard,
+    PlayMode::Taiko,
+    PlayMode::CatchTheBeat,
+    PlayMode::Mania,
+];
+
+/// A user's statistics in a mode at a point in time.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub user: User,
+    pub mode: PlayMode,
+    /// When the snapshot was taken.
+    pub taken: SystemTime,
+}
+
+impl Snapshot {
+    /// Creates a snapshot of a user, taken now.
+    pub fn new(user: User, mode: PlayMode) -> Snapshot {
+        Snapshot {
+            user,
+            mode,
+            taken: SystemTime::now(),
+        }
+    }
+}
+
+/// Storage of snapshots.
+///
+/// Implement this to keep snapshots in a database or file.
+pub trait HistoryStorage {
+    /// Adds a snapshot.
+    fn push(&mut self, snapshot: Snapshot) -> Result<()>;
+
+    /// Retrieves the snapshots of a user in a mode, oldest first.
+    fn snapshots(&self, user_id: i64, mode: PlayMode) -> Result<Vec<Snapshot>>;
+}
+
+/// Storage of snapshots in memory, which is lost when dropped.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryHistory {
+    /// The snapshots of each user by ID and mode, oldest first.
+    pub snapshots: HashMap<(i64, PlayMode), Vec<Snapshot>>,
+}
+
+impl HistoryStorage for MemoryHistory {
+    fn push(&mut self, snapshot: Snapshot) -> Result<()> {
+        let snapshots = self.snapshots.entry((snapshot.user.id, snapshot.mode)).or_default();
+        let idx = snapshots.iter().rposition(|s| s.taken <= snapshot.taken).map_or(0, |idx| idx + 1);
+        snapshots.insert(idx, snapshot);
+
+        Ok(())
+    }
+
+    fn snapshots(&self, user_id: i64, mode: PlayMode) -> Result<Vec<Snapshot>> {
+        Ok(self.snapshots.get(&(user_id, mode)).cloned().unwrap_or_default())
+    }
+}
+
+/// A history of users' statistics.
+#[derive(Clone, Debug)]
+pub struct History<S: HistoryStorage> {
+    storage: S,
+}
+
+impl<S: HistoryStorage> History<S> {
+    /// Creates a history keeping its snapshots in the given storage.
+    pub fn new(storage: S) -> History<S> {
+        History { storage }
+    }
+
+    /// The storage of the history's snapshots.
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    /// Consumes the history, returning its storage.
+    pub fn into_storage(self) -> S {
+        self.storage
+    }
+
+    /// Records a snapshot of a user, taken now.
+    pub fn record(&mut self, user: User, mode: PlayMode) -> Result<()> {
+        self.storage.push(Snapshot::new(user, mode))
+    }
+
+    /// Takes a snapshot of a user in each of the [`MODES`], retrieving them
+    /// with the given function, such as a call to `get_user` with the mode.
+    ///
+    /// Modes in which the user is not found are skipped. Returns the
+    /// snapshots taken, or the first error from retrieving the user or from
+    /// the storage.
+    ///
+    /// [`MODES`]: constant.MODES.html
+    pub fn take<F>(&mut self, mut fetch: F) -> Result<Vec<Snapshot>>
+        where F: FnMut(PlayMode) -> Result<Vec<User>> {
+        let mut taken = vec![];
+
+        for &mode in &MODES {
+            if let Some(user) = fetch(mode)?.into_iter().next() {
+                let snapshot = Snapshot::new(user, mode);
+                self.storage.push(snapshot.clone())?;
+                taken.push(snapshot);
+            }
+        }
+
+        Ok(taken)
+    }
+
+    /// Retrieves the snapshots of a user in a mode, oldest first.
+    pub fn snapshots(&self, user_id: i64, mode: PlayMode) -> Result<Vec<Snapshot>> {
+        self.storage.snapshots(user_id, mode)
+    }
+
+    /// Retrieves the latest snapshot of a user in a mode.
+    pub fn latest(&self, user_id: i64, mode: PlayMode) -> Result<Option<Snapshot>> {
+        Ok(self.storage.snapshots(user_id, mode)?.pop())
+    }
+
+    /// The changes from the last snapshot taken at or before a time, or the
+    /// earliest snapshot if all are later, to the latest snapshot.
+    ///
+    /// Returns `None` if there are no snapshots of the user in the mode.
+    pub fn diff_since(&self, user_id: i64, mode: PlayMode, since: SystemTime)
+        -> Result<Option<UserDiff>> {
+        let snapshots = self.storage.snapshots(user_id, mode)?;
+        let before = snapshots.iter()
+            .rev()
+            .find(|snapshot| snapshot.taken <= since)
+            .or_else(|| snapshots.first());
+
+        Ok(match (before, snapshots.last()) {
+            (Some(before), Some(after)) => Some(UserDiff::new(before, after)),
+            _ => None,
+        })
+    }
+}
+
+/// The changes in a user's statistics between two snapshots.
+///
+/// Each value is the later value minus the earlier one, so an improvement
+/// in rank is negative.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UserDiff {
+    pub accuracy: f64,
+    pub count_rank_a: i64,
+    pub count_rank_s: i64,
+    pub count_rank_ss: i64,
+    pub level: f64,
+    pub play_count: i64,
+    pub pp_country_rank: i64,
+    pub pp_rank: i64,
+    pub pp_raw: f64,
+    pub ranked_score: i64,
+    pub total_score: i64,
+    /// The time between the snapshots, or zero if the later snapshot was
+    /// taken first.
+    pub elapsed: Duration,
+}
+
+impl UserDiff {
+    /// Computes the changes from one snapshot to another.
+    pub fn new(before: &Snapshot, after: &Snapshot) -> UserDiff {
+        let (a, b) = (&before.user, &after.user);
+
+        UserDiff {
+            accuracy: b.accuracy - a.accuracy,
+            count_rank_a: b.count_rank_a - a.count_rank_a,
+            count_rank_s: b.count_rank_s - a.count_rank_s,
+            count_rank_ss: b.count_rank_ss - a.count_rank_ss,
+            level: b.level - a.level,
+            play_count: b.play_count - a.play_count,
+            pp_country_rank: b.pp_country_rank - a.pp_country_rank,
+            pp_rank: b.pp_rank - a.pp_rank,
+            pp_raw: b.pp_raw - a.pp_raw,
+            ranked_score: b.ranked_score - a.ranked_score,
+            total_score: b.total_score - a.total_score,
+            elapsed: after.taken.duration_since(before.taken).unwrap_or_default(),
+        }
+    }
+}
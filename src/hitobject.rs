@@ -0,0 +1,319 @@
+//! Hit objects from the `[HitObjects]` section of a `.osu` file.
+//!
+//! Each line of the section is parsed into a [`HitObject`], which keeps the
+//! object's position, time, combo information and the parameters of its
+//! kind. Slider paths are calculated by the [`slider`] module.
+//!
+//! # Examples
+//!
+//! Parse a slider:
+//!
+//! ```rust
+//! use osu::hitobject::{CurveType, HitObject, HitObjectKind};
+//!
+//! let object = "100,100,2666,6,0,L|300:100,1,140".parse::<HitObject>().unwrap();
+//! assert!(object.new_combo);
+//!
+//! match object.kind {
+//!     HitObjectKind::Slider { curve_type, ref control_points, .. } => {
+//!         assert_eq!(curve_type, CurveType::Linear);
+//!         assert_eq!(control_points.len(), 2);
+//!     },
+//!     _ => unreachable!(),
+//! }
+//! ```
+//!
+//! [`HitObject`]: struct.HitObject.html
+//! [`slider`]: ../slider/index.html
+
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+use ::{Error, Result};
+
+/// A position on the playfield, which is 512 by 384 osu!pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Position {
+    pub fn new(x: f64, y: f64) -> Position {
+        Position { x, y }
+    }
+
+    /// The distance from the origin.
+    pub fn length(&self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    /// The distance to another position.
+    pub fn distance(&self, other: Position) -> f64 {
+        (*self - other).length()
+    }
+
+    pub fn dot(&self, other: Position) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// The position with the same direction and a length of `1`, or the
+    /// origin if this is the origin.
+    pub fn normalize(&self) -> Position {
+        let length = self.length();
+
+        if length > 0.0 {
+            *self * (1.0 / length)
+        } else {
+            Position::default()
+        }
+    }
+}
+
+impl Add for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        Position::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Position {
+    type Output = Position;
+
+    fn sub(self, other: Position) -> Position {
+        Position::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Position {
+    type Output = Position;
+
+    fn mul(self, scale: f64) -> Position {
+        Position::new(self.x * scale, self.y * scale)
+    }
+}
+
+/// The kind of curve that a slider's path follows through its control
+/// points.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CurveType {
+    Bezier,
+    Catmull,
+    Linear,
+    PerfectCircle,
+}
+
+impl CurveType {
+    /// The curve type for its letter in a `.osu` file, falling back to
+    /// Catmull as the game does.
+    pub fn from_letter(letter: &str) -> CurveType {
+        match letter {
+            "B" => CurveType::Bezier,
+            "L" => CurveType::Linear,
+            "P" => CurveType::PerfectCircle,
+            _ => CurveType::Catmull,
+        }
+    }
+}
+
+/// The parameters of each kind of hit object.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HitObjectKind {
+    Circle,
+    Slider {
+        curve_type: CurveType,
+        /// The points that shape the slider's path, starting with the
+        /// object's own position. A point repeated twice in a row is a red
+        /// anchor, which starts a new segment of a Bézier path.
+        control_points: Vec<Position>,
+        /// The number of times that the path is travelled, so `1` for a
+        /// slider without repeats.
+        slides: u32,
+        /// The length of the path in osu!pixels, or `None` to use the full
+        /// length of the curve.
+        pixel_length: Option<f64>,
+        /// The hitsound flags of the head, each repeat and the tail, which
+        /// may be fewer than the nodes or empty in older files.
+        edge_sounds: Vec<u8>,
+    },
+    Spinner {
+        end_time: f64,
+    },
+    /// A mania hold note.
+    Hold {
+        end_time: f64,
+    },
+}
+
+/// A hit object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HitObject {
+    pub position: Position,
+    /// The time in milliseconds.
+    pub time: f64,
+    /// Whether the object starts a new combo.
+    pub new_combo: bool,
+    /// The number of combo colours to skip when starting a new combo.
+    pub combo_skip: u8,
+    /// The hitsound flags: whistle (`2`), finish (`4`) and clap (`8`).
+    pub hit_sound: u8,
+    pub kind: HitObjectKind,
+}
+
+impl HitObject {
+    /// The time that the object ends for spinners and hold notes, or its
+    /// start time for every other kind.
+    ///
+    /// The end time of a slider depends on the timing points, and is given
+    /// by [`Slider::end_time`].
+    ///
+    /// [`Slider::end_time`]: ../slider/struct.Slider.html#method.end_time
+    pub fn end_time(&self) -> f64 {
+        match self.kind {
+            HitObjectKind::Spinner { end_time } | HitObjectKind::Hold { end_time } => end_time,
+            _ => self.time,
+        }
+    }
+
+    pub fn is_circle(&self) -> bool {
+        self.kind == HitObjectKind::Circle
+    }
+
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn is_slider(&self) -> bool {
+        match self.kind {
+            HitObjectKind::Slider { .. } => true,
+            _ => false,
+        }
+    }
+
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn is_spinner(&self) -> bool {
+        match self.kind {
+            HitObjectKind::Spinner { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// The hitsound flags of a slider's node, counting the head as `0` and
+    /// the tail last, falling back to the object's own hitsound for nodes
+    /// without their own and for every other kind of object.
+    pub fn edge_sound(&self, node: usize) -> u8 {
+        match self.kind {
+            HitObjectKind::Slider { ref edge_sounds, .. } => {
+                edge_sounds.get(node).cloned().unwrap_or(self.hit_sound)
+            },
+            _ => self.hit_sound,
+        }
+    }
+
+    /// The column of a mania object, from `0`, for a number of keys.
+    pub fn column(&self, keys: u32) -> u32 {
+        let keys = keys.max(1);
+        let column = (self.position.x * f64::from(keys) / 512.0).floor();
+
+        (column.max(0.0) as u32).min(keys - 1)
+    }
+}
+
+/// The number of each object within its combo, from `1`, as shown in game
+/// and used to select objects in editor timestamps.
+///
+/// Spinners and the objects after them always start a new combo.
+pub fn combo_numbers(objects: &[HitObject]) -> Vec<u32> {
+    let mut numbers: Vec<u32> = Vec::with_capacity(objects.len());
+
+    for (idx, object) in objects.iter().enumerate() {
+        let new_combo = idx == 0
+            || object.new_combo
+            || object.is_spinner()
+            || objects[idx - 1].is_spinner();
+
+        numbers.push(if new_combo { 1 } else { numbers[idx - 1] + 1 });
+    }
+
+    numbers
+}
+
+impl FromStr for HitObject {
+    type Err = Error;
+
+    /// Parses a line of the `[HitObjects]` section.
+    ///
+    /// Returns [`Error::Parse`] if a field is malformed or the object is of
+    /// no known kind.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn from_str(s: &str) -> Result<HitObject> {
+        let invalid = || Error::Parse(format!("invalid hit object {:?}", s));
+        let fields = s.trim().split(',').map(str::trim).collect::<Vec<_>>();
+
+        if fields.len() < 5 {
+            return Err(invalid());
+        }
+
+        let number = |idx: usize| fields.get(idx).and_then(|f| f.parse::<f64>().ok()).ok_or_else(invalid);
+        let position = Position::new(number(0)?, number(1)?);
+        let time = number(2)?;
+        let flags = fields[3].parse::<u32>().map_err(|_| invalid())?;
+        let hit_sound = fields[4].parse::<u8>().unwrap_or(0);
+
+        let kind = if flags & 2 != 0 {
+            let mut points = fields.get(5).ok_or_else(invalid)?.split('|');
+            let curve_type = CurveType::from_letter(points.next().unwrap_or(""));
+            let mut control_points = vec![position];
+
+            for point in points {
+                let mut coords = point.split(':').map(|v| v.parse::<f64>());
+
+                match (coords.next(), coords.next()) {
+                    (Some(Ok(x)), Some(Ok(y))) => control_points.push(Position::new(x, y)),
+                    _ => return Err(invalid()),
+                }
+            }
+
+            HitObjectKind::Slider {
+                curve_type,
+                control_points,
+                slides: match fields.get(6) {
+                    Some(slides) => slides.parse::<u32>().map_err(|_| invalid())?.max(1),
+                    None => 1,
+                },
+                pixel_length: match fields.get(7) {
+                    Some(length) => Some(length.parse::<f64>().map_err(|_| invalid())?.max(0.0)),
+                    None => None,
+                },
+                edge_sounds: match fields.get(8) {
+                    Some(sounds) if !sounds.is_empty() => sounds.split('|')
+                        .map(|sound| sound.parse::<u8>().map_err(|_| invalid()))
+                        .collect::<Result<Vec<_>>>()?,
+                    _ => vec![],
+                },
+            }
+        } else if flags & 8 != 0 {
+            HitObjectKind::Spinner {
+                end_time: number(5)?,
+            }
+        } else if flags & 128 != 0 {
+            let end_time = fields.get(5)
+                .and_then(|f| f.split(':').next())
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(invalid)?;
+
+            HitObjectKind::Hold { end_time }
+        } else if flags & 1 != 0 {
+            HitObjectKind::Circle
+        } else {
+            return Err(invalid());
+        };
+
+        Ok(HitObject {
+            position,
+            time,
+            new_combo: flags & 4 != 0,
+            combo_skip: ((flags >> 4) & 7) as u8,
+            hit_sound,
+            kind,
+        })
+    }
+}
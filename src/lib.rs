@@ -114,8 +114,18 @@ extern crate serde_json;
 extern crate futures;
 #[cfg(feature = "hyper")]
 extern crate hyper;
+#[cfg(feature = "lzma-rs")]
+extern crate lzma_rs;
+#[cfg(feature = "md5")]
+extern crate md5;
 #[cfg(feature = "reqwest")]
 extern crate reqwest;
+#[cfg(feature = "rusqlite")]
+#[macro_use] extern crate rusqlite;
+#[cfg(feature = "tokio-core")]
+extern crate tokio_core;
+#[cfg(feature = "zip")]
+extern crate zip;
 
 /// The base URL for all requests to osu!'s API.
 ///
@@ -123,9 +133,38 @@ extern crate reqwest;
 /// any extra purposes that might be needed.
 pub const API_URL: &'static str = "https://osu.ppy.sh/api";
 
+pub mod analysis;
+pub mod best;
 pub mod bridge;
 pub mod builder;
+pub mod bulk;
+#[cfg(feature = "osz-support")]
+pub mod convert;
+pub mod db;
+#[cfg(feature = "osz-support")]
+pub mod difficulty;
 pub mod error;
+pub mod event;
+pub mod history;
+pub mod hitobject;
+pub mod link;
+pub mod mappool;
+#[cfg(feature = "osz-support")]
+pub mod osz;
+pub mod paginate;
+pub mod pp;
+pub mod ratelimit;
+#[cfg(feature = "replay-support")]
+pub mod replay;
+pub mod skin;
+pub mod slider;
+#[cfg(feature = "sqlite-support")]
+pub mod sqlite;
+pub mod stacking;
+pub mod storyboard;
+pub mod timestamp;
+pub mod timing;
+pub mod watch;
 
 mod model;
 
@@ -135,6 +174,7 @@ pub use model::*;
 /// Information for retrieving a user.
 ///
 /// User retrieval can either be done with an ID or with a username.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum GetBeatmapUser {
     /// The user's ID.
     UserId(u64),
@@ -0,0 +1,266 @@
+//! Parsing of links to beatmaps, users and matches.
+//!
+//! Links to the website, such as `https://osu.ppy.sh/b/123` or
+//! `osu.ppy.sh/users/124493/taiko`, and to the client, such as
+//! `osu://b/123`, are parsed into a [`Link`]. Both the old and new website
+//! paths are recognised, with or without the scheme and host.
+//!
+//! # Examples
+//!
+//! Parse a link to a difficulty of a beatmap set, and filter a beatmap
+//! request by it:
+//!
+//! ```rust
+//! use osu::builder::GetBeatmapsRequest;
+//! use osu::link::Link;
+//! use osu::PlayMode;
+//!
+//! let link = "https://osu.ppy.sh/beatmapsets/456#mania/789".parse::<Link>().unwrap();
+//!
+//! assert_eq!(link.mode(), Some(PlayMode::Mania));
+//!
+//! let request = GetBeatmapsRequest::default().link(&link);
+//!
+//! assert_eq!(request.0["b"], "789");
+//! ```
+//!
+//! [`Link`]: enum.Link.html
+
+use std::str::FromStr;
+use ::{Error, GetBeatmapUser, PlayMode, Result};
+
+/// The hosts of the website.
+const HOSTS: &[&str] = &["osu.ppy.sh", "old.ppy.sh", "new.ppy.sh", "www.osu.ppy.sh"];
+
+/// The target of a link.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Link {
+    /// A beatmap, from links such as `/b/123`, `/beatmaps/123` or
+    /// `/beatmapsets/456#osu/123`.
+    Beatmap {
+        beatmap_id: u64,
+        /// The beatmap's set, if given by the link.
+        beatmapset_id: Option<u64>,
+        /// The mode to show the beatmap in, which may be a conversion.
+        mode: Option<PlayMode>,
+    },
+    /// A beatmap set, from links such as `/s/456`, `/d/456` or
+    /// `/beatmapsets/456#taiko`.
+    BeatmapSet {
+        beatmapset_id: u64,
+        mode: Option<PlayMode>,
+    },
+    /// A multiplayer match, from links such as `/mp/123` or
+    /// `/community/matches/123`.
+    Match {
+        match_id: u64,
+    },
+    /// A user, from links such as `/u/name` or `/users/124493/taiko`.
+    ///
+    /// Numeric names are treated as IDs, as they are by the website.
+    User {
+        user: GetBeatmapUser,
+        mode: Option<PlayMode>,
+    },
+}
+
+impl Link {
+    /// The mode given by the link, if any.
+    pub fn mode(&self) -> Option<PlayMode> {
+        match *self {
+            Link::Beatmap { mode, .. }
+                | Link::BeatmapSet { mode, .. }
+                | Link::User { mode, .. } => mode,
+            Link::Match { .. } => None,
+        }
+    }
+
+    /// The ID of a beatmap link.
+    pub fn beatmap_id(&self) -> Option<u64> {
+        match *self {
+            Link::Beatmap { beatmap_id, .. } => Some(beatmap_id),
+            _ => None,
+        }
+    }
+
+    /// The beatmap set ID of a beatmap set link, or of a beatmap link that
+    /// gives its set.
+    pub fn beatmapset_id(&self) -> Option<u64> {
+        match *self {
+            Link::Beatmap { beatmapset_id, .. } => beatmapset_id,
+            Link::BeatmapSet { beatmapset_id, .. } => Some(beatmapset_id),
+            _ => None,
+        }
+    }
+
+    /// The ID of a match link, for use with `get_match`.
+    pub fn match_id(&self) -> Option<u64> {
+        match *self {
+            Link::Match { match_id } => Some(match_id),
+            _ => None,
+        }
+    }
+
+    /// The user of a user link, for use with `get_user` and similar
+    /// requests.
+    ///
+    /// The link's mode can be given to the request through
+    /// [`GetUserRequest::link`].
+    ///
+    /// [`GetUserRequest::link`]: ../builder/struct.GetUserRequest.html#method.link
+    pub fn user(&self) -> Option<GetBeatmapUser> {
+        match *self {
+            Link::User { ref user, .. } => Some(user.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Link {
+    type Err = Error;
+
+    /// Parses a link.
+    ///
+    /// Returns [`Error::Parse`] if the link is not to a beatmap, beatmap
+    /// set, match or user on the website or client.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn from_str(s: &str) -> Result<Link> {
+        let invalid = || Error::Parse(format!("unrecognised link {:?}", s));
+        let mut rest = s.trim();
+
+        if rest.starts_with("osu://") {
+            rest = &rest["osu://".len()..];
+        } else {
+            for scheme in &["https://", "http://"] {
+                if rest.starts_with(scheme) {
+                    rest = &rest[scheme.len()..];
+                }
+            }
+
+            let host_end = rest.find('/').unwrap_or(rest.len());
+            let host = &rest[..host_end];
+
+            if !host.is_empty() && !HOSTS.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+                return Err(invalid());
+            }
+
+            rest = &rest[host_end..];
+        }
+
+        let (rest, fragment) = split_once(rest, '#');
+        let (path, query) = split_once(rest, '?');
+        let segments = path.split('/').filter(|s| !s.is_empty()).collect::<Vec<_>>();
+
+        let query_mode = query.and_then(|query| {
+            query.split('&')
+                .find(|param| param.starts_with("m="))
+                .and_then(|param| mode_from_id(&param[2..]))
+        });
+        let id = |segment: &str| segment.parse::<u64>().map_err(|_| invalid());
+        let mode = |name: &str| mode_from_name(name).ok_or_else(invalid);
+
+        Ok(match segments[..] {
+            ["b", beatmap_id] | ["beatmaps", beatmap_id] => Link::Beatmap {
+                beatmap_id: id(beatmap_id)?,
+                beatmapset_id: None,
+                mode: query_mode,
+            },
+            ["s", beatmapset_id]
+                | ["d", beatmapset_id]
+                | ["dl", beatmapset_id]
+                | ["beatmapsets", beatmapset_id] => {
+                let beatmapset_id = id(beatmapset_id)?;
+
+                match fragment.map(|fragment| split_once(fragment, '/')) {
+                    Some((name, Some(beatmap_id))) => Link::Beatmap {
+                        beatmap_id: id(beatmap_id)?,
+                        beatmapset_id: Some(beatmapset_id),
+                        mode: Some(mode(name)?),
+                    },
+                    Some((name, None)) => Link::BeatmapSet {
+                        beatmapset_id,
+                        mode: Some(mode(name)?),
+                    },
+                    None => Link::BeatmapSet {
+                        beatmapset_id,
+                        mode: query_mode,
+                    },
+                }
+            },
+            ["mp", match_id] | ["community", "matches", match_id] => Link::Match {
+                match_id: id(match_id)?,
+            },
+            ["u", user] | ["users", user] => Link::User {
+                user: parse_user(user),
+                mode: query_mode,
+            },
+            ["users", user, name] => Link::User {
+                user: parse_user(user),
+                mode: Some(mode(name)?),
+            },
+            _ => return Err(invalid()),
+        })
+    }
+}
+
+fn mode_from_id(id: &str) -> Option<PlayMode> {
+    match id {
+        "0" => Some(PlayMode::Standard),
+        "1" => Some(PlayMode::Taiko),
+        "2" => Some(PlayMode::CatchTheBeat),
+        "3" => Some(PlayMode::Mania),
+        _ => None,
+    }
+}
+
+fn mode_from_name(name: &str) -> Option<PlayMode> {
+    match name {
+        "osu" => Some(PlayMode::Standard),
+        "taiko" => Some(PlayMode::Taiko),
+        "fruits" => Some(PlayMode::CatchTheBeat),
+        "mania" => Some(PlayMode::Mania),
+        _ => None,
+    }
+}
+
+fn parse_user(segment: &str) -> GetBeatmapUser {
+    match segment.parse() {
+        Ok(id) => GetBeatmapUser::UserId(id),
+        Err(_) => GetBeatmapUser::Username(percent_decode(segment)),
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        let escaped = if bytes[idx] == b'%' {
+            value.get(idx + 1..idx + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            },
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            },
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn split_once(value: &str, delimiter: char) -> (&str, Option<&str>) {
+    match value.find(delimiter) {
+        Some(idx) => (&value[..idx], Some(&value[idx + 1..])),
+        None => (value, None),
+    }
+}
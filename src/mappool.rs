@@ -0,0 +1,460 @@
+//! Tournament mappools, and checking the mods of their games.
+//!
+//! A [`Mappool`] assigns beatmaps to [`Slot`]s such as `NM1`, `HD2`, `DT3`,
+//! `FM` and `TB`. Each slot's category decides which mods its games must be
+//! played with:
+//!
+//! - NoMod, Hidden, HardRock and DoubleTime slots must be played with
+//!   exactly their mod, or none for NoMod. Nightcore counts as DoubleTime.
+//! - FreeMod and TieBreaker slots may be played with any of the pool's
+//!   [`free_mods`], which are the mods allowed in multiplayer's freemod by
+//!   default.
+//!
+//! The mods in the pool's [`allowed`] mods, NoFail by default, may always be
+//! used.
+//!
+//! A game is checked with [`Mappool::validate`], which lists every way the
+//! game broke its slot's rules, checking both the game's mods and each
+//! player's enabled mods.
+//!
+//! The pool also caches each slot's beatmap, so that its difficulty can be
+//! shown with the slot's mods applied as a [`PoolBeatmap`].
+//!
+//! # Examples
+//!
+//! Check the mods of a game of a match against a pool:
+//!
+//! ```rust
+//! use osu::mappool::Mappool;
+//!
+//! let mut pool = Mappool::new();
+//! pool.insert("NM1".parse().unwrap(), 774965);
+//! pool.insert("HD1".parse().unwrap(), 129891);
+//! pool.insert("TB".parse().unwrap(), 1262832);
+//!
+//! # let games: Vec<osu::Game> = vec![];
+//! for game in &games {
+//!     for violation in pool.validate(game) {
+//!         println!("game {}: {}", game.game_id, violation);
+//!     }
+//! }
+//! ```
+//!
+//! [`Mappool`]: struct.Mappool.html
+//! [`Mappool::validate`]: struct.Mappool.html#method.validate
+//! [`PoolBeatmap`]: struct.PoolBeatmap.html
+//! [`Slot`]: struct.Slot.html
+//! [`allowed`]: struct.Mappool.html#structfield.allowed
+//! [`free_mods`]: struct.Mappool.html#structfield.free_mods
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+use ::{Beatmap, Error, Game, Mods, Result, pp};
+
+#[cfg(feature = "osz-support")]
+use ::difficulty;
+#[cfg(feature = "osz-support")]
+use ::osz::Difficulty;
+#[cfg(feature = "osz-support")]
+use ::PlayMode;
+
+#[cfg(feature = "hyper")]
+use futures::{Future, Stream, future};
+#[cfg(feature = "hyper")]
+use hyper::client::{Client, Connect};
+#[cfg(feature = "hyper")]
+use hyper::Error as HyperError;
+#[cfg(feature = "hyper")]
+use ::bridge::hyper::OsuHyperRequester;
+
+#[cfg(feature = "reqwest")]
+use reqwest::Client as ReqwestClient;
+#[cfg(feature = "reqwest")]
+use ::bridge::reqwest::OsuReqwestRequester;
+
+/// The category of a slot, which decides the mods it is played with.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    NoMod,
+    Hidden,
+    HardRock,
+    DoubleTime,
+    FreeMod,
+    TieBreaker,
+}
+
+impl Category {
+    /// The prefix of the category's slot names, such as `HD`.
+    pub fn prefix(&self) -> &'static str {
+        match *self {
+            Category::NoMod => "NM",
+            Category::Hidden => "HD",
+            Category::HardRock => "HR",
+            Category::DoubleTime => "DT",
+            Category::FreeMod => "FM",
+            Category::TieBreaker => "TB",
+        }
+    }
+
+    /// The mods that the category's slots must be played with.
+    pub fn mods(&self) -> Mods {
+        match *self {
+            Category::Hidden => Mods::HIDDEN,
+            Category::HardRock => Mods::HARD_ROCK,
+            Category::DoubleTime => Mods::DOUBLE_TIME,
+            Category::NoMod | Category::FreeMod | Category::TieBreaker => Mods::NONE,
+        }
+    }
+
+    /// Whether players choose their own mods in the category's slots.
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn is_free_mod(&self) -> bool {
+        match *self {
+            Category::FreeMod | Category::TieBreaker => true,
+            _ => false,
+        }
+    }
+}
+
+/// A slot of a mappool, such as `HD2`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Slot {
+    pub category: Category,
+    /// The number of the slot in its category, or `0` for a slot without a
+    /// number, such as `TB`.
+    pub number: u8,
+}
+
+impl Slot {
+    /// Creates a slot from its category and its number in the category.
+    pub fn new(category: Category, number: u8) -> Slot {
+        Slot { category, number }
+    }
+}
+
+impl Display for Slot {
+    /// Formats the slot by its name, such as `HD2` or `TB`.
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str(self.category.prefix())?;
+
+        if self.number > 0 {
+            write!(f, "{}", self.number)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Slot {
+    type Err = Error;
+
+    /// Parses a slot by its name, such as `HD2`, `FM` or `tb`.
+    ///
+    /// Returns [`Error::Parse`] if its category is unknown or its number is
+    /// malformed.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn from_str(s: &str) -> Result<Slot> {
+        let invalid = || Error::Parse(format!("invalid slot {:?}", s));
+        let s = s.trim();
+
+        if s.len() < 2 || !s.is_char_boundary(2) {
+            return Err(invalid());
+        }
+
+        let category = match &*s[..2].to_uppercase() {
+            "NM" => Category::NoMod,
+            "HD" => Category::Hidden,
+            "HR" => Category::HardRock,
+            "DT" => Category::DoubleTime,
+            "FM" => Category::FreeMod,
+            "TB" => Category::TieBreaker,
+            _ => return Err(invalid()),
+        };
+        let number = match &s[2..] {
+            "" => 0,
+            number => number.parse().map_err(|_| invalid())?,
+        };
+
+        Ok(Slot::new(category, number))
+    }
+}
+
+/// A way that a game broke the rules of a mappool.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Violation {
+    /// The game's beatmap is not in the pool.
+    UnknownBeatmap(i64),
+    /// The game's beatmap is not the slot's beatmap.
+    WrongBeatmap {
+        expected: i64,
+        found: i64,
+    },
+    /// The game was set up with mods that the slot does not allow.
+    GameMods(Mods),
+    /// A player played with mods that the slot does not allow, including
+    /// the game's mods.
+    PlayerMods {
+        user_id: i64,
+        mods: Mods,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            Violation::UnknownBeatmap(id) => write!(f, "beatmap {} is not in the pool", id),
+            Violation::WrongBeatmap { expected, found } => {
+                write!(f, "beatmap {} was played instead of {}", found, expected)
+            },
+            Violation::GameMods(mods) => write!(f, "the game was set up with {:?}", mods),
+            Violation::PlayerMods { user_id, mods } => {
+                write!(f, "user {} played with {:?}", user_id, mods)
+            },
+        }
+    }
+}
+
+/// A slot's beatmap, with its difficulty adjusted for the slot's mods.
+///
+/// FreeMod and TieBreaker slots have no mods, so their difficulty is
+/// unmodded.
+///
+/// The API only gives the unmodded star rating, so the beatmap's
+/// [`difficulty_rating`] is not adjusted. The rating with the slot's mods can
+/// be calculated from the beatmap's `.osu` file with [`stars`].
+///
+/// [`difficulty_rating`]: ../struct.Beatmap.html#structfield.difficulty_rating
+/// [`stars`]: #method.stars
+#[derive(Clone, Debug)]
+pub struct PoolBeatmap {
+    pub slot: Slot,
+    pub beatmap: Beatmap,
+    pub mods: Mods,
+    pub approach_rate: f64,
+    pub circle_size: f64,
+    pub drain_rate: f64,
+    pub overall_difficulty: f64,
+    pub bpm: f64,
+    /// The drain time in seconds, adjusted for the clock rate.
+    pub hit_length: i64,
+    /// The total length in seconds, adjusted for the clock rate.
+    pub total_length: i64,
+}
+
+impl PoolBeatmap {
+    /// Applies the mods of a slot to its beatmap.
+    pub fn new(slot: Slot, beatmap: Beatmap) -> PoolBeatmap {
+        let mods = slot.category.mods();
+        let rate = pp::clock_rate(mods);
+
+        PoolBeatmap {
+            slot,
+            mods,
+            approach_rate: pp::approach_rate(beatmap.diff_approach, mods),
+            circle_size: pp::circle_size(beatmap.diff_size, mods),
+            drain_rate: pp::drain_rate(beatmap.diff_drain, mods),
+            overall_difficulty: pp::overall_difficulty(beatmap.diff_overall, mods),
+            bpm: beatmap.bpm * rate,
+            hit_length: (beatmap.hit_length as f64 / rate).round() as i64,
+            total_length: (beatmap.total_length as f64 / rate).round() as i64,
+            beatmap,
+        }
+    }
+
+    /// Calculates the star rating with the slot's mods from the beatmap's
+    /// parsed `.osu` file, in the beatmap's mode.
+    ///
+    /// Gives `None` if the difficulty is for a mode that the beatmap cannot
+    /// be converted to.
+    ///
+    /// Requires the `osz-support` feature.
+    #[cfg(feature = "osz-support")]
+    pub fn stars(&self, difficulty: &Difficulty) -> Option<f64> {
+        let mode = match self.beatmap.mode {
+            1 => PlayMode::Taiko,
+            2 => PlayMode::CatchTheBeat,
+            3 => PlayMode::Mania,
+            _ => PlayMode::Standard,
+        };
+
+        difficulty::calculate(difficulty, mode, self.mods).map(|attributes| attributes.stars())
+    }
+}
+
+/// A tournament mappool, assigning beatmaps to slots.
+#[derive(Clone, Debug)]
+pub struct Mappool {
+    /// The ID of each slot's beatmap.
+    pub slots: BTreeMap<Slot, i64>,
+    /// The mods that may be used in any slot.
+    pub allowed: Mods,
+    /// The mods that may be used in FreeMod and TieBreaker slots.
+    pub free_mods: Mods,
+    /// The retrieved beatmaps, by ID.
+    pub beatmaps: HashMap<i64, Beatmap>,
+}
+
+impl Default for Mappool {
+    /// An empty pool allowing NoFail, and multiplayer's freemod mods.
+    fn default() -> Mappool {
+        Mappool {
+            slots: BTreeMap::new(),
+            allowed: Mods::NO_FAIL,
+            free_mods: Mods::FREE_MOD_ALLOWED,
+            beatmaps: HashMap::new(),
+        }
+    }
+}
+
+impl Mappool {
+    /// Creates an empty pool.
+    pub fn new() -> Mappool {
+        Mappool::default()
+    }
+
+    /// Assigns a beatmap to a slot, returning the beatmap previously in the
+    /// slot.
+    pub fn insert(&mut self, slot: Slot, beatmap_id: i64) -> Option<i64> {
+        self.slots.insert(slot, beatmap_id)
+    }
+
+    /// The slot of a beatmap, if it is in the pool.
+    pub fn slot(&self, beatmap_id: i64) -> Option<Slot> {
+        self.slots.iter().find(|&(_, &id)| id == beatmap_id).map(|(&slot, _)| slot)
+    }
+
+    /// Checks a game against the slot of its beatmap.
+    ///
+    /// Returns every violation of the slot's rules, which is empty if the
+    /// game was played correctly.
+    pub fn validate(&self, game: &Game) -> Vec<Violation> {
+        match self.slot(game.beatmap_id) {
+            Some(slot) => self.validate_slot(slot, game),
+            None => vec![Violation::UnknownBeatmap(game.beatmap_id)],
+        }
+    }
+
+    /// Checks a game against a slot, such as the slot that was picked.
+    ///
+    /// The mods that each player played with are the game's mods along with
+    /// their enabled mods.
+    pub fn validate_slot(&self, slot: Slot, game: &Game) -> Vec<Violation> {
+        let mut violations = vec![];
+
+        match self.slots.get(&slot) {
+            Some(&expected) if expected != game.beatmap_id => {
+                violations.push(Violation::WrongBeatmap { expected, found: game.beatmap_id });
+            },
+            Some(_) => {},
+            None => violations.push(Violation::UnknownBeatmap(game.beatmap_id)),
+        }
+
+        let (required, optional) = self.rules(slot);
+        let game_mods = game.mods - self.allowed - required - optional;
+
+        if !game_mods.is_empty() {
+            violations.push(Violation::GameMods(game_mods));
+        }
+
+        for score in &game.scores {
+            let mods = (game.mods | score.enabled_mods) - self.allowed;
+            let extra = mods - required - optional;
+
+            if !mods.contains(required) || !extra.is_empty() {
+                violations.push(Violation::PlayerMods { user_id: score.user_id, mods });
+            }
+        }
+
+        violations
+    }
+
+    /// The mods that a slot must and may be played with.
+    fn rules(&self, slot: Slot) -> (Mods, Mods) {
+        match slot.category {
+            Category::DoubleTime => (Mods::DOUBLE_TIME, Mods::NIGHTCORE),
+            category if category.is_free_mod() => (Mods::NONE, self.free_mods),
+            category => (category.mods(), Mods::NONE),
+        }
+    }
+
+    /// The IDs of the slots' beatmaps that have not been retrieved.
+    pub fn missing(&self) -> Vec<i64> {
+        let mut missing = self.slots.values()
+            .filter(|id| !self.beatmaps.contains_key(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        missing.sort();
+        missing.dedup();
+
+        missing
+    }
+
+    /// Caches a retrieved beatmap.
+    pub fn cache(&mut self, beatmap: Beatmap) {
+        self.beatmaps.insert(beatmap.beatmap_id, beatmap);
+    }
+
+    /// Retrieves the beatmaps that have not been retrieved, each with the
+    /// given function, such as a call to `get_beatmaps` for its ID.
+    ///
+    /// Returns [`Error::Parse`] if a beatmap does not exist. The beatmaps
+    /// retrieved before an error are still cached.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn fetch<F>(&mut self, mut fetch: F) -> Result<()>
+        where F: FnMut(i64) -> Result<Vec<Beatmap>> {
+        for id in self.missing() {
+            let beatmap = fetch(id)?.into_iter()
+                .find(|beatmap| beatmap.beatmap_id == id)
+                .ok_or_else(|| Error::Parse(format!("beatmap {} does not exist", id)))?;
+
+            self.cache(beatmap);
+        }
+
+        Ok(())
+    }
+
+    /// A slot's beatmap with the slot's mods applied, if it has been
+    /// retrieved.
+    pub fn beatmap(&self, slot: Slot) -> Option<PoolBeatmap> {
+        self.slots.get(&slot)
+            .and_then(|id| self.beatmaps.get(id))
+            .map(|beatmap| PoolBeatmap::new(slot, beatmap.clone()))
+    }
+
+    /// The retrieved beatmaps of the slots with their mods applied, in slot
+    /// order.
+    pub fn beatmaps(&self) -> Vec<PoolBeatmap> {
+        self.slots.keys().filter_map(|&slot| self.beatmap(slot)).collect()
+    }
+}
+
+/// Retrieves the beatmaps of a pool that have not been retrieved, to be
+/// cached with [`Mappool::cache`].
+///
+/// Requires the `hyper-support` feature.
+///
+/// [`Mappool::cache`]: struct.Mappool.html#method.cache
+#[cfg(feature = "hyper")]
+pub fn fetch_missing<B, C>(pool: &Mappool, client: &Client<C, B>, key: &str)
+    -> Box<Future<Item = Vec<Beatmap>, Error = Error>>
+    where B: Stream<Error = HyperError> + 'static,
+          B::Item: AsRef<[u8]>,
+          C: Connect {
+    let requests = pool.missing().into_iter().map(|id| {
+        client.get_beatmaps(key, move |req| req.beatmap_id(id as u64))
+    }).collect::<Vec<_>>();
+
+    Box::new(future::join_all(requests).map(|pages| pages.into_iter().flatten().collect()))
+}
+
+/// Retrieves and caches the beatmaps of a pool that have not been
+/// retrieved.
+///
+/// Requires the `reqwest-support` feature.
+#[cfg(feature = "reqwest")]
+pub fn fetch_blocking(pool: &mut Mappool, client: &ReqwestClient, key: &str) -> Result<()> {
+    pool.fetch(|id| client.get_beatmaps(key, |req| req.beatmap_id(id as u64)))
+}
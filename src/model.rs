@@ -3,7 +3,7 @@ use std::fmt::Display;
 use std::result::Result as StdResult;
 use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Approval {
     #[serde(rename = "2")]
     Approved,
@@ -21,7 +21,7 @@ pub enum Approval {
     WIP,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum Genre {
     #[serde(rename = "3")]
     Anime,
@@ -45,7 +45,7 @@ pub enum Genre {
     VideoGame,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum Language {
     #[serde(rename = "0")]
     Any,
@@ -73,7 +73,7 @@ pub enum Language {
     Other,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PlayMode {
     #[serde(rename = "2")]
     CatchTheBeat,
@@ -85,7 +85,7 @@ pub enum PlayMode {
     Taiko,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum ScoringType {
     #[serde(rename = "1")]
     Accuracy,
@@ -97,7 +97,7 @@ pub enum ScoringType {
     ScoreV2,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
 pub enum TeamType {
     #[serde(rename = "0")]
     HeadToHead,
@@ -0,0 +1,480 @@
+//! Reading of `.osz` beatmap set archives.
+//!
+//! An archive is read into a [`BeatmapSet`], listing every file within it and
+//! parsing each `.osu` difficulty's general, metadata, difficulty and event
+//! sections, its timing points and hit objects, along with any storyboards.
+//!
+//! Requires the `osz-support` feature.
+//!
+//! [`BeatmapSet`]: struct.BeatmapSet.html
+
+use md5;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use zip::ZipArchive;
+use ::hitobject::{HitObject, Position};
+use ::pp;
+use ::slider::Slider;
+use ::stacking;
+use ::storyboard::Storyboard;
+use ::timing::{Timing, TimingPoint};
+use ::{Beatmap, Error, Mods, Result};
+
+/// The contents of a `.osz` archive.
+#[derive(Clone, Debug)]
+pub struct BeatmapSet {
+    /// The difficulties in the set, in archive order.
+    pub difficulties: Vec<Difficulty>,
+    /// Every file in the archive, including the difficulties.
+    pub files: Vec<ArchiveFile>,
+    /// The storyboard shared by all difficulties, from the set's `.osb` file.
+    pub storyboard: Option<Storyboard>,
+}
+
+impl BeatmapSet {
+    /// Reads a beatmap set from a `.osz` archive.
+    ///
+    /// Returns [`Error::Parse`] if a difficulty's sections or a storyboard
+    /// are malformed.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn read<R: Read + Seek>(reader: R) -> Result<BeatmapSet> {
+        let mut archive = ZipArchive::new(reader)?;
+        let mut set = BeatmapSet {
+            difficulties: vec![],
+            files: vec![],
+            storyboard: None,
+        };
+
+        for idx in 0..archive.len() {
+            let mut file = archive.by_index(idx)?;
+            let name = file.name().to_owned();
+            let lowercase = name.to_lowercase();
+
+            let kind = if lowercase.ends_with(".osu") {
+                let mut bytes = vec![];
+                file.read_to_end(&mut bytes)?;
+
+                let difficulty = Difficulty::parse(&name, &bytes)
+                    .map_err(|why| prefix_error(&name, why))?;
+                set.difficulties.push(difficulty);
+
+                FileKind::Beatmap
+            } else if lowercase.ends_with(".osb") {
+                let mut bytes = vec![];
+                file.read_to_end(&mut bytes)?;
+
+                let storyboard = Storyboard::parse(&String::from_utf8_lossy(&bytes))
+                    .map_err(|why| prefix_error(&name, why))?;
+
+                match set.storyboard {
+                    Some(ref mut existing) => existing.extend(storyboard),
+                    None => set.storyboard = Some(storyboard),
+                }
+
+                FileKind::Storyboard
+            } else {
+                FileKind::Other
+            };
+
+            set.files.push(ArchiveFile {
+                name,
+                kind,
+                size: file.size(),
+            });
+        }
+
+        // Classify the files that the difficulties refer to.
+        for file in &mut set.files {
+            if file.kind != FileKind::Other {
+                continue;
+            }
+
+            for difficulty in &set.difficulties {
+                if eq_path(difficulty.audio_file.as_ref(), &file.name) {
+                    file.kind = FileKind::Audio;
+                } else if eq_path(difficulty.background_file.as_ref(), &file.name) {
+                    file.kind = FileKind::Background;
+                } else if eq_path(difficulty.video_file.as_ref(), &file.name) {
+                    file.kind = FileKind::Video;
+                }
+            }
+        }
+
+        Ok(set)
+    }
+
+    /// The ID of the beatmap set, as given by the first difficulty that
+    /// specifies one.
+    pub fn beatmapset_id(&self) -> Option<i64> {
+        self.difficulties.iter().filter_map(|d| d.beatmapset_id).next()
+    }
+
+    /// Retrieves the files of a given kind.
+    pub fn files_of_kind(&self, kind: FileKind) -> Vec<&ArchiveFile> {
+        self.files.iter().filter(|file| file.kind == kind).collect()
+    }
+
+    /// Verifies the MD5 hash of each difficulty against the
+    /// [`Beatmap::file_md5`] of the beatmaps retrieved from the API for the
+    /// set.
+    ///
+    /// Difficulties are matched to beatmaps by their beatmap ID, or by their
+    /// version name if the `.osu` file does not specify an ID.
+    ///
+    /// [`Beatmap::file_md5`]: ../struct.Beatmap.html#structfield.file_md5
+    pub fn verify(&self, beatmaps: &[Beatmap]) -> Vec<Verification> {
+        let mut matched = vec![false; beatmaps.len()];
+        let mut results = vec![];
+
+        for difficulty in &self.difficulties {
+            let found = beatmaps.iter().position(|beatmap| {
+                match difficulty.beatmap_id {
+                    Some(id) => beatmap.beatmap_id == id,
+                    None => beatmap.version == difficulty.version,
+                }
+            });
+
+            let idx = match found {
+                Some(idx) => idx,
+                None => {
+                    results.push(Verification::Unlisted {
+                        file_name: difficulty.file_name.clone(),
+                    });
+
+                    continue;
+                },
+            };
+
+            matched[idx] = true;
+            let beatmap = &beatmaps[idx];
+
+            if beatmap.file_md5.eq_ignore_ascii_case(&difficulty.file_md5) {
+                results.push(Verification::Match {
+                    beatmap_id: beatmap.beatmap_id,
+                    file_name: difficulty.file_name.clone(),
+                });
+            } else {
+                results.push(Verification::Mismatch {
+                    beatmap_id: beatmap.beatmap_id,
+                    file_name: difficulty.file_name.clone(),
+                    expected: beatmap.file_md5.clone(),
+                    actual: difficulty.file_md5.clone(),
+                });
+            }
+        }
+
+        for (beatmap, _) in beatmaps.iter().zip(matched).filter(|&(_, m)| !m) {
+            results.push(Verification::Missing {
+                beatmap_id: beatmap.beatmap_id,
+            });
+        }
+
+        results
+    }
+}
+
+/// A file within an archive.
+#[derive(Clone, Debug)]
+pub struct ArchiveFile {
+    /// The path of the file within the archive.
+    pub name: String,
+    pub kind: FileKind,
+    /// The uncompressed size in bytes.
+    pub size: u64,
+}
+
+/// The role of a file within a beatmap set.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FileKind {
+    /// A song file referenced by a difficulty.
+    Audio,
+    /// A background image referenced by a difficulty.
+    Background,
+    /// A `.osu` difficulty.
+    Beatmap,
+    /// A `.osb` storyboard.
+    Storyboard,
+    /// A video referenced by a difficulty.
+    Video,
+    /// Any other file, such as hitsounds, skin elements and storyboard
+    /// images.
+    Other,
+}
+
+/// The parsed sections of a `.osu` difficulty.
+///
+/// Field names and types match those of [`Beatmap`] where they correspond.
+///
+/// [`Beatmap`]: ../struct.Beatmap.html
+#[derive(Clone, Debug)]
+pub struct Difficulty {
+    pub file_name: String,
+    pub file_md5: String,
+    /// The version of the `.osu` file format, such as `14`.
+    pub format_version: u32,
+    /// The beatmap ID, if the difficulty has been submitted.
+    pub beatmap_id: Option<i64>,
+    /// The beatmap set ID, if the set has been submitted.
+    pub beatmapset_id: Option<i64>,
+    pub artist: String,
+    pub title: String,
+    pub creator: String,
+    pub version: String,
+    pub source: String,
+    /// A list of tags, separated by spaces.
+    pub tags: String,
+    pub mode: i64,
+    pub diff_approach: f64,
+    pub diff_drain: f64,
+    pub diff_overall: f64,
+    pub diff_size: f64,
+    /// The distance that sliders travel per beat, in hundreds of
+    /// osu!pixels.
+    pub slider_multiplier: f64,
+    /// The number of slider ticks per beat.
+    pub slider_tick_rate: f64,
+    /// How close in time objects must be to stack, as a multiple of the
+    /// time that they appear for.
+    pub stack_leniency: f64,
+    pub audio_file: Option<String>,
+    pub background_file: Option<String>,
+    pub video_file: Option<String>,
+    /// The difficulty-specific storyboard from the `[Events]` section.
+    pub storyboard: Storyboard,
+    /// The start and end times of each break in milliseconds.
+    pub breaks: Vec<(f64, f64)>,
+    /// The timing points, ordered by time.
+    pub timing_points: Vec<TimingPoint>,
+    /// The hit objects, ordered by time.
+    pub hit_objects: Vec<HitObject>,
+}
+
+impl Difficulty {
+    /// Parses a difficulty from the contents of a `.osu` file.
+    pub fn parse(file_name: &str, bytes: &[u8]) -> Result<Difficulty> {
+        let content = String::from_utf8_lossy(bytes);
+        let mut values = HashMap::new();
+        let mut background_file = None;
+        let mut video_file = None;
+        let mut breaks = vec![];
+        let mut timing_points = vec![];
+        let mut hit_objects = vec![];
+        let mut format_version = None;
+        let mut section = "";
+
+        for line in content.lines().map(|line| line.trim().trim_start_matches('\u{feff}')) {
+            if format_version.is_none() && line.starts_with("osu file format v") {
+                format_version = line["osu file format v".len()..].parse::<u32>().ok();
+
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = &line[1..line.len() - 1];
+
+                continue;
+            }
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            match section {
+                "General" | "Metadata" | "Difficulty" => {
+                    if let Some(idx) = line.find(':') {
+                        let value = line[idx + 1..].trim();
+
+                        values.insert(&line[..idx], value);
+                    }
+                },
+                "Events" => {
+                    let fields = line.split(',').collect::<Vec<_>>();
+
+                    match fields[0] {
+                        "0" if fields.len() >= 3 => {
+                            background_file = Some(unquote(fields[2]));
+                        },
+                        "1" | "Video" if fields.len() >= 3 => {
+                            video_file = Some(unquote(fields[2]));
+                        },
+                        "2" | "Break" if fields.len() >= 3 => {
+                            match (fields[1].trim().parse::<f64>(), fields[2].trim().parse::<f64>()) {
+                                (Ok(start), Ok(end)) => breaks.push((start, end)),
+                                _ => return Err(Error::Parse(format!("invalid break {:?}", line))),
+                            }
+                        },
+                        _ => {},
+                    }
+                },
+                "TimingPoints" => timing_points.push(line.parse::<TimingPoint>()?),
+                "HitObjects" => hit_objects.push(line.parse::<HitObject>()?),
+                _ => {},
+            }
+        }
+
+        // Sorting is stable, so points and objects at the same time keep
+        // their order in the file.
+        timing_points.sort_by(|a: &TimingPoint, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+        hit_objects.sort_by(|a: &HitObject, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        let text = |key: &str| values.get(key).map_or_else(String::new, |v| v.to_string());
+        let number = |key: &str| -> Result<Option<f64>> {
+            match values.get(key) {
+                Some(value) => value.parse().map(Some).map_err(|_| {
+                    Error::Parse(format!("invalid {}: {:?}", key, value))
+                }),
+                None => Ok(None),
+            }
+        };
+        let id = |key: &str| -> Result<Option<i64>> {
+            Ok(number(key)?.map(|id| id as i64).and_then(|id| {
+                if id > 0 { Some(id) } else { None }
+            }))
+        };
+
+        let diff_overall = number("OverallDifficulty")?.unwrap_or(5.0);
+
+        Ok(Difficulty {
+            file_name: file_name.to_owned(),
+            file_md5: format!("{:x}", md5::compute(bytes)),
+            format_version: format_version.unwrap_or(14),
+            beatmap_id: id("BeatmapID")?,
+            beatmapset_id: id("BeatmapSetID")?,
+            artist: text("Artist"),
+            title: text("Title"),
+            creator: text("Creator"),
+            version: text("Version"),
+            source: text("Source"),
+            tags: text("Tags"),
+            mode: number("Mode")?.unwrap_or(0.0) as i64,
+            // Files older than v8 share one value for approach rate and
+            // overall difficulty.
+            diff_approach: number("ApproachRate")?.unwrap_or(diff_overall),
+            diff_drain: number("HPDrainRate")?.unwrap_or(5.0),
+            diff_overall,
+            diff_size: number("CircleSize")?.unwrap_or(5.0),
+            slider_multiplier: number("SliderMultiplier")?.unwrap_or(1.4),
+            slider_tick_rate: number("SliderTickRate")?.unwrap_or(1.0),
+            stack_leniency: number("StackLeniency")?.unwrap_or(0.7),
+            audio_file: values.get("AudioFilename").map(|v| v.to_string()),
+            background_file,
+            video_file,
+            storyboard: Storyboard::parse(&content)?,
+            breaks,
+            timing_points,
+            hit_objects,
+        })
+    }
+
+    /// The difficulty's timing points as a [`Timing`].
+    ///
+    /// [`Timing`]: ../timing/struct.Timing.html
+    pub fn timing(&self) -> Timing {
+        Timing::new(self.timing_points.clone())
+    }
+
+    /// The calculated slider of each hit object that is one, in the same
+    /// order as [`hit_objects`].
+    ///
+    /// [`hit_objects`]: #structfield.hit_objects
+    pub fn sliders(&self) -> Vec<Option<Slider>> {
+        let timing = self.timing();
+
+        self.hit_objects.iter().map(|object| {
+            Slider::new(object, &timing, self.slider_multiplier, self.slider_tick_rate)
+        }).collect()
+    }
+
+    /// The stack height of each hit object, for the HardRock and Easy
+    /// changes to approach rate of a set of mods.
+    pub fn stack_heights(&self, mods: Mods) -> Vec<i32> {
+        stacking::stack_heights(
+            &self.hit_objects,
+            &self.sliders(),
+            pp::approach_rate(self.diff_approach, without_clock_rate(mods)),
+            self.stack_leniency,
+            self.format_version,
+        )
+    }
+
+    /// The position of each hit object as played with a set of mods, with
+    /// stacking applied and flipped vertically by HardRock.
+    pub fn stacked_positions(&self, mods: Mods) -> Vec<Position> {
+        let circle_size = pp::circle_size(self.diff_size, mods);
+
+        self.hit_objects.iter().zip(self.stack_heights(mods)).map(|(object, height)| {
+            let mut position = object.position;
+
+            if mods.contains(Mods::HARD_ROCK) {
+                position.y = 384.0 - position.y;
+            }
+
+            position + stacking::stack_offset(height, circle_size)
+        }).collect()
+    }
+}
+
+/// The result of verifying a difficulty against the API's beatmaps, as
+/// returned by [`BeatmapSet::verify`].
+///
+/// [`BeatmapSet::verify`]: struct.BeatmapSet.html#method.verify
+#[derive(Clone, Debug)]
+pub enum Verification {
+    /// The difficulty's hash matches the beatmap's.
+    Match {
+        beatmap_id: i64,
+        file_name: String,
+    },
+    /// The difficulty's hash differs from the beatmap's.
+    Mismatch {
+        beatmap_id: i64,
+        file_name: String,
+        expected: String,
+        actual: String,
+    },
+    /// The beatmap has no corresponding difficulty in the archive.
+    Missing {
+        beatmap_id: i64,
+    },
+    /// The difficulty has no corresponding beatmap in the API's data.
+    Unlisted {
+        file_name: String,
+    },
+}
+
+impl Verification {
+    /// Whether the difficulty was found and its hash matches.
+    #[allow(clippy::match_like_matches_macro)]
+    pub fn is_match(&self) -> bool {
+        match *self {
+            Verification::Match { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// Removes the mods that change the clock rate, which do not change how long
+/// objects appear for relative to the song.
+fn without_clock_rate(mods: Mods) -> Mods {
+    mods - (Mods::DOUBLE_TIME | Mods::NIGHTCORE | Mods::HALF_TIME)
+}
+
+fn eq_path(referenced: Option<&String>, name: &str) -> bool {
+    match referenced {
+        Some(path) => path.replace('\\', "/").eq_ignore_ascii_case(name),
+        None => false,
+    }
+}
+
+fn prefix_error(name: &str, err: Error) -> Error {
+    match err {
+        Error::Parse(why) => Error::Parse(format!("{}: {}", name, why)),
+        other => other,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_owned()
+}
@@ -0,0 +1,259 @@
+//! Paging through `get_beatmaps` with its `since` parameter.
+//!
+//! The API returns at most [`PAGE_LIMIT`] beatmaps per request, in order of
+//! their approval date, from the date given by `since`. Each page is
+//! requested from the approval date of the last beatmap of the previous
+//! page, so beatmaps approved at that date are returned again; these are
+//! skipped by remembering which have been seen in a [`BeatmapCursor`].
+//!
+//! The cursor is serializable, and is updated as each beatmap is yielded, so
+//! a crawl can be resumed from where it stopped.
+//!
+//! Beatmaps without an approval date cannot be paged through, and are
+//! skipped.
+//!
+//! # Examples
+//!
+//! Page through all beatmaps with a client of your own, resuming from a
+//! saved cursor:
+//!
+//! ```rust
+//! use osu::paginate::{BeatmapCursor, BeatmapPages};
+//!
+//! let cursor = BeatmapCursor::since("2018-01-01 00:00:00");
+//! let mut pages = BeatmapPages::new(cursor, |cursor| {
+//!     let _request = cursor.request(Default::default());
+//!
+//!     Ok(vec![])
+//! });
+//!
+//! for beatmap in pages.by_ref() {
+//!     println!("{}", beatmap.unwrap().beatmap_id);
+//! }
+//!
+//! let saved = pages.cursor().clone();
+//! # let _ = saved;
+//! ```
+//!
+//! [`BeatmapCursor`]: struct.BeatmapCursor.html
+//! [`PAGE_LIMIT`]: constant.PAGE_LIMIT.html
+
+use std::collections::VecDeque;
+use ::builder::GetBeatmapsRequest;
+use ::{Beatmap, Error, Result};
+
+#[cfg(feature = "hyper")]
+use futures::{Future, Stream, stream};
+#[cfg(feature = "hyper")]
+use hyper::client::{Client, Connect};
+#[cfg(feature = "hyper")]
+use hyper::Error as HyperError;
+#[cfg(feature = "hyper")]
+use std::cell::RefCell;
+#[cfg(feature = "hyper")]
+use std::rc::Rc;
+#[cfg(feature = "hyper")]
+use ::bridge::hyper::OsuHyperRequester;
+
+/// The maximum number of beatmaps that the API returns per request.
+pub const PAGE_LIMIT: u16 = 500;
+
+/// A position in the beatmaps returned by `get_beatmaps`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BeatmapCursor {
+    /// The approval date of the last beatmap seen, or `None` to start from
+    /// the first beatmap.
+    pub since: Option<String>,
+    /// The IDs of the beatmaps seen that were approved at [`since`].
+    ///
+    /// [`since`]: #structfield.since
+    pub seen: Vec<i64>,
+}
+
+impl BeatmapCursor {
+    /// Creates a cursor starting from the first beatmap.
+    pub fn new() -> BeatmapCursor {
+        BeatmapCursor::default()
+    }
+
+    /// Creates a cursor starting from the beatmaps approved at a date,
+    /// which must be a MySQL date.
+    pub fn since(date: &str) -> BeatmapCursor {
+        BeatmapCursor {
+            since: Some(date.to_owned()),
+            seen: vec![],
+        }
+    }
+
+    /// Sets the parameters of a request for the next page.
+    pub fn request<'a>(&self, request: GetBeatmapsRequest<'a>) -> GetBeatmapsRequest<'a> {
+        let request = request.limit(PAGE_LIMIT);
+
+        match self.since {
+            Some(ref since) => request.since(since),
+            None => request,
+        }
+    }
+
+    /// Filters a page down to the beatmaps that have not been seen, in the
+    /// order of their approval dates.
+    pub fn filter(&self, page: Vec<Beatmap>) -> Vec<Beatmap> {
+        let mut beatmaps = page.into_iter().filter(|beatmap| match beatmap.approved_date {
+            Some(ref date) => match self.since {
+                Some(ref since) => date > since || (date == since && !self.seen.contains(&beatmap.beatmap_id)),
+                None => true,
+            },
+            None => false,
+        }).collect::<Vec<_>>();
+
+        beatmaps.sort_by(|a, b| (&a.approved_date, a.beatmap_id).cmp(&(&b.approved_date, b.beatmap_id)));
+        beatmaps.dedup_by_key(|beatmap| beatmap.beatmap_id);
+
+        beatmaps
+    }
+
+    /// Records a beatmap as seen.
+    pub fn record(&mut self, beatmap: &Beatmap) {
+        let date = match beatmap.approved_date {
+            Some(ref date) => date,
+            None => return,
+        };
+
+        let later = match self.since {
+            Some(ref since) => date > since,
+            None => true,
+        };
+
+        if later {
+            self.since = Some(date.clone());
+            self.seen.clear();
+        }
+
+        if self.since.as_ref() == Some(date) && !self.seen.contains(&beatmap.beatmap_id) {
+            self.seen.push(beatmap.beatmap_id);
+        }
+    }
+
+    /// Filters a page, returning its new beatmaps and whether there are more
+    /// pages after it, or an error if the cursor cannot advance past it.
+    fn next_page(&self, page: Vec<Beatmap>) -> Result<(Vec<Beatmap>, bool)> {
+        let full = page.len() >= PAGE_LIMIT as usize;
+        let beatmaps = self.filter(page);
+
+        if full && beatmaps.is_empty() {
+            return Err(Error::Parse(format!(
+                "more than {} beatmaps were approved at {}",
+                PAGE_LIMIT,
+                self.since.as_ref().map_or("the same time", |since| since),
+            )));
+        }
+
+        Ok((beatmaps, full))
+    }
+}
+
+/// An iterator over the beatmaps of `get_beatmaps`, requesting a page at a
+/// time.
+///
+/// The iterator ends when a page is not full. If a request fails, its error
+/// is yielded and the iterator ends, with the cursor at the last beatmap
+/// yielded.
+pub struct BeatmapPages<F> {
+    buffer: VecDeque<Beatmap>,
+    cursor: BeatmapCursor,
+    done: bool,
+    fetch: F,
+}
+
+impl<F> BeatmapPages<F> where F: FnMut(&BeatmapCursor) -> Result<Vec<Beatmap>> {
+    /// Creates an iterator from a cursor, retrieving each page with the
+    /// given function, such as a call to `get_beatmaps` with the request
+    /// set by [`BeatmapCursor::request`].
+    ///
+    /// [`BeatmapCursor::request`]: struct.BeatmapCursor.html#method.request
+    pub fn new(cursor: BeatmapCursor, fetch: F) -> BeatmapPages<F> {
+        BeatmapPages {
+            buffer: VecDeque::new(),
+            cursor,
+            done: false,
+            fetch,
+        }
+    }
+
+    /// The cursor after the last beatmap yielded, to save to resume later.
+    pub fn cursor(&self) -> &BeatmapCursor {
+        &self.cursor
+    }
+}
+
+impl<F> Iterator for BeatmapPages<F> where F: FnMut(&BeatmapCursor) -> Result<Vec<Beatmap>> {
+    type Item = Result<Beatmap>;
+
+    fn next(&mut self) -> Option<Result<Beatmap>> {
+        loop {
+            if let Some(beatmap) = self.buffer.pop_front() {
+                self.cursor.record(&beatmap);
+
+                return Some(Ok(beatmap));
+            }
+
+            if self.done {
+                return None;
+            }
+
+            let page = (self.fetch)(&self.cursor)
+                .and_then(|page| self.cursor.next_page(page));
+
+            match page {
+                Ok((beatmaps, more)) => {
+                    self.buffer.extend(beatmaps);
+                    self.done = !more;
+                },
+                Err(why) => {
+                    self.done = true;
+
+                    return Some(Err(why));
+                },
+            }
+        }
+    }
+}
+
+/// Streams the beatmaps of `get_beatmaps`, requesting a page at a time with
+/// the parameters set by the given function and the cursor.
+///
+/// The stream ends when a page is not full, or after the first error. The
+/// cursor is shared so that it can be saved while streaming, and is updated
+/// as each beatmap is yielded.
+///
+/// Requires the `hyper-support` feature.
+#[cfg(feature = "hyper")]
+pub fn stream<B, C, F>(
+    cursor: &Rc<RefCell<BeatmapCursor>>,
+    client: Client<C, B>,
+    key: String,
+    f: F,
+) -> Box<Stream<Item = Beatmap, Error = Error>>
+    where B: Stream<Error = HyperError> + 'static,
+          B::Item: AsRef<[u8]>,
+          C: Connect,
+          F: Fn(GetBeatmapsRequest) -> GetBeatmapsRequest + 'static {
+    let f = Rc::new(f);
+    let pages_cursor = cursor.clone();
+    let cursor = cursor.clone();
+
+    Box::new(stream::unfold(false, move |done| {
+        if done {
+            return None;
+        }
+
+        let (f, request_cursor) = (f.clone(), pages_cursor.borrow().clone());
+        let pages_cursor = pages_cursor.clone();
+
+        Some(client.get_beatmaps(&key, move |request| request_cursor.request(f(request)))
+            .and_then(move |page| pages_cursor.borrow().next_page(page))
+            .map(|(beatmaps, more)| (stream::iter_ok(beatmaps), !more)))
+    })
+    .flatten()
+    .inspect(move |beatmap| cursor.borrow_mut().record(beatmap)))
+}
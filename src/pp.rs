@@ -0,0 +1,624 @@
+//! Performance point (pp) calculation from difficulty attributes.
+//!
+//! These implement the osu!-performance formulas for all four modes, as
+//! they were live in early 2019. Difficulty attributes (star rating and its
+//! components) are not calculated here and must be provided by the caller,
+//! while hit counts, combo and score can be taken straight from a
+//! [`Performance`], [`RecentPlay`], [`GameScore`], [`MatchScore`] or
+//! [`LocalScore`].
+//!
+//! Approach rate and overall difficulty are given as their unmodded values,
+//! such as [`Beatmap::diff_approach`], and are adjusted for the score's mods
+//! internally.
+//!
+//! [`Beatmap::diff_approach`]: ../struct.Beatmap.html#structfield.diff_approach
+//! [`GameScore`]: ../struct.GameScore.html
+//! [`LocalScore`]: ../db/struct.LocalScore.html
+//! [`MatchScore`]: ../struct.MatchScore.html
+//! [`Performance`]: ../struct.Performance.html
+//! [`RecentPlay`]: ../struct.RecentPlay.html
+
+use ::db::LocalScore;
+use ::model::*;
+
+/// Difficulty attributes of a beatmap for a single mode and set of mods.
+#[derive(Clone, Debug)]
+pub enum DifficultyAttributes {
+    CatchTheBeat(CatchAttributes),
+    Mania(ManiaAttributes),
+    Standard(StandardAttributes),
+    Taiko(TaikoAttributes),
+}
+
+impl DifficultyAttributes {
+    /// The mode that the attributes are for.
+    pub fn mode(&self) -> PlayMode {
+        match *self {
+            DifficultyAttributes::CatchTheBeat(_) => PlayMode::CatchTheBeat,
+            DifficultyAttributes::Mania(_) => PlayMode::Mania,
+            DifficultyAttributes::Standard(_) => PlayMode::Standard,
+            DifficultyAttributes::Taiko(_) => PlayMode::Taiko,
+        }
+    }
+
+    /// The star rating.
+    pub fn stars(&self) -> f64 {
+        match *self {
+            DifficultyAttributes::CatchTheBeat(ref attrs) => attrs.stars,
+            DifficultyAttributes::Mania(ref attrs) => attrs.stars,
+            DifficultyAttributes::Standard(ref attrs) => attrs.stars,
+            DifficultyAttributes::Taiko(ref attrs) => attrs.stars,
+        }
+    }
+
+    /// The maximum achievable combo, as used for full combo calculations.
+    pub fn max_combo(&self) -> i64 {
+        match *self {
+            DifficultyAttributes::CatchTheBeat(ref attrs) => attrs.max_combo,
+            DifficultyAttributes::Mania(ref attrs) => attrs.note_count,
+            DifficultyAttributes::Standard(ref attrs) => attrs.max_combo,
+            DifficultyAttributes::Taiko(ref attrs) => attrs.max_combo,
+        }
+    }
+}
+
+/// Difficulty attributes of an osu!catch beatmap, or of a standard beatmap
+/// converted to catch.
+#[derive(Clone, Debug)]
+pub struct CatchAttributes {
+    /// The star rating, with the mods applied.
+    pub stars: f64,
+    /// The unmodded approach rate.
+    pub approach_rate: f64,
+    /// The number of fruits and droplets, excluding tiny droplets.
+    pub max_combo: i64,
+}
+
+/// Difficulty attributes of an osu!mania beatmap, or of a standard beatmap
+/// converted to mania.
+#[derive(Clone, Debug)]
+pub struct ManiaAttributes {
+    /// The star rating, with the mods applied.
+    pub stars: f64,
+    /// The unmodded overall difficulty.
+    pub overall_difficulty: f64,
+    /// The number of notes and hold notes.
+    pub note_count: i64,
+}
+
+/// Difficulty attributes of an osu!standard beatmap.
+#[derive(Clone, Debug)]
+pub struct StandardAttributes {
+    /// The star rating, with the mods applied.
+    pub stars: f64,
+    /// The aim component of the star rating.
+    pub aim_strain: f64,
+    /// The speed component of the star rating.
+    pub speed_strain: f64,
+    /// The unmodded approach rate.
+    pub approach_rate: f64,
+    /// The unmodded overall difficulty.
+    pub overall_difficulty: f64,
+    /// The maximum combo, counting each slider's head, ticks, repeats and
+    /// tail.
+    pub max_combo: i64,
+    /// The number of hit circles.
+    pub circle_count: i64,
+    /// The number of sliders.
+    pub slider_count: i64,
+    /// The number of spinners.
+    pub spinner_count: i64,
+}
+
+/// Difficulty attributes of an osu!taiko beatmap, or of a standard beatmap
+/// converted to taiko.
+#[derive(Clone, Debug)]
+pub struct TaikoAttributes {
+    /// The star rating, with the mods applied.
+    pub stars: f64,
+    /// The unmodded overall difficulty.
+    pub overall_difficulty: f64,
+    /// The number of hits, excluding drumrolls and swells.
+    pub max_combo: i64,
+}
+
+/// The hit counts, combo and score of a play.
+///
+/// This can be created from any of the score models, or built by hand to
+/// calculate the value of a hypothetical play.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Score {
+    pub count_100: i64,
+    pub count_300: i64,
+    pub count_50: i64,
+    pub count_geki: i64,
+    pub count_katu: i64,
+    pub count_miss: i64,
+    pub max_combo: i64,
+    pub score: i64,
+}
+
+impl Score {
+    /// Returns the play as if it were a full combo, with every miss counted
+    /// as a 300 instead.
+    ///
+    /// For mania, where the value of a play depends on its score rather than
+    /// its combo, the submitted score is kept.
+    pub fn fc(&self, attributes: &DifficultyAttributes) -> Score {
+        let mut score = *self;
+        score.count_300 += score.count_miss;
+        score.count_miss = 0;
+        score.max_combo = attributes.max_combo();
+
+        score
+    }
+
+    /// Returns the play as if every object were hit perfectly.
+    ///
+    /// The mods are needed to give mania plays the highest score achievable
+    /// with them.
+    pub fn ss(&self, attributes: &DifficultyAttributes, mods: Mods) -> Score {
+        let mut score = Score {
+            max_combo: attributes.max_combo(),
+            ..Score::default()
+        };
+
+        match *attributes {
+            DifficultyAttributes::CatchTheBeat(ref attrs) => {
+                score.count_300 = attrs.max_combo;
+                score.count_50 = self.count_50 + self.count_katu;
+            },
+            DifficultyAttributes::Mania(ref attrs) => {
+                score.count_geki = attrs.note_count;
+                score.score = (1_000_000.0 * mania_score_multiplier(mods)) as i64;
+            },
+            DifficultyAttributes::Standard(ref attrs) => {
+                score.count_300 = attrs.circle_count
+                    + attrs.slider_count
+                    + attrs.spinner_count;
+            },
+            DifficultyAttributes::Taiko(ref attrs) => {
+                score.count_300 = attrs.max_combo;
+            },
+        }
+
+        score
+    }
+}
+
+macro_rules! impl_from_model {
+    ($($model:ty),*) => {
+        $(
+            impl<'a> From<&'a $model> for Score {
+                fn from(model: &'a $model) -> Score {
+                    Score {
+                        count_100: model.count_100,
+                        count_300: model.count_300,
+                        count_50: model.count_50,
+                        count_geki: model.count_geki,
+                        count_katu: model.count_katu,
+                        count_miss: model.count_miss,
+                        max_combo: model.max_combo,
+                        score: model.score,
+                    }
+                }
+            }
+        )*
+    }
+}
+
+impl_from_model!(GameScore, LocalScore, MatchScore, Performance, RecentPlay);
+
+/// Calculates the accuracy of a score, from `0.0` to `1.0`, using the
+/// formula for the given mode.
+pub fn accuracy(mode: PlayMode, score: &Score) -> f64 {
+    let (hit, total) = match mode {
+        PlayMode::CatchTheBeat => {
+            let caught = score.count_300 + score.count_100 + score.count_50;
+
+            (caught as f64, caught + score.count_katu + score.count_miss)
+        },
+        PlayMode::Mania => {
+            let hit = score.count_50 * 50
+                + score.count_100 * 100
+                + score.count_katu * 200
+                + (score.count_300 + score.count_geki) * 300;
+            let total = score.count_geki
+                + score.count_300
+                + score.count_katu
+                + score.count_100
+                + score.count_50
+                + score.count_miss;
+
+            (hit as f64 / 300.0, total)
+        },
+        PlayMode::Standard => {
+            let hit = score.count_50 + score.count_100 * 2 + score.count_300 * 6;
+            let total = score.count_300
+                + score.count_100
+                + score.count_50
+                + score.count_miss;
+
+            (hit as f64 / 6.0, total)
+        },
+        PlayMode::Taiko => {
+            let hit = score.count_300 as f64 + score.count_100 as f64 * 0.5;
+
+            (hit, score.count_300 + score.count_100 + score.count_miss)
+        },
+    };
+
+    if total <= 0 {
+        return 0.0;
+    }
+
+    hit / total as f64
+}
+
+/// Calculates the performance points awarded for a score.
+///
+/// Scores set with Relax, Autopilot or Autoplay are unranked and are worth
+/// nothing.
+pub fn calculate(attributes: &DifficultyAttributes, mods: Mods, score: &Score)
+    -> f64 {
+    if mods.intersects(Mods::RELAX | Mods::RELAX2 | Mods::AUTOPLAY) {
+        return 0.0;
+    }
+
+    match *attributes {
+        DifficultyAttributes::CatchTheBeat(ref attrs) => catch(attrs, mods, score),
+        DifficultyAttributes::Mania(ref attrs) => mania(attrs, mods, score),
+        DifficultyAttributes::Standard(ref attrs) => standard(attrs, mods, score),
+        DifficultyAttributes::Taiko(ref attrs) => taiko(attrs, mods, score),
+    }
+}
+
+/// The speed multiplier applied by DoubleTime, Nightcore or HalfTime.
+pub fn clock_rate(mods: Mods) -> f64 {
+    if mods.intersects(Mods::DOUBLE_TIME | Mods::NIGHTCORE) {
+        1.5
+    } else if mods.contains(Mods::HALF_TIME) {
+        0.75
+    } else {
+        1.0
+    }
+}
+
+/// Applies the HardRock, Easy and clock rate changes of a set of mods to an
+/// unmodded approach rate.
+pub fn approach_rate(approach_rate: f64, mods: Mods) -> f64 {
+    let ar = scale_difficulty(approach_rate, mods, 1.4);
+
+    let preempt = if ar < 5.0 {
+        1800.0 - 120.0 * ar
+    } else {
+        1200.0 - 150.0 * (ar - 5.0)
+    } / clock_rate(mods);
+
+    if preempt > 1200.0 {
+        (1800.0 - preempt) / 120.0
+    } else {
+        5.0 + (1200.0 - preempt) / 150.0
+    }
+}
+
+/// Applies the HardRock, Easy and clock rate changes of a set of mods to an
+/// unmodded overall difficulty, using osu!standard's hit windows.
+pub fn overall_difficulty(overall_difficulty: f64, mods: Mods) -> f64 {
+    let od = scale_difficulty(overall_difficulty, mods, 1.4);
+    let window = (80.0 - 6.0 * od) / clock_rate(mods);
+
+    (80.0 - window) / 6.0
+}
+
+/// Applies the HardRock and Easy changes of a set of mods to an unmodded
+/// circle size.
+pub fn circle_size(circle_size: f64, mods: Mods) -> f64 {
+    scale_difficulty(circle_size, mods, 1.3)
+}
+
+/// Applies the HardRock and Easy changes of a set of mods to an unmodded HP
+/// drain rate.
+pub fn drain_rate(drain_rate: f64, mods: Mods) -> f64 {
+    scale_difficulty(drain_rate, mods, 1.4)
+}
+
+fn scale_difficulty(value: f64, mods: Mods, hard_rock: f64) -> f64 {
+    if mods.contains(Mods::HARD_ROCK) {
+        (value * hard_rock).min(10.0)
+    } else if mods.contains(Mods::EASY) {
+        value * 0.5
+    } else {
+        value
+    }
+}
+
+fn catch(attrs: &CatchAttributes, mods: Mods, score: &Score) -> f64 {
+    let ar = approach_rate(attrs.approach_rate, mods);
+    let total_hits = (score.count_300 + score.count_100 + score.count_miss) as f64;
+
+    let mut value = (5.0 * (attrs.stars / 0.0049).max(1.0) - 4.0).powi(2) / 100_000.0;
+
+    let mut length_bonus = 0.95 + 0.4 * (total_hits / 3000.0).min(1.0);
+
+    if total_hits > 3000.0 {
+        length_bonus += (total_hits / 3000.0).log10() * 0.5;
+    }
+
+    value *= length_bonus;
+    value *= 0.97f64.powf(score.count_miss as f64);
+    value *= combo_scaling(score.max_combo, attrs.max_combo);
+
+    let mut ar_factor = 1.0;
+
+    if ar > 9.0 {
+        ar_factor += 0.1 * (ar - 9.0);
+    } else if ar < 8.0 {
+        ar_factor += 0.025 * (8.0 - ar);
+    }
+
+    value *= ar_factor;
+
+    if mods.contains(Mods::HIDDEN) {
+        value *= 1.05 + 0.075 * (10.0 - ar.min(10.0));
+    }
+
+    if mods.contains(Mods::FLASHLIGHT) {
+        value *= 1.35 * length_bonus;
+    }
+
+    value *= accuracy(PlayMode::CatchTheBeat, score).powf(5.5);
+
+    if mods.contains(Mods::NO_FAIL) {
+        value *= 0.9;
+    }
+
+    if mods.contains(Mods::SPUN_OUT) {
+        value *= 0.95;
+    }
+
+    value
+}
+
+fn mania(attrs: &ManiaAttributes, mods: Mods, score: &Score) -> f64 {
+    let score_multiplier = mania_score_multiplier(mods);
+
+    if score_multiplier <= 0.0 {
+        return 0.0;
+    }
+
+    let scaled_score = score.score as f64 / score_multiplier;
+
+    let total_hits = (score.count_geki
+        + score.count_300
+        + score.count_katu
+        + score.count_100
+        + score.count_50
+        + score.count_miss) as f64;
+
+    // The game's hit window attribute scales with the clock rate rather than
+    // against it, and is truncated after every adjustment.
+    let mut hit_window = 64.0 - 3.0 * attrs.overall_difficulty;
+
+    if mods.contains(Mods::HARD_ROCK) {
+        hit_window /= 1.4;
+    } else if mods.contains(Mods::EASY) {
+        hit_window *= 1.4;
+    }
+
+    hit_window = (hit_window * clock_rate(mods)).trunc();
+
+    let mut strain = (5.0 * (attrs.stars / 0.2).max(1.0) - 4.0).powf(2.2) / 135.0;
+    strain *= 1.0 + 0.1 * (total_hits / 1500.0).min(1.0);
+
+    strain *= if scaled_score <= 500_000.0 {
+        0.0
+    } else if scaled_score <= 600_000.0 {
+        (scaled_score - 500_000.0) / 100_000.0 * 0.3
+    } else if scaled_score <= 700_000.0 {
+        0.3 + (scaled_score - 600_000.0) / 100_000.0 * 0.25
+    } else if scaled_score <= 800_000.0 {
+        0.55 + (scaled_score - 700_000.0) / 100_000.0 * 0.2
+    } else if scaled_score <= 900_000.0 {
+        0.75 + (scaled_score - 800_000.0) / 100_000.0 * 0.15
+    } else {
+        0.9 + (scaled_score - 900_000.0) / 100_000.0 * 0.1
+    };
+
+    let acc = (0.2 - (hit_window - 34.0) * 0.006667).max(0.0)
+        * strain
+        * ((scaled_score - 960_000.0).max(0.0) / 40_000.0).powf(1.1);
+
+    let mut multiplier = 0.8;
+
+    if mods.contains(Mods::NO_FAIL) {
+        multiplier *= 0.9;
+    }
+
+    if mods.contains(Mods::EASY) {
+        multiplier *= 0.5;
+    }
+
+    (strain.powf(1.1) + acc.powf(1.1)).powf(1.0 / 1.1) * multiplier
+}
+
+fn mania_score_multiplier(mods: Mods) -> f64 {
+    let mut multiplier = 1.0;
+
+    for &m in &[Mods::EASY, Mods::NO_FAIL, Mods::HALF_TIME] {
+        if mods.contains(m) {
+            multiplier *= 0.5;
+        }
+    }
+
+    multiplier
+}
+
+fn standard(attrs: &StandardAttributes, mods: Mods, score: &Score) -> f64 {
+    let ar = approach_rate(attrs.approach_rate, mods);
+    let od = overall_difficulty(attrs.overall_difficulty, mods);
+    let acc = accuracy(PlayMode::Standard, score);
+    let total_hits = (score.count_300
+        + score.count_100
+        + score.count_50
+        + score.count_miss) as f64;
+
+    let mut length_bonus = 0.95 + 0.4 * (total_hits / 2000.0).min(1.0);
+
+    if total_hits > 2000.0 {
+        length_bonus += (total_hits / 2000.0).log10() * 0.5;
+    }
+
+    let miss_penalty = 0.97f64.powf(score.count_miss as f64);
+    let combo_scaling = combo_scaling(score.max_combo, attrs.max_combo);
+
+    // Aim.
+    let mut aim = strain_value(attrs.aim_strain);
+    aim *= length_bonus * miss_penalty * combo_scaling;
+
+    let mut ar_factor = 0.0;
+
+    if ar > 10.33 {
+        ar_factor += 0.3 * (ar - 10.33);
+    } else if ar < 8.0 {
+        ar_factor += 0.01 * (8.0 - ar);
+    }
+
+    aim *= 1.0 + ar_factor.min(ar_factor * (total_hits / 1000.0));
+
+    if mods.contains(Mods::HIDDEN) {
+        aim *= 1.0 + 0.04 * (12.0 - ar);
+    }
+
+    if mods.contains(Mods::FLASHLIGHT) {
+        let mut bonus = 1.0 + 0.35 * (total_hits / 200.0).min(1.0);
+
+        if total_hits > 200.0 {
+            bonus += 0.3 * ((total_hits - 200.0) / 300.0).min(1.0);
+        }
+
+        if total_hits > 500.0 {
+            bonus += (total_hits - 500.0) / 1200.0;
+        }
+
+        aim *= bonus;
+    }
+
+    aim *= 0.5 + acc / 2.0;
+    aim *= 0.98 + od.powi(2) / 2500.0;
+
+    // Speed.
+    let mut speed = strain_value(attrs.speed_strain);
+    speed *= length_bonus * miss_penalty * combo_scaling;
+
+    if ar > 10.33 {
+        let ar_factor = 0.3 * (ar - 10.33);
+
+        speed *= 1.0 + ar_factor.min(ar_factor * (total_hits / 1000.0));
+    }
+
+    if mods.contains(Mods::HIDDEN) {
+        speed *= 1.0 + 0.04 * (12.0 - ar);
+    }
+
+    speed *= 0.02 + acc;
+    speed *= 0.96 + od.powi(2) / 1600.0;
+
+    // Accuracy, which only considers hit circles.
+    let circles = attrs.circle_count as f64;
+    let mut better_acc = 0.0;
+
+    if circles > 0.0 {
+        let non_circles = total_hits - circles;
+        let hit = (score.count_300 as f64 - non_circles) * 6.0
+            + score.count_100 as f64 * 2.0
+            + score.count_50 as f64;
+
+        better_acc = (hit / (circles * 6.0)).max(0.0);
+    }
+
+    let mut acc_value = 1.52163f64.powf(od) * better_acc.powi(24) * 2.83;
+    acc_value *= (circles / 1000.0).powf(0.3).min(1.15);
+
+    if mods.contains(Mods::HIDDEN) {
+        acc_value *= 1.08;
+    }
+
+    if mods.contains(Mods::FLASHLIGHT) {
+        acc_value *= 1.02;
+    }
+
+    let mut multiplier = 1.12;
+
+    if mods.contains(Mods::NO_FAIL) {
+        multiplier *= 0.9;
+    }
+
+    if mods.contains(Mods::SPUN_OUT) {
+        multiplier *= 0.95;
+    }
+
+    (aim.powf(1.1) + speed.powf(1.1) + acc_value.powf(1.1)).powf(1.0 / 1.1)
+        * multiplier
+}
+
+fn taiko(attrs: &TaikoAttributes, mods: Mods, score: &Score) -> f64 {
+    let acc = accuracy(PlayMode::Taiko, score);
+    let total_hits = (score.count_300 + score.count_100 + score.count_miss) as f64;
+
+    let mut strain = (5.0 * (attrs.stars / 0.0075).max(1.0) - 4.0).powi(2) / 100_000.0;
+
+    let length_bonus = 1.0 + 0.1 * (total_hits / 1500.0).min(1.0);
+    strain *= length_bonus;
+    strain *= 0.985f64.powf(score.count_miss as f64);
+
+    if attrs.max_combo > 0 {
+        strain *= ((score.max_combo as f64).powf(0.5) / (attrs.max_combo as f64).powf(0.5)).min(1.0);
+    }
+
+    if mods.contains(Mods::HIDDEN) {
+        strain *= 1.025;
+    }
+
+    if mods.contains(Mods::FLASHLIGHT) {
+        strain *= 1.05 * length_bonus;
+    }
+
+    strain *= acc;
+
+    let od = scale_difficulty(attrs.overall_difficulty, mods, 1.4);
+    let hit_window = (50.0 - 3.0 * od).floor() / clock_rate(mods);
+
+    let acc_value = if hit_window <= 0.0 {
+        0.0
+    } else {
+        (150.0 / hit_window).powf(1.1)
+            * acc.powi(15)
+            * 22.0
+            * (total_hits / 1500.0).powf(0.3).min(1.15)
+    };
+
+    let mut multiplier = 1.1;
+
+    if mods.contains(Mods::NO_FAIL) {
+        multiplier *= 0.9;
+    }
+
+    if mods.contains(Mods::HIDDEN) {
+        multiplier *= 1.1;
+    }
+
+    (strain.powf(1.1) + acc_value.powf(1.1)).powf(1.0 / 1.1) * multiplier
+}
+
+fn combo_scaling(combo: i64, max_combo: i64) -> f64 {
+    if max_combo <= 0 {
+        return 1.0;
+    }
+
+    ((combo as f64).powf(0.8) / (max_combo as f64).powf(0.8)).min(1.0)
+}
+
+fn strain_value(strain: f64) -> f64 {
+    (5.0 * (strain / 0.0675).max(1.0) - 4.0).powi(3) / 100_000.0
+}
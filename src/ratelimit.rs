@@ -0,0 +1,61 @@
+//! Client-side rate limiting of API requests.
+//!
+//! The API allows 1200 requests per minute for each key. A [`RateLimiter`]
+//! spaces requests evenly to stay within a limit, and can be shared between
+//! threads, or between the helpers of this crate that make many requests.
+//!
+//! [`RateLimiter`]: struct.RateLimiter.html
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The number of requests per minute allowed by the API.
+pub const REQUESTS_PER_MINUTE: u32 = 1200;
+
+/// Spaces requests evenly to stay within a number of requests per minute.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Creates a rate limiter allowing a number of requests per minute.
+    pub fn new(requests_per_minute: u32) -> RateLimiter {
+        RateLimiter {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Reserves the next available time for a request, returning how long
+    /// to wait before making it.
+    pub fn reserve(&self) -> Duration {
+        let mut next = self.next.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = Instant::now();
+        let at = if *next > now { *next } else { now };
+        *next = at + self.interval;
+
+        at - now
+    }
+
+    /// Blocks the current thread until a request can be made.
+    pub fn wait(&self) {
+        let delay = self.reserve();
+
+        if delay > Duration::from_millis(0) {
+            thread::sleep(delay);
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    /// Creates a rate limiter allowing the API's limit of
+    /// [`REQUESTS_PER_MINUTE`].
+    ///
+    /// [`REQUESTS_PER_MINUTE`]: constant.REQUESTS_PER_MINUTE.html
+    fn default() -> RateLimiter {
+        RateLimiter::new(REQUESTS_PER_MINUTE)
+    }
+}
@@ -0,0 +1,696 @@
+//! Replay frames, and the analysis of a play against its difficulty.
+//!
+//! A replay (`.osr`) file is read with [`LocalScore::from_replay`], which
+//! keeps the replay's LZMA-compressed frames. [`frames`] decompresses them,
+//! and [`analyze`] matches the key presses in them to the difficulty's hit
+//! objects, giving each object's hit error and judgement along with
+//! statistics of the keys pressed.
+//!
+//! Standard and mania replays can be analyzed. Judgements are reconstructed
+//! with the stable client's hit windows, and can be checked against the
+//! counts in the replay's header with [`ReplayAnalysis::validate`]. Times
+//! are in the beatmap's milliseconds, so hit errors with DoubleTime are one
+//! and a half times those felt by the player.
+//!
+//! Requires the `replay-support` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use osu::db::LocalScore;
+//! use osu::osz::Difficulty;
+//! use osu::replay;
+//! use std::fs::{self, File};
+//! use std::io::BufReader;
+//!
+//! let file = File::open("replay.osr").unwrap();
+//! let score = LocalScore::from_replay(BufReader::new(file)).unwrap();
+//! let bytes = fs::read("Horizon - Flare (Jade Harley) [Normal].osu").unwrap();
+//! let normal = Difficulty::parse("Normal.osu", &bytes).unwrap();
+//!
+//! let frames = replay::frames(score.replay.as_ref().unwrap()).unwrap();
+//! let analysis = replay::analyze(&normal, score.mode, score.enabled_mods, &frames).unwrap();
+//!
+//! println!("{:.2} UR", analysis.unstable_rate().unwrap_or(0.0));
+//! assert!(analysis.validate(&score).is_empty());
+//! ```
+//!
+//! [`LocalScore::from_replay`]: ../db/struct.LocalScore.html#method.from_replay
+//! [`ReplayAnalysis::validate`]: struct.ReplayAnalysis.html#method.validate
+//! [`analyze`]: fn.analyze.html
+//! [`frames`]: fn.frames.html
+
+use lzma_rs::lzma_decompress;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use ::convert;
+use ::db::LocalScore;
+use ::hitobject::{HitObjectKind, Position};
+use ::osz::Difficulty;
+use ::slider::SliderEventKind;
+use ::{pp, Error, Mods, PlayMode, Result};
+
+/// The time of the frame holding the seed of the replay's random numbers,
+/// rather than input.
+const SEED_FRAME_TIME: i64 = -12_345;
+
+/// How early a press may be before a standard object and still miss it,
+/// rather than being ignored, in milliseconds.
+const STANDARD_MISS_WINDOW: f64 = 400.0;
+
+/// The centre of the playfield, which spinners are spun around.
+const SPINNER_CENTRE: Position = Position { x: 256.0, y: 192.0 };
+
+/// The radius of the slider's follow circle relative to its head's.
+const FOLLOW_RADIUS_FACTOR: f64 = 2.4;
+
+/// The judgements in order from best to worst.
+const JUDGEMENTS: [Judgement; 6] = [
+    Judgement::Geki,
+    Judgement::Hit300,
+    Judgement::Katu,
+    Judgement::Hit100,
+    Judgement::Hit50,
+    Judgement::Miss,
+];
+
+bitflags! {
+    /// The buttons held in a frame of a standard replay.
+    ///
+    /// Keyboard keys also set the mouse button that they stand in for.
+    pub struct Keys: u32 {
+        const M1 = 1;
+        const M2 = 1 << 1;
+        const K1 = 1 << 2;
+        const K2 = 1 << 3;
+        const SMOKE = 1 << 4;
+    }
+}
+
+/// A frame of input in a replay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReplayFrame {
+    /// The time in milliseconds.
+    pub time: f64,
+    /// The horizontal position of the cursor in osu!pixels, or in mania a
+    /// bitmask of the held columns.
+    pub x: f64,
+    /// The vertical position of the cursor in osu!pixels.
+    pub y: f64,
+    /// The buttons held, which are always empty in mania.
+    pub keys: Keys,
+}
+
+impl ReplayFrame {
+    /// The columns held in a mania frame, as a bitmask with the first column
+    /// in the lowest bit.
+    pub fn columns(&self) -> u32 {
+        self.x as u32
+    }
+
+    fn position(&self) -> Position {
+        Position::new(self.x, self.y)
+    }
+}
+
+/// Decompresses and parses the frames of a replay, such as the
+/// [`LocalScore::replay`] of a replay file.
+///
+/// Frames store the time since the frame before, which is summed into the
+/// time since the start of the beatmap. The frame holding the seed of the
+/// replay's random numbers is skipped.
+///
+/// Returns [`Error::Parse`] if the data is not LZMA-compressed or a frame is
+/// malformed.
+///
+/// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+/// [`LocalScore::replay`]: ../db/struct.LocalScore.html#structfield.replay
+pub fn frames(data: &[u8]) -> Result<Vec<ReplayFrame>> {
+    let mut decompressed = vec![];
+    lzma_decompress(&mut &data[..], &mut decompressed)
+        .map_err(|why| Error::Parse(format!("invalid replay data: {}", why)))?;
+
+    let text = String::from_utf8(decompressed)
+        .map_err(|_| Error::Parse("invalid replay data: frames are not UTF-8".to_owned()))?;
+    let mut time = 0;
+    let mut frames = vec![];
+
+    for frame in text.split(',').filter(|frame| !frame.is_empty()) {
+        let invalid = || Error::Parse(format!("invalid replay frame {:?}", frame));
+        let fields = frame.split('|').collect::<Vec<_>>();
+
+        if fields.len() != 4 {
+            return Err(invalid());
+        }
+
+        let delta = fields[0].parse::<i64>().map_err(|_| invalid())?;
+
+        if delta == SEED_FRAME_TIME {
+            continue;
+        }
+
+        time += delta;
+
+        frames.push(ReplayFrame {
+            time: time as f64,
+            x: fields[1].parse().map_err(|_| invalid())?,
+            y: fields[2].parse().map_err(|_| invalid())?,
+            keys: Keys::from_bits_truncate(fields[3].parse().map_err(|_| invalid())?),
+        });
+    }
+
+    Ok(frames)
+}
+
+/// The judgement of a hit object.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Judgement {
+    /// A mania MAX, the best judgement of a note.
+    Geki,
+    Hit300,
+    /// A mania 200.
+    Katu,
+    Hit100,
+    Hit50,
+    Miss,
+}
+
+/// A key that can be pressed in a replay.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Key {
+    /// The first mouse button, when not pressed with [`K1`].
+    ///
+    /// [`K1`]: #variant.K1
+    M1,
+    /// The second mouse button, when not pressed with [`K2`].
+    ///
+    /// [`K2`]: #variant.K2
+    M2,
+    K1,
+    K2,
+    /// A mania column, from `0`.
+    Column(u32),
+}
+
+/// The judgement of a hit object in a replay.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    /// The time of the object in milliseconds.
+    pub time: f64,
+    /// The time of the press that hit the object relative to the object,
+    /// which is negative if early.
+    ///
+    /// This is the press on the head of sliders and hold notes, and `None`
+    /// for misses and spinners.
+    pub error: Option<f64>,
+    pub judgement: Judgement,
+}
+
+/// A judgement that was reconstructed a different number of times to the
+/// count in a replay's header.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mismatch {
+    pub judgement: Judgement,
+    /// The count in the header.
+    pub expected: i64,
+    /// The reconstructed count.
+    pub found: i64,
+}
+
+/// The analysis of a replay, as returned by [`analyze`].
+///
+/// [`analyze`]: fn.analyze.html
+#[derive(Clone, Debug)]
+pub struct ReplayAnalysis {
+    pub mode: PlayMode,
+    /// The judgement of each hit object, in the order of the difficulty's
+    /// objects in standard and of the converted notes in mania.
+    pub hits: Vec<Hit>,
+    /// The number of times each key was pressed.
+    pub key_presses: BTreeMap<Key, u32>,
+    /// The tapping speed, as the BPM of 1/4 notes at the median time between
+    /// presses, or `None` if there were fewer than two.
+    ///
+    /// Keys pressed together count once.
+    pub tapping_bpm: Option<f64>,
+}
+
+impl ReplayAnalysis {
+    /// The mean hit error, which is negative if the player tends to hit
+    /// early, or `None` if nothing was hit.
+    pub fn mean_error(&self) -> Option<f64> {
+        let errors = self.errors();
+
+        if errors.is_empty() {
+            return None;
+        }
+
+        Some(errors.iter().sum::<f64>() / errors.len() as f64)
+    }
+
+    /// The unstable rate, which is ten times the standard deviation of the
+    /// hit errors, or `None` if nothing was hit.
+    pub fn unstable_rate(&self) -> Option<f64> {
+        let mean = self.mean_error()?;
+        let errors = self.errors();
+        let variance = errors.iter().map(|error| (error - mean).powi(2)).sum::<f64>() / errors.len() as f64;
+
+        Some(variance.sqrt() * 10.0)
+    }
+
+    /// The number of objects given a judgement.
+    pub fn count(&self, judgement: Judgement) -> i64 {
+        self.hits.iter().filter(|hit| hit.judgement == judgement).count() as i64
+    }
+
+    /// Checks the reconstructed judgements against the counts in the header
+    /// of the replay, giving those that differ.
+    ///
+    /// Gekis and katus are only checked in mania, as standard counts them at
+    /// the end of each combo rather than for each object.
+    pub fn validate(&self, score: &LocalScore) -> Vec<Mismatch> {
+        JUDGEMENTS.iter().filter_map(|&judgement| {
+            let expected = match judgement {
+                Judgement::Geki if self.mode == PlayMode::Mania => score.count_geki,
+                Judgement::Katu if self.mode == PlayMode::Mania => score.count_katu,
+                Judgement::Geki | Judgement::Katu => return None,
+                Judgement::Hit300 => score.count_300,
+                Judgement::Hit100 => score.count_100,
+                Judgement::Hit50 => score.count_50,
+                Judgement::Miss => score.count_miss,
+            };
+            let found = self.count(judgement);
+
+            if expected == found {
+                None
+            } else {
+                Some(Mismatch { judgement, expected, found })
+            }
+        }).collect()
+    }
+
+    fn errors(&self) -> Vec<f64> {
+        self.hits.iter().filter_map(|hit| hit.error).collect()
+    }
+}
+
+/// Analyzes the frames of a replay played on a difficulty in a mode with a
+/// set of mods, which are given in the replay's header.
+///
+/// Standard difficulties may be played in mania, as converted by
+/// [`convert::mania`]. Gives `None` for taiko and catch, and for
+/// difficulties made for a different mode.
+///
+/// In standard, objects must be hit in order, and presses are ignored if
+/// they are outside of the object's circle or more than 400ms early. Sliders
+/// are judged on how many of their head, ticks, repeats and tail are hit,
+/// following the slider with a button held, and spinners on the number of
+/// spins. In mania, hold notes are judged on both the press and release,
+/// and releasing late is not penalised. Converted beatmaps are judged with
+/// the game's fixed windows for converts rather than from their overall
+/// difficulty.
+///
+/// [`convert::mania`]: ../convert/fn.mania.html
+pub fn analyze(difficulty: &Difficulty, mode: PlayMode, mods: Mods, frames: &[ReplayFrame])
+    -> Option<ReplayAnalysis> {
+    let own_mode = match difficulty.mode {
+        1 => PlayMode::Taiko,
+        2 => PlayMode::CatchTheBeat,
+        3 => PlayMode::Mania,
+        _ => PlayMode::Standard,
+    };
+
+    if own_mode != PlayMode::Standard && own_mode != mode {
+        return None;
+    }
+
+    match mode {
+        PlayMode::Standard => Some(standard(difficulty, mods, frames)),
+        PlayMode::Mania => Some(mania(difficulty, mods, frames)),
+        PlayMode::CatchTheBeat | PlayMode::Taiko => None,
+    }
+}
+
+fn standard(difficulty: &Difficulty, mods: Mods, frames: &[ReplayFrame]) -> ReplayAnalysis {
+    let hard_rock = mods.contains(Mods::HARD_ROCK);
+    let od = pp::overall_difficulty(difficulty.diff_overall, mods - (Mods::DOUBLE_TIME | Mods::NIGHTCORE | Mods::HALF_TIME));
+    let windows = [
+        (Judgement::Hit300, 80.0 - 6.0 * od),
+        (Judgement::Hit100, 140.0 - 8.0 * od),
+        (Judgement::Hit50, 200.0 - 10.0 * od),
+    ];
+    let meh_window = windows[2].1;
+    let radius = 32.0 * (1.0 - 0.7 * (pp::circle_size(difficulty.diff_size, mods) - 5.0) / 5.0);
+    let objects = &difficulty.hit_objects;
+    let sliders = difficulty.sliders();
+    let positions = difficulty.stacked_positions(mods);
+
+    let mut key_presses = BTreeMap::new();
+    let mut press_times = vec![];
+    let mut head_errors = vec![None; objects.len()];
+    let mut next = 0;
+    let mut held = Keys::empty();
+
+    for frame in frames {
+        let pressed = frame.keys - held;
+        let mut keys = vec![];
+        held = frame.keys;
+
+        if pressed.contains(Keys::K1) {
+            keys.push(Key::K1);
+        } else if pressed.contains(Keys::M1) {
+            keys.push(Key::M1);
+        }
+
+        if pressed.contains(Keys::K2) {
+            keys.push(Key::K2);
+        } else if pressed.contains(Keys::M2) {
+            keys.push(Key::M2);
+        }
+
+        if keys.is_empty() {
+            continue;
+        }
+
+        for key in keys {
+            *key_presses.entry(key).or_insert(0) += 1;
+        }
+
+        press_times.push(frame.time);
+
+        // Only the first object that can still be hit takes a press.
+        while next < objects.len() && (objects[next].is_spinner() || objects[next].time + meh_window < frame.time) {
+            next += 1;
+        }
+
+        let object = match objects.get(next) {
+            Some(object) => object,
+            None => continue,
+        };
+
+        if frame.time < object.time - STANDARD_MISS_WINDOW || frame.position().distance(positions[next]) > radius {
+            continue;
+        }
+
+        head_errors[next] = Some(frame.time - object.time);
+        next += 1;
+    }
+
+    let hits = objects.iter().enumerate().map(|(idx, object)| {
+        let head = head_errors[idx].map(|error| (error, judge(&windows, error)));
+        let (error, judgement) = match head {
+            Some((error, judgement)) if judgement != Judgement::Miss => (Some(error), judgement),
+            _ => (None, Judgement::Miss),
+        };
+
+        match (&object.kind, sliders[idx].as_ref()) {
+            (&HitObjectKind::Spinner { end_time }, _) => Hit {
+                time: object.time,
+                error: None,
+                judgement: spinner(frames, object.time, end_time, od),
+            },
+            (&HitObjectKind::Slider { .. }, Some(slider)) => {
+                // The offset of the stacked position from the file's
+                // position.
+                let offset = positions[idx] - flip(object.position, hard_rock);
+                let events = slider.events();
+                let followed = events.iter().skip(1).filter(|event| {
+                    // The game judges the tail slightly before the end.
+                    let time = match event.kind {
+                        SliderEventKind::Tail => slider.legacy_last_tick_time(),
+                        _ => event.time,
+                    };
+                    let position = flip(slider.position_at_time(time), hard_rock) + offset;
+
+                    match frame_at(frames, time) {
+                        Some(frame) => frame.keys.intersects(Keys::M1 | Keys::M2)
+                            && frame.position().distance(position) <= radius * FOLLOW_RADIUS_FACTOR,
+                        None => false,
+                    }
+                }).count();
+                let hit = followed + if error.is_some() { 1 } else { 0 };
+
+                Hit {
+                    time: object.time,
+                    error,
+                    judgement: if hit == events.len() {
+                        Judgement::Hit300
+                    } else if hit * 2 >= events.len() {
+                        Judgement::Hit100
+                    } else if hit > 0 {
+                        Judgement::Hit50
+                    } else {
+                        Judgement::Miss
+                    },
+                }
+            },
+            _ => Hit {
+                time: object.time,
+                error,
+                judgement,
+            },
+        }
+    }).collect();
+
+    ReplayAnalysis {
+        mode: PlayMode::Standard,
+        hits,
+        key_presses,
+        tapping_bpm: tapping_bpm(press_times),
+    }
+}
+
+/// Judges a spinner on the spins made with a button held, against the
+/// number needed for the overall difficulty.
+fn spinner(frames: &[ReplayFrame], start_time: f64, end_time: f64, od: f64) -> Judgement {
+    let spins_per_second = if od > 5.0 {
+        5.0 + 2.5 * (od - 5.0) / 5.0
+    } else {
+        5.0 - 2.0 * (5.0 - od) / 5.0
+    };
+    let required = ((end_time - start_time) / 1000.0 * spins_per_second).floor();
+
+    let mut angle = 0.0;
+    let mut last_angle = None;
+
+    for frame in frames.iter().filter(|frame| frame.time >= start_time && frame.time <= end_time) {
+        if !frame.keys.intersects(Keys::M1 | Keys::M2) {
+            last_angle = None;
+
+            continue;
+        }
+
+        let offset = frame.position() - SPINNER_CENTRE;
+        let current = offset.y.atan2(offset.x);
+
+        if let Some(last) = last_angle {
+            let mut delta = current - last;
+
+            if delta > PI {
+                delta -= 2.0 * PI;
+            } else if delta < -PI {
+                delta += 2.0 * PI;
+            }
+
+            angle += delta;
+        }
+
+        last_angle = Some(current);
+    }
+
+    let progress = if required > 0.0 {
+        angle.abs() / (2.0 * PI) / required
+    } else {
+        1.0
+    };
+
+    if progress >= 1.0 {
+        Judgement::Hit300
+    } else if progress > 0.9 {
+        Judgement::Hit100
+    } else if progress > 0.75 {
+        Judgement::Hit50
+    } else {
+        Judgement::Miss
+    }
+}
+
+fn mania(difficulty: &Difficulty, mods: Mods, frames: &[ReplayFrame]) -> ReplayAnalysis {
+    let keys = convert::mania_keys(difficulty, mods);
+    let notes = convert::mania(difficulty, mods);
+    let od = difficulty.diff_overall;
+    let scale = if mods.contains(Mods::HARD_ROCK) {
+        1.0 / 1.4
+    } else if mods.contains(Mods::EASY) {
+        1.4
+    } else {
+        1.0
+    };
+    // Converted beatmaps are played with fixed windows, which only depend on
+    // whether the overall difficulty is above 4.
+    let (great, good, ok, meh, miss) = if difficulty.mode != 3 {
+        if od > 4.0 {
+            (34.0, 67.0, 97.0, 121.0, 158.0)
+        } else {
+            (47.0, 77.0, 97.0, 121.0, 158.0)
+        }
+    } else {
+        (64.0 - 3.0 * od, 97.0 - 3.0 * od, 127.0 - 3.0 * od, 151.0 - 3.0 * od, 188.0 - 3.0 * od)
+    };
+    let windows = [
+        (Judgement::Geki, 16.0 * scale),
+        (Judgement::Hit300, great * scale),
+        (Judgement::Katu, good * scale),
+        (Judgement::Hit100, ok * scale),
+        (Judgement::Hit50, meh * scale),
+    ];
+    let meh_window = windows[4].1;
+    let miss_window = miss * scale;
+
+    // The time of each press in each column, and of its release.
+    let mut presses = vec![vec![]; keys as usize];
+    let mut held = 0;
+
+    for (idx, frame) in frames.iter().enumerate() {
+        let columns = frame.columns();
+
+        for (column, column_presses) in presses.iter_mut().enumerate() {
+            let bit = 1 << column;
+
+            if columns & bit != 0 && held & bit == 0 {
+                let release = frames[idx + 1..].iter()
+                    .find(|frame| frame.columns() & bit == 0)
+                    .map(|frame| frame.time);
+
+                column_presses.push((frame.time, release));
+            }
+        }
+
+        held = columns;
+    }
+
+    let mut hits = notes.iter().map(|note| Hit {
+        time: note.time,
+        error: None,
+        judgement: Judgement::Miss,
+    }).collect::<Vec<_>>();
+
+    for (column, column_presses) in presses.iter().enumerate() {
+        let column_notes = (0..notes.len())
+            .filter(|&idx| notes[idx].column as usize == column)
+            .collect::<Vec<_>>();
+        let mut next = 0;
+
+        for &(time, release) in column_presses {
+            while next < column_notes.len() && notes[column_notes[next]].time + meh_window < time {
+                next += 1;
+            }
+
+            let idx = match column_notes.get(next) {
+                Some(&idx) => idx,
+                None => break,
+            };
+            let note = &notes[idx];
+            let error = time - note.time;
+
+            if error < -miss_window {
+                continue;
+            }
+
+            next += 1;
+
+            let judgement = match note.end_time {
+                Some(end_time) => {
+                    let release_error = release.map_or(0.0, |release| (end_time - release).max(0.0));
+
+                    judge_hold(&windows, error, release_error)
+                },
+                None => judge(&windows, error),
+            };
+
+            if judgement != Judgement::Miss {
+                hits[idx] = Hit {
+                    time: note.time,
+                    error: Some(error),
+                    judgement,
+                };
+            }
+        }
+    }
+
+    let key_presses = presses.iter().enumerate()
+        .filter(|&(_, column_presses)| !column_presses.is_empty())
+        .map(|(column, column_presses)| (Key::Column(column as u32), column_presses.len() as u32))
+        .collect();
+    let press_times = presses.iter().flat_map(|column_presses| {
+        column_presses.iter().map(|&(time, _)| time)
+    }).collect();
+
+    ReplayAnalysis {
+        mode: PlayMode::Mania,
+        hits,
+        key_presses,
+        tapping_bpm: tapping_bpm(press_times),
+    }
+}
+
+/// The best judgement whose window a hit error is within.
+fn judge(windows: &[(Judgement, f64)], error: f64) -> Judgement {
+    windows.iter()
+        .find(|&&(_, window)| error.abs() <= window)
+        .map_or(Judgement::Miss, |&(judgement, _)| judgement)
+}
+
+/// Judges a hold note on the errors of its press and of an early release,
+/// whose sum must be within twice the window, widened for MAX and 300.
+fn judge_hold(windows: &[(Judgement, f64)], error: f64, release_error: f64) -> Judgement {
+    let error = error.abs();
+    let leniencies = [1.2, 1.1, 1.0, 1.0];
+
+    if judge(windows, error) == Judgement::Miss {
+        return Judgement::Miss;
+    }
+
+    windows.iter().zip(&leniencies)
+        .find(|&(&(_, window), leniency)| {
+            error <= window * leniency && error + release_error <= window * leniency * 2.0
+        })
+        .map_or(Judgement::Hit50, |(&(judgement, _), _)| judgement)
+}
+
+/// The last frame at or before a time.
+fn frame_at(frames: &[ReplayFrame], time: f64) -> Option<&ReplayFrame> {
+    match frames.binary_search_by(|frame| frame.time.partial_cmp(&time).unwrap_or(Ordering::Less)) {
+        Ok(idx) => Some(&frames[idx]),
+        Err(0) => None,
+        Err(idx) => Some(&frames[idx - 1]),
+    }
+}
+
+fn flip(position: Position, hard_rock: bool) -> Position {
+    if hard_rock {
+        Position::new(position.x, 384.0 - position.y)
+    } else {
+        position
+    }
+}
+
+fn tapping_bpm(mut press_times: Vec<f64>) -> Option<f64> {
+    press_times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let mut intervals = press_times.windows(2)
+        .map(|pair| pair[1] - pair[0])
+        .filter(|&interval| interval > 0.0)
+        .collect::<Vec<_>>();
+    intervals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let middle = intervals.len() / 2;
+    let median = match intervals.len() {
+        0 => return None,
+        len if len % 2 == 1 => intervals[middle],
+        _ => (intervals[middle - 1] + intervals[middle]) / 2.0,
+    };
+
+    Some(15_000.0 / median)
+}
@@ -0,0 +1,818 @@
+//! Parsing and validation of skins.
+//!
+//! A skin's `skin.ini` is parsed into a [`SkinIni`] of typed values. Like the
+//! client, parsing never fails: unknown keys and invalid values are skipped,
+//! leaving the defaults in place, and are reported as [`Issue`]s.
+//!
+//! Given the files in a skin's folder, [`SkinIni::elements`] lists which of
+//! the images used by a mode the skin provides, and which fall back to the
+//! default skin.
+//!
+//! # Examples
+//!
+//! Parse a `skin.ini` and check which hit circle images it provides:
+//!
+//! ```rust
+//! use osu::skin::SkinIni;
+//! use osu::PlayMode;
+//!
+//! let skin = SkinIni::parse("[General]
+//! Name: Example
+//! CursorRotate: 2
+//! ");
+//!
+//! assert_eq!(skin.general.name, "Example");
+//! assert_eq!(skin.issues.len(), 1);
+//!
+//! let elements = skin.elements(PlayMode::Standard, &["hitcircle@2x.png"]);
+//! let hitcircle = elements.iter().find(|e| e.name == "hitcircle").unwrap();
+//!
+//! assert_eq!(hitcircle.file, Some("hitcircle@2x.png".to_owned()));
+//! ```
+//!
+//! [`Issue`]: struct.Issue.html
+//! [`SkinIni`]: struct.SkinIni.html
+//! [`SkinIni::elements`]: struct.SkinIni.html#method.elements
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use ::PlayMode;
+
+/// Images used in every mode.
+const COMMON_ELEMENTS: &[&str] = &[
+    "arrow-pause", "arrow-warning", "count1", "count2", "count3", "cursor",
+    "cursortrail", "fail-background", "go", "pause-back", "pause-continue",
+    "pause-overlay", "pause-retry", "play-skip", "play-unranked",
+    "play-warningarrow", "ranking-A", "ranking-B", "ranking-C", "ranking-D",
+    "ranking-S", "ranking-SH", "ranking-X", "ranking-XH", "ranking-accuracy",
+    "ranking-graph", "ranking-maxcombo", "ranking-panel", "ranking-perfect",
+    "ranking-title", "ready", "scorebar-bg", "scorebar-colour", "scorebar-ki",
+    "scorebar-kidanger", "scorebar-kidanger2", "section-fail", "section-pass",
+];
+
+const STANDARD_ELEMENTS: &[&str] = &[
+    "approachcircle", "followpoint", "hit0", "hit100", "hit100k", "hit300",
+    "hit300g", "hit300k", "hit50", "hitcircle", "hitcircleoverlay",
+    "hitcircleselect", "lighting", "particle100", "particle300", "particle50",
+    "reversearrow", "sliderb", "sliderfollowcircle", "sliderscorepoint",
+    "spinner-approachcircle", "spinner-background", "spinner-bottom",
+    "spinner-circle", "spinner-clear", "spinner-glow", "spinner-metre",
+    "spinner-middle", "spinner-middle2", "spinner-osu", "spinner-rpm",
+    "spinner-spin", "spinner-top",
+];
+
+const TAIKO_ELEMENTS: &[&str] = &[
+    "pippidonclear", "pippidonfail", "pippidonidle", "pippidonkiai",
+    "sliderscorepoint", "spinner-approachcircle", "spinner-circle",
+    "spinner-warning", "taiko-bar-left", "taiko-bar-right",
+    "taiko-bar-right-glow", "taiko-drum-inner", "taiko-drum-outer",
+    "taiko-flower-group", "taiko-glow", "taiko-hit0", "taiko-hit100",
+    "taiko-hit100k", "taiko-hit300", "taiko-hit300g", "taiko-hit300k",
+    "taiko-roll-end", "taiko-roll-middle", "taiko-slider", "taiko-slider-fail",
+    "taikobigcircle", "taikobigcircleoverlay", "taikohitcircle",
+    "taikohitcircleoverlay",
+];
+
+const CATCH_ELEMENTS: &[&str] = &[
+    "fruit-apple", "fruit-apple-overlay", "fruit-bananas",
+    "fruit-bananas-overlay", "fruit-catcher-fail", "fruit-catcher-idle",
+    "fruit-catcher-kiai", "fruit-drop", "fruit-drop-overlay", "fruit-grapes",
+    "fruit-grapes-overlay", "fruit-orange", "fruit-orange-overlay",
+    "fruit-pear", "fruit-pear-overlay", "fruit-ryuuta", "lighting",
+];
+
+const MANIA_ELEMENTS: &[&str] = &[
+    "lightingL", "lightingN", "mania-hit0", "mania-hit100", "mania-hit200",
+    "mania-hit300", "mania-hit300g", "mania-hit50", "mania-key1",
+    "mania-key1D", "mania-key2", "mania-key2D", "mania-keyS", "mania-keySD",
+    "mania-note1", "mania-note1H", "mania-note1L", "mania-note1T",
+    "mania-note2", "mania-note2H", "mania-note2L", "mania-note2T",
+    "mania-noteS", "mania-noteSH", "mania-noteSL", "mania-noteST",
+    "mania-stage-bottom", "mania-stage-hint", "mania-stage-left",
+    "mania-stage-light", "mania-stage-right", "mania-warningarrow",
+];
+
+/// Colours of a mania configuration that do not belong to a column.
+const MANIA_COLOURS: &[&str] = &[
+    "ColourBarline", "ColourBreak", "ColourColumnLine", "ColourHold",
+    "ColourJudgementLine", "ColourKeyWarning",
+];
+
+/// Images of a mania configuration that do not belong to a column.
+const MANIA_IMAGES: &[&str] = &[
+    "Hit0", "Hit100", "Hit200", "Hit300", "Hit300g", "Hit50", "LightingL",
+    "LightingN", "StageBottom", "StageHint", "StageLeft", "StageLight",
+    "StageRight", "WarningArrow",
+];
+
+/// Settings of a mania configuration that are recognised but not typed.
+const MANIA_OTHER: &[&str] = &[
+    "ComboBurstStyle", "KeyFlipWhenUpsideDown", "KeysUnderNotes",
+    "LightFramePerSecond", "LightingLWidth", "LightingNWidth",
+    "NoteBodyStyle", "NoteFlipWhenUpsideDown", "SeparateScore",
+    "SplitStages", "StageSeparation", "WidthForNoteHeightScale",
+];
+
+/// The contents of a `skin.ini` file.
+#[derive(Clone, Debug, Default)]
+pub struct SkinIni {
+    pub general: General,
+    pub colours: Colours,
+    pub fonts: Fonts,
+    pub catch_the_beat: CatchTheBeat,
+    /// The mania configurations, each for a key count.
+    pub mania: Vec<Mania>,
+    /// The problems found while parsing, in line order.
+    pub issues: Vec<Issue>,
+}
+
+impl SkinIni {
+    /// Parses the contents of a `skin.ini` file.
+    pub fn parse(content: &str) -> SkinIni {
+        let mut skin = SkinIni::default();
+        let mut section = None;
+        let mut mania_lines = vec![];
+
+        for (idx, line) in content.lines().enumerate() {
+            let number = idx + 1;
+            let line = line.trim_start_matches('\u{feff}').trim();
+
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = &line[1..line.len() - 1];
+
+                match name {
+                    "General" | "Colours" | "Fonts" | "CatchTheBeat" => {},
+                    "Mania" => {
+                        skin.mania.push(Mania::default());
+                        mania_lines.push(number);
+                    },
+                    _ => skin.issue(number, IssueKind::UnknownSection(name.to_owned())),
+                }
+
+                section = Some(name);
+
+                continue;
+            }
+
+            let (key, value) = match (section, line.find(':')) {
+                (Some(_), Some(idx)) => (line[..idx].trim(), line[idx + 1..].trim()),
+                _ => {
+                    skin.issue(number, IssueKind::Malformed);
+
+                    continue;
+                },
+            };
+
+            let setting = match section {
+                Some("General") => skin.general.set(key, value),
+                Some("Colours") => skin.colours.set(key, value),
+                Some("Fonts") => skin.fonts.set(key, value),
+                Some("CatchTheBeat") => skin.catch_the_beat.set(key, value),
+                Some("Mania") => match skin.mania.last_mut() {
+                    Some(mania) => mania.set(key, value),
+                    None => continue,
+                },
+                // Lines of unknown sections are covered by the section's
+                // issue.
+                _ => continue,
+            };
+
+            match setting {
+                Setting::Valid => {},
+                Setting::Invalid => skin.issue(number, IssueKind::InvalidValue {
+                    key: key.to_owned(),
+                    value: value.to_owned(),
+                }),
+                Setting::Unknown => skin.issue(number, IssueKind::UnknownKey {
+                    section: section.unwrap_or_default().to_owned(),
+                    key: key.to_owned(),
+                }),
+            }
+        }
+
+        for (mania, &number) in skin.mania.iter().zip(&mania_lines) {
+            if mania.keys == 0 {
+                skin.issues.push(Issue {
+                    line: number,
+                    kind: IssueKind::MissingKey {
+                        section: "Mania".to_owned(),
+                        key: "Keys".to_owned(),
+                    },
+                });
+            }
+        }
+
+        skin.issues.sort_by_key(|issue| issue.line);
+        skin.mania.retain(|mania| mania.keys != 0);
+
+        skin
+    }
+
+    /// Retrieves the mania configuration for a key count.
+    pub fn mania(&self, keys: u8) -> Option<&Mania> {
+        self.mania.iter().find(|mania| mania.keys == keys)
+    }
+
+    /// Lists the images used by a mode, and the file in the skin that
+    /// provides each of them.
+    ///
+    /// Files are given by their paths within the skin's folder, and are
+    /// matched case-insensitively. An image is provided by either its
+    /// standard or `@2x` version, or by the first frame of an animation.
+    ///
+    /// Number images use the prefixes of [`Fonts`], and the images of each
+    /// [`Mania`] configuration are included for [`PlayMode::Mania`].
+    ///
+    /// [`Fonts`]: struct.Fonts.html
+    /// [`Mania`]: struct.Mania.html
+    /// [`PlayMode::Mania`]: ../enum.PlayMode.html#variant.Mania
+    pub fn elements<S: AsRef<str>>(&self, mode: PlayMode, files: &[S]) -> Vec<Element> {
+        let mut names = COMMON_ELEMENTS.iter().map(|&name| name.to_owned()).collect::<Vec<_>>();
+
+        for prefix in &[&self.fonts.score_prefix, &self.fonts.combo_prefix] {
+            for suffix in &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9", "comma", "dot", "percent", "x"] {
+                names.push(format!("{}-{}", prefix, suffix));
+            }
+        }
+
+        let specific = match mode {
+            PlayMode::CatchTheBeat => CATCH_ELEMENTS,
+            PlayMode::Mania => MANIA_ELEMENTS,
+            PlayMode::Standard => STANDARD_ELEMENTS,
+            PlayMode::Taiko => TAIKO_ELEMENTS,
+        };
+        names.extend(specific.iter().map(|&name| name.to_owned()));
+
+        match mode {
+            PlayMode::Standard => {
+                for digit in 0..10 {
+                    names.push(format!("{}-{}", self.fonts.hit_circle_prefix, digit));
+                }
+            },
+            PlayMode::Mania => {
+                for mania in &self.mania {
+                    names.extend(mania.images.values().map(|path| {
+                        path.trim_end_matches(".png").to_owned()
+                    }));
+                }
+            },
+            _ => {},
+        }
+
+        let files = files.iter().map(|file| {
+            (normalize(file.as_ref()), file.as_ref())
+        }).collect::<Vec<_>>();
+        let mut elements: Vec<Element> = vec![];
+
+        for name in names {
+            if elements.iter().any(|element| element.name.eq_ignore_ascii_case(&name)) {
+                continue;
+            }
+
+            let base = normalize(&name);
+            let candidates = [
+                format!("{}@2x.png", base),
+                format!("{}.png", base),
+                format!("{}-0@2x.png", base),
+                format!("{}-0.png", base),
+            ];
+
+            let file = candidates.iter()
+                .filter_map(|candidate| files.iter().find(|&(f, _)| f == candidate))
+                .map(|&(_, original)| original.to_owned())
+                .next();
+
+            elements.push(Element { name, file });
+        }
+
+        elements
+    }
+
+    fn issue(&mut self, line: usize, kind: IssueKind) {
+        self.issues.push(Issue { line, kind });
+    }
+}
+
+/// The `[General]` section, describing the skin and its behaviour.
+#[derive(Clone, Debug)]
+pub struct General {
+    pub name: String,
+    pub author: String,
+    /// The skin version, such as `"2.5"` or `"latest"`, which determines how
+    /// the client draws some elements.
+    pub version: String,
+    /// The frame rate of animations, or `None` to show each animation over
+    /// one second.
+    pub animation_framerate: Option<i64>,
+    pub allow_slider_ball_tint: bool,
+    pub combo_burst_random: bool,
+    pub cursor_centre: bool,
+    pub cursor_expand: bool,
+    pub cursor_rotate: bool,
+    pub cursor_trail_rotate: bool,
+    /// The combo counts that play the combo burst sound.
+    pub custom_combo_burst_sounds: Vec<i64>,
+    pub hit_circle_overlay_above_number: bool,
+    pub layered_hit_sounds: bool,
+    pub slider_ball_flip: bool,
+    pub spinner_fade_playfield: bool,
+    pub spinner_frequency_modulate: bool,
+    pub spinner_no_blink: bool,
+}
+
+impl General {
+    fn set(&mut self, key: &str, value: &str) -> Setting {
+        match key {
+            "Name" => assign(&mut self.name, Some(value.to_owned())),
+            "Author" => assign(&mut self.author, Some(value.to_owned())),
+            "Version" => {
+                let valid = value.eq_ignore_ascii_case("latest") || value.parse::<f64>().is_ok();
+
+                assign(&mut self.version, Some(value.to_owned()).filter(|_| valid))
+            },
+            "AnimationFramerate" => {
+                let fps = value.parse::<i64>().ok().map(|fps| Some(fps).filter(|&fps| fps > 0));
+
+                assign(&mut self.animation_framerate, fps)
+            },
+            "AllowSliderBallTint" => assign(&mut self.allow_slider_ball_tint, parse_bool(value)),
+            "ComboBurstRandom" => assign(&mut self.combo_burst_random, parse_bool(value)),
+            "CursorCentre" => assign(&mut self.cursor_centre, parse_bool(value)),
+            "CursorExpand" => assign(&mut self.cursor_expand, parse_bool(value)),
+            "CursorRotate" => assign(&mut self.cursor_rotate, parse_bool(value)),
+            "CursorTrailRotate" => assign(&mut self.cursor_trail_rotate, parse_bool(value)),
+            "CustomComboBurstSounds" => {
+                assign(&mut self.custom_combo_burst_sounds, parse_list(value))
+            },
+            // The misspelling is accepted by the client.
+            "HitCircleOverlayAboveNumber" | "HitCircleOverlayAboveNumer" => {
+                assign(&mut self.hit_circle_overlay_above_number, parse_bool(value))
+            },
+            "LayeredHitSounds" => assign(&mut self.layered_hit_sounds, parse_bool(value)),
+            "SliderBallFlip" => assign(&mut self.slider_ball_flip, parse_bool(value)),
+            "SpinnerFadePlayfield" => assign(&mut self.spinner_fade_playfield, parse_bool(value)),
+            "SpinnerFrequencyModulate" => {
+                assign(&mut self.spinner_frequency_modulate, parse_bool(value))
+            },
+            "SpinnerNoBlink" => assign(&mut self.spinner_no_blink, parse_bool(value)),
+            _ => Setting::Unknown,
+        }
+    }
+}
+
+impl Default for General {
+    fn default() -> General {
+        General {
+            name: String::new(),
+            author: String::new(),
+            version: "1.0".to_owned(),
+            animation_framerate: None,
+            allow_slider_ball_tint: false,
+            combo_burst_random: false,
+            cursor_centre: true,
+            cursor_expand: true,
+            cursor_rotate: true,
+            cursor_trail_rotate: true,
+            custom_combo_burst_sounds: vec![],
+            hit_circle_overlay_above_number: true,
+            layered_hit_sounds: true,
+            slider_ball_flip: true,
+            spinner_fade_playfield: false,
+            spinner_frequency_modulate: true,
+            spinner_no_blink: false,
+        }
+    }
+}
+
+/// The `[Colours]` section.
+#[derive(Clone, Debug)]
+pub struct Colours {
+    /// The combo colours, by their number from 1 to 8.
+    pub combos: BTreeMap<u8, Colour>,
+    pub input_overlay_text: Colour,
+    pub menu_glow: Colour,
+    pub slider_ball: Colour,
+    pub slider_border: Colour,
+    /// The colour of slider bodies, or `None` to use the combo colour.
+    pub slider_track_override: Option<Colour>,
+    pub song_select_active_text: Colour,
+    pub song_select_inactive_text: Colour,
+    pub spinner_background: Colour,
+    pub star_break_additive: Colour,
+}
+
+impl Colours {
+    fn set(&mut self, key: &str, value: &str) -> Setting {
+        let colour = value.parse().ok();
+
+        match key {
+            "InputOverlayText" => assign(&mut self.input_overlay_text, colour),
+            "MenuGlow" => assign(&mut self.menu_glow, colour),
+            "SliderBall" => assign(&mut self.slider_ball, colour),
+            "SliderBorder" => assign(&mut self.slider_border, colour),
+            "SliderTrackOverride" => assign(&mut self.slider_track_override, colour.map(Some)),
+            "SongSelectActiveText" => assign(&mut self.song_select_active_text, colour),
+            "SongSelectInactiveText" => assign(&mut self.song_select_inactive_text, colour),
+            "SpinnerBackground" => assign(&mut self.spinner_background, colour),
+            "StarBreakAdditive" => assign(&mut self.star_break_additive, colour),
+            _ => match column(key, "Combo", &[""]) {
+                Some(number @ 1..=8) => match colour {
+                    Some(colour) => {
+                        self.combos.insert(number as u8, colour);
+
+                        Setting::Valid
+                    },
+                    None => Setting::Invalid,
+                },
+                _ => Setting::Unknown,
+            },
+        }
+    }
+}
+
+impl Default for Colours {
+    fn default() -> Colours {
+        Colours {
+            combos: BTreeMap::new(),
+            input_overlay_text: Colour::rgb(0, 0, 0),
+            menu_glow: Colour::rgb(0, 78, 155),
+            slider_ball: Colour::rgb(2, 170, 255),
+            slider_border: Colour::rgb(255, 255, 255),
+            slider_track_override: None,
+            song_select_active_text: Colour::rgb(0, 0, 0),
+            song_select_inactive_text: Colour::rgb(255, 255, 255),
+            spinner_background: Colour::rgb(100, 100, 100),
+            star_break_additive: Colour::rgb(255, 182, 193),
+        }
+    }
+}
+
+/// The `[Fonts]` section, giving the prefixes of number images and how much
+/// adjacent digits overlap in pixels.
+#[derive(Clone, Debug)]
+pub struct Fonts {
+    pub hit_circle_prefix: String,
+    pub hit_circle_overlap: i64,
+    pub score_prefix: String,
+    pub score_overlap: i64,
+    pub combo_prefix: String,
+    pub combo_overlap: i64,
+}
+
+impl Fonts {
+    fn set(&mut self, key: &str, value: &str) -> Setting {
+        match key {
+            "HitCirclePrefix" => assign(&mut self.hit_circle_prefix, Some(value.to_owned())),
+            "HitCircleOverlap" => assign(&mut self.hit_circle_overlap, value.parse().ok()),
+            "ScorePrefix" => assign(&mut self.score_prefix, Some(value.to_owned())),
+            "ScoreOverlap" => assign(&mut self.score_overlap, value.parse().ok()),
+            "ComboPrefix" => assign(&mut self.combo_prefix, Some(value.to_owned())),
+            "ComboOverlap" => assign(&mut self.combo_overlap, value.parse().ok()),
+            _ => Setting::Unknown,
+        }
+    }
+}
+
+impl Default for Fonts {
+    fn default() -> Fonts {
+        Fonts {
+            hit_circle_prefix: "default".to_owned(),
+            hit_circle_overlap: -2,
+            score_prefix: "score".to_owned(),
+            score_overlap: 0,
+            combo_prefix: "score".to_owned(),
+            combo_overlap: 0,
+        }
+    }
+}
+
+/// The `[CatchTheBeat]` section.
+#[derive(Clone, Debug)]
+pub struct CatchTheBeat {
+    pub hyper_dash: Colour,
+    /// The colour of fruits that start a hyper dash, or `None` to use
+    /// [`hyper_dash`].
+    ///
+    /// [`hyper_dash`]: #structfield.hyper_dash
+    pub hyper_dash_fruit: Option<Colour>,
+    /// The colour of the catcher's after-image, or `None` to use
+    /// [`hyper_dash`].
+    ///
+    /// [`hyper_dash`]: #structfield.hyper_dash
+    pub hyper_dash_after_image: Option<Colour>,
+}
+
+impl CatchTheBeat {
+    fn set(&mut self, key: &str, value: &str) -> Setting {
+        let colour = value.parse().ok();
+
+        match key {
+            "HyperDash" => assign(&mut self.hyper_dash, colour),
+            "HyperDashFruit" => assign(&mut self.hyper_dash_fruit, colour.map(Some)),
+            "HyperDashAfterImage" => assign(&mut self.hyper_dash_after_image, colour.map(Some)),
+            _ => Setting::Unknown,
+        }
+    }
+}
+
+impl Default for CatchTheBeat {
+    fn default() -> CatchTheBeat {
+        CatchTheBeat {
+            hyper_dash: Colour::rgb(255, 0, 0),
+            hyper_dash_fruit: None,
+            hyper_dash_after_image: None,
+        }
+    }
+}
+
+/// A `[Mania]` section, configuring the stage for a key count.
+///
+/// Column settings are validated against [`keys`] when it is given before
+/// them, as it is by the client's own skins.
+///
+/// [`keys`]: #structfield.keys
+#[derive(Clone, Debug)]
+pub struct Mania {
+    /// The key count, from 1 to 18.
+    pub keys: u8,
+    /// The distance of the stage from the left of the screen.
+    pub column_start: f64,
+    pub column_right: f64,
+    /// The spacing between each pair of adjacent columns.
+    pub column_spacing: Vec<f64>,
+    pub column_width: Vec<f64>,
+    /// The widths of the lines on either side of each column.
+    pub column_line_width: Vec<f64>,
+    pub barline_height: f64,
+    pub hit_position: i64,
+    pub light_position: i64,
+    pub score_position: Option<i64>,
+    pub combo_position: Option<i64>,
+    pub judgement_line: bool,
+    pub special_style: i64,
+    pub upside_down: bool,
+    /// The colours, by their key, such as `Colour1` or `ColourHold`.
+    pub colours: BTreeMap<String, Colour>,
+    /// The paths of the images that replace the default elements, by their
+    /// key, such as `NoteImage0` or `StageLeft`.
+    pub images: BTreeMap<String, String>,
+    /// Other recognised settings, as given.
+    pub other: BTreeMap<String, String>,
+}
+
+impl Mania {
+    fn set(&mut self, key: &str, value: &str) -> Setting {
+        match key {
+            "Keys" => assign(&mut self.keys, value.parse().ok().filter(|keys| (1..=18).contains(keys))),
+            "ColumnStart" => assign(&mut self.column_start, value.parse().ok()),
+            "ColumnRight" => assign(&mut self.column_right, value.parse().ok()),
+            "ColumnSpacing" => assign(&mut self.column_spacing, parse_list(value)),
+            "ColumnWidth" => assign(&mut self.column_width, parse_list(value)),
+            "ColumnLineWidth" => assign(&mut self.column_line_width, parse_list(value)),
+            "BarlineHeight" => assign(&mut self.barline_height, value.parse().ok()),
+            "HitPosition" => assign(&mut self.hit_position, value.parse().ok()),
+            "LightPosition" => assign(&mut self.light_position, value.parse().ok()),
+            "ScorePosition" => assign(&mut self.score_position, value.parse().ok().map(Some)),
+            "ComboPosition" => assign(&mut self.combo_position, value.parse().ok().map(Some)),
+            "JudgementLine" => assign(&mut self.judgement_line, parse_bool(value)),
+            "SpecialStyle" => {
+                let style = value.parse().ok().filter(|style| (0..=2).contains(style));
+
+                assign(&mut self.special_style, style)
+            },
+            "UpsideDown" => assign(&mut self.upside_down, parse_bool(value)),
+            _ => self.set_indexed(key, value),
+        }
+    }
+
+    fn set_indexed(&mut self, key: &str, value: &str) -> Setting {
+        let colour_column = column(key, "Colour", &[""])
+            .or_else(|| column(key, "ColourLight", &[""]));
+
+        if colour_column.map_or(MANIA_COLOURS.contains(&key), |col| self.has_column(col, 1)) {
+            return match value.parse() {
+                Ok(colour) => {
+                    self.colours.insert(key.to_owned(), colour);
+
+                    Setting::Valid
+                },
+                Err(_) => Setting::Invalid,
+            };
+        }
+
+        let image_column = column(key, "KeyImage", &["", "D"])
+            .or_else(|| column(key, "NoteImage", &["", "H", "L", "T"]));
+
+        if image_column.map_or(MANIA_IMAGES.contains(&key), |col| self.has_column(col, 0)) {
+            self.images.insert(key.to_owned(), value.to_owned());
+
+            return Setting::Valid;
+        }
+
+        let other_column = column(key, "KeyFlipWhenUpsideDown", &["", "D"])
+            .or_else(|| column(key, "NoteFlipWhenUpsideDown", &["", "H", "L", "T"]))
+            .or_else(|| column(key, "NoteBodyStyle", &[""]));
+
+        if other_column.map_or(MANIA_OTHER.contains(&key), |col| self.has_column(col, 0)) {
+            self.other.insert(key.to_owned(), value.to_owned());
+
+            return Setting::Valid;
+        }
+
+        Setting::Unknown
+    }
+
+    /// Whether a column exists, numbering columns from `first`.
+    fn has_column(&self, column: usize, first: usize) -> bool {
+        column >= first && (self.keys == 0 || column < first + self.keys as usize)
+    }
+}
+
+impl Default for Mania {
+    fn default() -> Mania {
+        Mania {
+            keys: 0,
+            column_start: 136.0,
+            column_right: 19.0,
+            column_spacing: vec![],
+            column_width: vec![],
+            column_line_width: vec![],
+            barline_height: 1.2,
+            hit_position: 402,
+            light_position: 413,
+            score_position: None,
+            combo_position: None,
+            judgement_line: false,
+            special_style: 0,
+            upside_down: false,
+            colours: BTreeMap::new(),
+            images: BTreeMap::new(),
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+/// A colour, given in a `skin.ini` as `r,g,b` or `r,g,b,a`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+impl Colour {
+    /// Creates an opaque colour.
+    pub fn rgb(red: u8, green: u8, blue: u8) -> Colour {
+        Colour {
+            red,
+            green,
+            blue,
+            alpha: 255,
+        }
+    }
+}
+
+impl FromStr for Colour {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Colour, String> {
+        let channels = s.split(',')
+            .map(|channel| channel.trim().parse::<u8>())
+            .collect::<StdResult<Vec<_>, _>>()
+            .map_err(|_| format!("invalid colour {:?}", s))?;
+
+        match channels[..] {
+            [red, green, blue] => Ok(Colour::rgb(red, green, blue)),
+            [red, green, blue, alpha] => Ok(Colour { red, green, blue, alpha }),
+            _ => Err(format!("invalid colour {:?}", s)),
+        }
+    }
+}
+
+/// An image used in a mode, as listed by [`SkinIni::elements`].
+///
+/// [`SkinIni::elements`]: struct.SkinIni.html#method.elements
+#[derive(Clone, Debug)]
+pub struct Element {
+    /// The name of the image, without its extension, `@2x` suffix or frame
+    /// number.
+    pub name: String,
+    /// The file in the skin that provides the image, or `None` if the
+    /// default skin's is used.
+    pub file: Option<String>,
+}
+
+/// A problem found while parsing a `skin.ini` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    /// The line number, starting from 1.
+    pub line: usize,
+    pub kind: IssueKind,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "line {}: ", self.line)?;
+
+        match self.kind {
+            IssueKind::InvalidValue { ref key, ref value } => {
+                write!(f, "invalid value {:?} for {}", value, key)
+            },
+            IssueKind::Malformed => f.write_str("expected a section or a key-value pair"),
+            IssueKind::MissingKey { ref section, ref key } => {
+                write!(f, "[{}] is missing {}", section, key)
+            },
+            IssueKind::UnknownKey { ref section, ref key } => {
+                write!(f, "unknown key {} in [{}]", key, section)
+            },
+            IssueKind::UnknownSection(ref section) => write!(f, "unknown section [{}]", section),
+        }
+    }
+}
+
+/// The kind of an [`Issue`].
+///
+/// [`Issue`]: struct.Issue.html
+#[derive(Clone, Debug, PartialEq)]
+pub enum IssueKind {
+    /// A value that is malformed or out of range, which is ignored.
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    /// A line that is neither a section header nor a key-value pair within a
+    /// section.
+    Malformed,
+    /// A required key that is not given. Mania sections without a key count
+    /// are ignored.
+    MissingKey {
+        section: String,
+        key: String,
+    },
+    /// A key that is not used by the client, or a column setting for a
+    /// column that does not exist.
+    UnknownKey {
+        section: String,
+        key: String,
+    },
+    /// A section that is not used by the client, whose lines are ignored.
+    UnknownSection(String),
+}
+
+/// Whether a setting was applied.
+enum Setting {
+    Valid,
+    Invalid,
+    Unknown,
+}
+
+fn assign<T>(field: &mut T, value: Option<T>) -> Setting {
+    match value {
+        Some(value) => {
+            *field = value;
+
+            Setting::Valid
+        },
+        None => Setting::Invalid,
+    }
+}
+
+/// Parses the column number of keys such as `NoteImage3H`, made up of a
+/// prefix, a number and one of the given suffixes.
+fn column(key: &str, prefix: &str, suffixes: &[&str]) -> Option<usize> {
+    if !key.starts_with(prefix) {
+        return None;
+    }
+
+    let rest = &key[prefix.len()..];
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+
+    if digits == 0 || !suffixes.contains(&&rest[digits..]) {
+        return None;
+    }
+
+    rest[..digits].parse().ok()
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => value.to_lowercase().parse().ok(),
+    }
+}
+
+fn parse_list<T: FromStr>(value: &str) -> Option<Vec<T>> {
+    value.split(',').map(|item| item.trim().parse().ok()).collect()
+}
@@ -0,0 +1,552 @@
+//! Slider path geometry and timing.
+//!
+//! A [`SliderPath`] approximates a slider's curve from its control points as
+//! a series of line segments, as the game does, and clamps it to the
+//! slider's pixel length. A [`Slider`] adds the timing points and the
+//! difficulty's `SliderMultiplier` and `SliderTickRate` to give the slider's
+//! duration and the times of its ticks, repeats and tail.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use osu::hitobject::{CurveType, Position};
+//! use osu::slider::SliderPath;
+//!
+//! let points = [Position::new(100.0, 100.0), Position::new(300.0, 100.0)];
+//! let path = SliderPath::new(CurveType::Linear, &points, Some(140.0));
+//!
+//! assert_eq!(path.length(), 140.0);
+//! assert_eq!(path.position_at(0.5), Position::new(170.0, 100.0));
+//! ```
+//!
+//! [`Slider`]: struct.Slider.html
+//! [`SliderPath`]: struct.SliderPath.html
+
+use std::f64::consts::PI;
+use ::hitobject::{CurveType, HitObject, HitObjectKind, Position};
+use ::timing::Timing;
+
+/// The distance in osu!pixels that a slider travels per beat at a
+/// `SliderMultiplier` and slider velocity of `1`.
+const BASE_SCORING_DISTANCE: f64 = 100.0;
+const BEZIER_TOLERANCE: f64 = 0.25;
+const CATMULL_DETAIL: usize = 50;
+const CIRCULAR_ARC_TOLERANCE: f64 = 0.1;
+/// How long before the end of a slider its last tick is judged, in
+/// milliseconds.
+const LEGACY_LAST_TICK_OFFSET: f64 = 36.0;
+
+/// The path of a slider, approximated by line segments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SliderPath {
+    points: Vec<Position>,
+    /// The distance along the path to each point.
+    lengths: Vec<f64>,
+}
+
+impl SliderPath {
+    /// Calculates the path through a slider's control points, starting with
+    /// its own position.
+    ///
+    /// The path is shortened, or its last segment lengthened, to the pixel
+    /// length if one is given. A perfect circle is only used for exactly
+    /// three points that are not in a line, with a Bézier curve used
+    /// otherwise.
+    pub fn new(curve_type: CurveType, control_points: &[Position], pixel_length: Option<f64>) -> SliderPath {
+        let mut points: Vec<Position> = vec![];
+
+        for segment in segments(curve_type, control_points) {
+            for point in approximate(curve_type, segment, control_points.len()) {
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+            }
+        }
+
+        if points.is_empty() {
+            points.extend(control_points.first());
+        }
+
+        let mut path = SliderPath {
+            points,
+            lengths: vec![],
+        };
+        path.measure(pixel_length);
+
+        path
+    }
+
+    /// The points of the approximated path.
+    pub fn points(&self) -> &[Position] {
+        &self.points
+    }
+
+    /// The length of the path in osu!pixels.
+    pub fn length(&self) -> f64 {
+        self.lengths.last().cloned().unwrap_or(0.0)
+    }
+
+    /// The position at a progress along the path, from `0` at its start to
+    /// `1` at its end.
+    #[allow(clippy::manual_clamp)]
+    pub fn position_at(&self, progress: f64) -> Position {
+        let distance = progress.max(0.0).min(1.0) * self.length();
+        let idx = self.lengths.iter().position(|&length| length >= distance).unwrap_or(0);
+
+        if idx == 0 {
+            return self.points.first().cloned().unwrap_or_default();
+        }
+
+        let (start, end) = (self.points[idx - 1], self.points[idx]);
+        let segment = self.lengths[idx] - self.lengths[idx - 1];
+
+        if segment <= 0.0 {
+            return end;
+        }
+
+        start + (end - start) * ((distance - self.lengths[idx - 1]) / segment)
+    }
+
+    /// Calculates the distance to each point, clamping the path to the
+    /// expected length as the game does.
+    fn measure(&mut self, expected: Option<f64>) {
+        let mut length = 0.0;
+        self.lengths = vec![0.0];
+
+        for pair in self.points.windows(2) {
+            length += pair[0].distance(pair[1]);
+            self.lengths.push(length);
+        }
+
+        let expected = match expected {
+            Some(expected) if expected != length => expected,
+            _ => return,
+        };
+
+        let count = self.points.len();
+
+        if count == 0 {
+            return;
+        }
+
+        // A path that ends on a repeated point is not lengthened.
+        if count >= 2 && self.points[count - 1] == self.points[count - 2] && expected > length {
+            return;
+        }
+
+        self.lengths.pop();
+        let mut end = count - 1;
+
+        if length > expected {
+            // There is one length fewer than points here.
+            while end > 0 && self.lengths[end - 1] >= expected {
+                self.lengths.pop();
+                self.points.remove(end);
+                end -= 1;
+            }
+        }
+
+        if end == 0 {
+            self.points.truncate(1);
+            self.lengths = vec![0.0];
+
+            return;
+        }
+
+        let previous = self.lengths[self.lengths.len() - 1];
+        let direction = (self.points[end] - self.points[end - 1]).normalize();
+
+        self.points[end] = self.points[end - 1] + direction * (expected - previous);
+        self.points.truncate(end + 1);
+        self.lengths.push(expected);
+    }
+}
+
+/// A slider with its path and timing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slider {
+    pub start_time: f64,
+    pub path: SliderPath,
+    /// The number of times that the path is travelled.
+    pub slides: u32,
+    /// The velocity in osu!pixels per millisecond.
+    pub velocity: f64,
+    /// The distance between ticks in osu!pixels.
+    pub tick_distance: f64,
+}
+
+impl Slider {
+    /// Calculates a slider from its hit object, or `None` if the object is
+    /// not a slider.
+    ///
+    /// The velocity comes from the timing points at the slider's start and
+    /// the difficulty's `SliderMultiplier`, and the tick distance from its
+    /// `SliderTickRate`.
+    pub fn new(object: &HitObject, timing: &Timing, slider_multiplier: f64, slider_tick_rate: f64) -> Option<Slider> {
+        let (curve_type, control_points, slides, pixel_length) = match object.kind {
+            HitObjectKind::Slider { curve_type, ref control_points, slides, pixel_length, .. } => {
+                (curve_type, control_points, slides, pixel_length)
+            },
+            _ => return None,
+        };
+
+        let path = SliderPath::new(curve_type, control_points, pixel_length);
+        let beat_length = timing.beat_length_at(object.time).unwrap_or(1000.0);
+        let scoring_distance = BASE_SCORING_DISTANCE * slider_multiplier * timing.slider_velocity_at(object.time);
+        let tick_distance = if slider_tick_rate > 0.0 {
+            scoring_distance / slider_tick_rate
+        } else {
+            0.0
+        };
+
+        Some(Slider {
+            start_time: object.time,
+            slides: slides.max(1),
+            velocity: scoring_distance / beat_length,
+            tick_distance: tick_distance.min(path.length()),
+            path,
+        })
+    }
+
+    /// The time taken to travel the path once, in milliseconds.
+    pub fn span_duration(&self) -> f64 {
+        if self.velocity > 0.0 {
+            self.path.length() / self.velocity
+        } else {
+            0.0
+        }
+    }
+
+    /// The time taken to travel every span of the path, in milliseconds.
+    pub fn duration(&self) -> f64 {
+        self.span_duration() * f64::from(self.slides)
+    }
+
+    pub fn end_time(&self) -> f64 {
+        self.start_time + self.duration()
+    }
+
+    /// The position of the slider ball at a time, going back along the path
+    /// on each repeat.
+    #[allow(clippy::manual_clamp)]
+    pub fn position_at_time(&self, time: f64) -> Position {
+        let span_duration = self.span_duration();
+
+        if span_duration <= 0.0 {
+            return self.path.position_at(0.0);
+        }
+
+        let progress = ((time - self.start_time) / span_duration).max(0.0).min(f64::from(self.slides));
+        let span = (progress.floor() as u32).min(self.slides - 1);
+        let span_progress = progress - f64::from(span);
+
+        if span % 2 == 1 {
+            self.path.position_at(1.0 - span_progress)
+        } else {
+            self.path.position_at(span_progress)
+        }
+    }
+
+    /// The position where the slider ends, which is its start for an even
+    /// number of slides.
+    pub fn end_position(&self) -> Position {
+        if self.slides % 2 == 1 {
+            self.path.position_at(1.0)
+        } else {
+            self.path.position_at(0.0)
+        }
+    }
+
+    /// The time that the last tick of the slider is judged, which is
+    /// slightly before its end.
+    pub fn legacy_last_tick_time(&self) -> f64 {
+        let span_duration = self.span_duration();
+        let last_span_start = self.start_time + f64::from(self.slides - 1) * span_duration;
+
+        (self.start_time + self.duration() / 2.0).max(last_span_start + span_duration - LEGACY_LAST_TICK_OFFSET)
+    }
+
+    /// The head, ticks, repeats and tail of the slider, ordered by time.
+    ///
+    /// Each of these adds one to the combo.
+    pub fn events(&self) -> Vec<SliderEvent> {
+        let span_duration = self.span_duration();
+        let length = self.path.length();
+        // Ticks too close to the end of a span are skipped.
+        let min_distance_from_end = self.velocity * 10.0;
+        let mut events = vec![SliderEvent {
+            kind: SliderEventKind::Head,
+            time: self.start_time,
+            position: self.path.position_at(0.0),
+            progress: 0.0,
+            span: 0,
+        }];
+
+        for span in 0..self.slides {
+            let span_start = self.start_time + f64::from(span) * span_duration;
+            let reversed = span % 2 == 1;
+            let mut ticks = vec![];
+
+            if self.tick_distance > 0.0 && length > 0.0 {
+                let mut distance = self.tick_distance;
+
+                while distance <= length && distance < length - min_distance_from_end {
+                    let progress = distance / length;
+                    let time_progress = if reversed { 1.0 - progress } else { progress };
+
+                    ticks.push(SliderEvent {
+                        kind: SliderEventKind::Tick,
+                        time: span_start + time_progress * span_duration,
+                        position: self.path.position_at(progress),
+                        progress,
+                        span,
+                    });
+
+                    distance += self.tick_distance;
+                }
+            }
+
+            if reversed {
+                ticks.reverse();
+            }
+
+            events.extend(ticks);
+
+            if span + 1 < self.slides {
+                let progress = if reversed { 0.0 } else { 1.0 };
+
+                events.push(SliderEvent {
+                    kind: SliderEventKind::Repeat,
+                    time: span_start + span_duration,
+                    position: self.path.position_at(progress),
+                    progress,
+                    span,
+                });
+            }
+        }
+
+        events.push(SliderEvent {
+            kind: SliderEventKind::Tail,
+            time: self.end_time(),
+            position: self.end_position(),
+            progress: f64::from(self.slides % 2),
+            span: self.slides - 1,
+        });
+
+        events
+    }
+
+    /// The combo that the slider gives when fully hit.
+    pub fn max_combo(&self) -> u32 {
+        self.events().len() as u32
+    }
+}
+
+/// A judged point of a slider.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SliderEvent {
+    pub kind: SliderEventKind,
+    /// The time in milliseconds.
+    pub time: f64,
+    pub position: Position,
+    /// How far along the path the event is, from `0` at the head to `1` at
+    /// the end of the path.
+    pub progress: f64,
+    /// The span of the path that the event is on, from `0`.
+    pub span: u32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SliderEventKind {
+    Head,
+    Tick,
+    /// The end of a span that the slider turns back at.
+    Repeat,
+    Tail,
+}
+
+/// Splits control points into segments at red anchors, where a point is
+/// repeated.
+fn segments(curve_type: CurveType, points: &[Position]) -> Vec<&[Position]> {
+    if curve_type == CurveType::PerfectCircle && points.len() == 3 {
+        return vec![points];
+    }
+
+    let mut segments = vec![];
+    let mut start = 0;
+
+    for idx in 1..points.len() {
+        if points[idx] == points[idx - 1] {
+            segments.push(&points[start..idx]);
+            start = idx;
+        }
+    }
+
+    segments.push(&points[start..]);
+    segments.into_iter().filter(|segment| !segment.is_empty()).collect()
+}
+
+fn approximate(curve_type: CurveType, points: &[Position], total: usize) -> Vec<Position> {
+    match curve_type {
+        CurveType::Linear => points.to_vec(),
+        CurveType::Catmull => catmull(points),
+        CurveType::PerfectCircle if total == 3 => circular_arc(points).unwrap_or_else(|| bezier(points)),
+        CurveType::Bezier | CurveType::PerfectCircle => bezier(points),
+    }
+}
+
+/// Approximates a Bézier curve by subdividing it until each part is flat.
+fn bezier(points: &[Position]) -> Vec<Position> {
+    let count = points.len();
+    let mut output = vec![];
+
+    if count == 0 {
+        return output;
+    }
+
+    let mut to_flatten = vec![points.to_vec()];
+    let mut left = vec![Position::default(); count * 2 - 1];
+    let mut right = vec![Position::default(); count];
+
+    while let Some(mut parent) = to_flatten.pop() {
+        if bezier_is_flat(&parent) {
+            bezier_approximate(&parent, &mut output, &mut left, &mut right);
+
+            continue;
+        }
+
+        let mut right_child = vec![Position::default(); count];
+        bezier_subdivide(&parent, &mut left, &mut right_child);
+        parent.copy_from_slice(&left[..count]);
+
+        to_flatten.push(right_child);
+        to_flatten.push(parent);
+    }
+
+    output.push(points[count - 1]);
+
+    output
+}
+
+fn bezier_is_flat(points: &[Position]) -> bool {
+    points.windows(3).all(|p| {
+        let d = p[0] - p[1] * 2.0 + p[2];
+
+        d.dot(d) <= BEZIER_TOLERANCE * BEZIER_TOLERANCE * 4.0
+    })
+}
+
+/// Splits a Bézier curve in half with de Casteljau's algorithm.
+fn bezier_subdivide(points: &[Position], left: &mut [Position], right: &mut [Position]) {
+    let count = points.len();
+    let mut midpoints = points.to_vec();
+
+    for i in 0..count {
+        left[i] = midpoints[0];
+        right[count - i - 1] = midpoints[count - i - 1];
+
+        for j in 0..count - i - 1 {
+            midpoints[j] = (midpoints[j] + midpoints[j + 1]) * 0.5;
+        }
+    }
+}
+
+fn bezier_approximate(points: &[Position], output: &mut Vec<Position>, left: &mut [Position], right: &mut [Position]) {
+    let count = points.len();
+    bezier_subdivide(points, left, right);
+
+    left[count..].copy_from_slice(&right[1..]);
+
+    output.push(points[0]);
+
+    for i in 1..count - 1 {
+        let idx = 2 * i;
+
+        output.push((left[idx - 1] + left[idx] * 2.0 + left[idx + 1]) * 0.25);
+    }
+}
+
+/// Approximates a Catmull-Rom spline, which passes through each point.
+fn catmull(points: &[Position]) -> Vec<Position> {
+    let count = points.len();
+    let mut output = vec![];
+
+    for i in 0..count.saturating_sub(1) {
+        let v1 = if i > 0 { points[i - 1] } else { points[i] };
+        let v2 = points[i];
+        let v3 = if i + 1 < count { points[i + 1] } else { v2 * 2.0 - v1 };
+        let v4 = if i + 2 < count { points[i + 2] } else { v3 * 2.0 - v2 };
+
+        for c in 0..CATMULL_DETAIL {
+            output.push(catmull_point(v1, v2, v3, v4, c as f64 / CATMULL_DETAIL as f64));
+        }
+    }
+
+    output.extend(points.last());
+
+    output
+}
+
+fn catmull_point(v1: Position, v2: Position, v3: Position, v4: Position, t: f64) -> Position {
+    let t2 = t * t;
+    let t3 = t * t2;
+    let axis = |p1: f64, p2: f64, p3: f64, p4: f64| {
+        0.5 * (2.0 * p2
+            + (-p1 + p3) * t
+            + (2.0 * p1 - 5.0 * p2 + 4.0 * p3 - p4) * t2
+            + (-p1 + 3.0 * p2 - 3.0 * p3 + p4) * t3)
+    };
+
+    Position::new(axis(v1.x, v2.x, v3.x, v4.x), axis(v1.y, v2.y, v3.y, v4.y))
+}
+
+/// Approximates the arc of a circle through three points, or `None` if they
+/// are in a line.
+fn circular_arc(points: &[Position]) -> Option<Vec<Position>> {
+    let (a, b, c) = (points[0], points[1], points[2]);
+
+    if ((b.y - a.y) * (c.x - a.x) - (b.x - a.x) * (c.y - a.y)).abs() < 1e-3 {
+        return None;
+    }
+
+    let d = 2.0 * (a.x * (b - c).y + b.x * (c - a).y + c.x * (a - b).y);
+    let (a_sq, b_sq, c_sq) = (a.dot(a), b.dot(b), c.dot(c));
+    let centre = Position::new(
+        (a_sq * (b - c).y + b_sq * (c - a).y + c_sq * (a - b).y) / d,
+        (a_sq * (c - b).x + b_sq * (a - c).x + c_sq * (b - a).x) / d,
+    );
+
+    let radius = a.distance(centre);
+    let theta_start = (a - centre).y.atan2((a - centre).x);
+    let mut theta_end = (c - centre).y.atan2((c - centre).x);
+
+    while theta_end < theta_start {
+        theta_end += 2.0 * PI;
+    }
+
+    let mut direction = 1.0;
+    let mut theta_range = theta_end - theta_start;
+
+    // The arc goes the other way around if the middle point is on the
+    // other side of the line from the first point to the last.
+    let ortho = Position::new((c - a).y, -(c - a).x);
+
+    if ortho.dot(b - a) < 0.0 {
+        direction = -1.0;
+        theta_range = 2.0 * PI - theta_range;
+    }
+
+    let count = if 2.0 * radius <= CIRCULAR_ARC_TOLERANCE {
+        2
+    } else {
+        let step = 2.0 * (1.0 - CIRCULAR_ARC_TOLERANCE / radius).acos();
+
+        ((theta_range / step).ceil() as usize).max(2)
+    };
+
+    Some((0..count).map(|i| {
+        let theta = theta_start + direction * (i as f64 / (count - 1) as f64) * theta_range;
+
+        centre + Position::new(theta.cos(), theta.sin()) * radius
+    }).collect())
+}
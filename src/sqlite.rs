@@ -0,0 +1,697 @@
+//! Persistence of API data in a local SQLite database.
+//!
+//! A [`Store`] keeps beatmaps, users, top plays, recent plays, matches and
+//! their games in a SQLite database, bundled with the crate. Each type is
+//! upserted by its natural key, so storing the same data again updates it
+//! in place:
+//!
+//! | Table          | Key                                                  |
+//! |----------------|------------------------------------------------------|
+//! | `beatmaps`     | `beatmap_id`                                         |
+//! | `users`        | `user_id`, `mode`                                    |
+//! | `user_events`  | `user_id`, `date`, `display_html`                    |
+//! | `performances` | `user_id`, `beatmap_id`, `enabled_mods`, `score`, `date` |
+//! | `recent_plays` | `user_id`, `beatmap_id`, `enabled_mods`, `score`, `date` |
+//! | `matches`      | `match_id`                                           |
+//! | `games`        | `game_id`                                            |
+//! | `game_scores`  | `game_id`, `user_id`                                 |
+//!
+//! Enums and mods are stored as the integers used by the API. The schema is
+//! migrated to the latest version when a store is opened, and its version
+//! is kept in the database's `user_version`.
+//!
+//! Requires the `sqlite-support` feature.
+//!
+//! # Examples
+//!
+//! Store the beatmaps from a request, and read one back:
+//!
+//! ```rust
+//! use osu::sqlite::Store;
+//!
+//! # fn beatmaps() -> Vec<osu::Beatmap> { vec![] }
+//! let mut store = Store::open_in_memory().unwrap();
+//!
+//! store.upsert_beatmaps(&beatmaps()).unwrap();
+//!
+//! if let Some(beatmap) = store.get_beatmap(129891).unwrap() {
+//!     println!("{} - {} [{}]", beatmap.artist, beatmap.title, beatmap.version);
+//! }
+//! ```
+//!
+//! [`Store`]: struct.Store.html
+
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, Row, NO_PARAMS};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Value};
+use std::path::Path;
+use ::{
+    Beatmap,
+    Error,
+    Game,
+    GameScore,
+    Match,
+    Mods,
+    Performance,
+    PlayMode,
+    RecentPlay,
+    Result,
+    User,
+    UserEvent,
+};
+
+/// The migrations of the schema, each bringing it to the version after its
+/// index.
+///
+/// Migrations must only ever be appended, as databases record how many of
+/// them have been applied.
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE beatmaps (
+        beatmap_id INTEGER PRIMARY KEY,
+        beatmapset_id INTEGER NOT NULL,
+        approved INTEGER NOT NULL,
+        approved_date TEXT,
+        artist TEXT NOT NULL,
+        bpm REAL NOT NULL,
+        creator TEXT NOT NULL,
+        difficulty_rating REAL NOT NULL,
+        diff_approach REAL NOT NULL,
+        diff_drain REAL NOT NULL,
+        diff_overall REAL NOT NULL,
+        diff_size REAL NOT NULL,
+        favourite_count INTEGER NOT NULL,
+        file_md5 TEXT NOT NULL,
+        genre_id INTEGER NOT NULL,
+        hit_length INTEGER NOT NULL,
+        language_id INTEGER NOT NULL,
+        last_update TEXT NOT NULL,
+        max_combo INTEGER,
+        mode INTEGER NOT NULL,
+        pass_count INTEGER NOT NULL,
+        play_count INTEGER NOT NULL,
+        source TEXT NOT NULL,
+        tags TEXT NOT NULL,
+        title TEXT NOT NULL,
+        total_length INTEGER NOT NULL,
+        version TEXT NOT NULL
+    );
+    CREATE INDEX beatmaps_beatmapset_id ON beatmaps (beatmapset_id);
+
+    CREATE TABLE users (
+        user_id INTEGER NOT NULL,
+        mode INTEGER NOT NULL,
+        accuracy REAL NOT NULL,
+        count_100 INTEGER NOT NULL,
+        count_300 INTEGER NOT NULL,
+        count_50 INTEGER NOT NULL,
+        count_rank_a INTEGER NOT NULL,
+        count_rank_s INTEGER NOT NULL,
+        count_rank_ss INTEGER NOT NULL,
+        country TEXT NOT NULL,
+        level REAL NOT NULL,
+        play_count INTEGER NOT NULL,
+        pp_country_rank INTEGER NOT NULL,
+        pp_rank INTEGER NOT NULL,
+        pp_raw REAL NOT NULL,
+        ranked_score INTEGER NOT NULL,
+        total_score INTEGER NOT NULL,
+        username TEXT NOT NULL,
+        PRIMARY KEY (user_id, mode)
+    );
+
+    CREATE TABLE user_events (
+        user_id INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        display_html TEXT NOT NULL,
+        beatmap_id INTEGER NOT NULL,
+        beatmapset_id INTEGER NOT NULL,
+        epic_factor INTEGER NOT NULL,
+        PRIMARY KEY (user_id, date, display_html)
+    );
+
+    CREATE TABLE performances (
+        user_id INTEGER NOT NULL,
+        beatmap_id INTEGER NOT NULL,
+        enabled_mods INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        mode INTEGER NOT NULL,
+        count_100 INTEGER NOT NULL,
+        count_300 INTEGER NOT NULL,
+        count_50 INTEGER NOT NULL,
+        count_geki INTEGER NOT NULL,
+        count_katu INTEGER NOT NULL,
+        count_miss INTEGER NOT NULL,
+        max_combo INTEGER NOT NULL,
+        perfect INTEGER NOT NULL,
+        pp REAL NOT NULL,
+        rank TEXT NOT NULL,
+        PRIMARY KEY (user_id, beatmap_id, enabled_mods, score, date)
+    );
+
+    CREATE TABLE recent_plays (
+        user_id INTEGER NOT NULL,
+        beatmap_id INTEGER NOT NULL,
+        enabled_mods INTEGER NOT NULL,
+        score INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        mode INTEGER NOT NULL,
+        count_100 INTEGER NOT NULL,
+        count_300 INTEGER NOT NULL,
+        count_50 INTEGER NOT NULL,
+        count_geki INTEGER NOT NULL,
+        count_katu INTEGER NOT NULL,
+        count_miss INTEGER NOT NULL,
+        max_combo INTEGER NOT NULL,
+        perfect INTEGER NOT NULL,
+        rank TEXT NOT NULL,
+        PRIMARY KEY (user_id, beatmap_id, enabled_mods, score, date)
+    );
+
+    CREATE TABLE matches (
+        match_id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL,
+        start_time TEXT NOT NULL,
+        end_time TEXT
+    );
+
+    CREATE TABLE games (
+        game_id INTEGER PRIMARY KEY,
+        match_id INTEGER NOT NULL,
+        beatmap_id INTEGER NOT NULL,
+        start_time TEXT NOT NULL,
+        end_time TEXT NOT NULL,
+        match_type INTEGER NOT NULL,
+        mods INTEGER NOT NULL,
+        play_mode INTEGER NOT NULL,
+        scoring_type INTEGER NOT NULL,
+        team_type INTEGER NOT NULL
+    );
+    CREATE INDEX games_match_id ON games (match_id);
+
+    CREATE TABLE game_scores (
+        game_id INTEGER NOT NULL,
+        user_id INTEGER NOT NULL,
+        count_100 INTEGER NOT NULL,
+        count_300 INTEGER NOT NULL,
+        count_50 INTEGER NOT NULL,
+        count_geki INTEGER NOT NULL,
+        count_katu INTEGER NOT NULL,
+        count_miss INTEGER NOT NULL,
+        date TEXT NOT NULL,
+        enabled_mods INTEGER NOT NULL,
+        max_combo INTEGER NOT NULL,
+        perfect INTEGER NOT NULL,
+        pp REAL NOT NULL,
+        rank TEXT NOT NULL,
+        score INTEGER NOT NULL,
+        username TEXT NOT NULL,
+        PRIMARY KEY (game_id, user_id)
+    );",
+];
+
+const BEATMAP_COLUMNS: &[&str] = &[
+    "beatmap_id", "beatmapset_id", "approved", "approved_date", "artist", "bpm", "creator",
+    "difficulty_rating", "diff_approach", "diff_drain", "diff_overall", "diff_size",
+    "favourite_count", "file_md5", "genre_id", "hit_length", "language_id", "last_update",
+    "max_combo", "mode", "pass_count", "play_count", "source", "tags", "title", "total_length",
+    "version",
+];
+const USER_COLUMNS: &[&str] = &[
+    "user_id", "mode", "accuracy", "count_100", "count_300", "count_50", "count_rank_a",
+    "count_rank_s", "count_rank_ss", "country", "level", "play_count", "pp_country_rank",
+    "pp_rank", "pp_raw", "ranked_score", "total_score", "username",
+];
+const USER_EVENT_COLUMNS: &[&str] = &[
+    "user_id", "date", "display_html", "beatmap_id", "beatmapset_id", "epic_factor",
+];
+const PERFORMANCE_COLUMNS: &[&str] = &[
+    "user_id", "beatmap_id", "enabled_mods", "score", "date", "mode", "count_100", "count_300",
+    "count_50", "count_geki", "count_katu", "count_miss", "max_combo", "perfect", "pp", "rank",
+];
+const RECENT_PLAY_COLUMNS: &[&str] = &[
+    "user_id", "beatmap_id", "enabled_mods", "score", "date", "mode", "count_100", "count_300",
+    "count_50", "count_geki", "count_katu", "count_miss", "max_combo", "perfect", "rank",
+];
+const MATCH_COLUMNS: &[&str] = &["match_id", "name", "start_time", "end_time"];
+const GAME_COLUMNS: &[&str] = &[
+    "game_id", "match_id", "beatmap_id", "start_time", "end_time", "match_type", "mods",
+    "play_mode", "scoring_type", "team_type",
+];
+const GAME_SCORE_COLUMNS: &[&str] = &[
+    "game_id", "user_id", "count_100", "count_300", "count_50", "count_geki", "count_katu",
+    "count_miss", "date", "enabled_mods", "max_combo", "perfect", "pp", "rank", "score",
+    "username",
+];
+
+/// A SQLite database of API data.
+#[derive(Debug)]
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens or creates a database at a path, migrating it to the latest
+    /// schema.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store> {
+        Store::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a database in memory, which is lost when dropped.
+    pub fn open_in_memory() -> Result<Store> {
+        Store::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an open connection, migrating its database to the latest
+    /// schema.
+    pub fn from_connection(conn: Connection) -> Result<Store> {
+        let mut store = Store { conn };
+        store.migrate()?;
+
+        Ok(store)
+    }
+
+    /// The underlying connection, for queries not covered by the store.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Consumes the store, returning its connection.
+    pub fn into_connection(self) -> Connection {
+        self.conn
+    }
+
+    /// The version of the database's schema, which is the number of
+    /// migrations applied.
+    pub fn version(&self) -> Result<usize> {
+        let version: i64 = self.conn.query_row("PRAGMA user_version", NO_PARAMS, |row| row.get(0))?;
+
+        Ok(version as usize)
+    }
+
+    /// Applies any migrations that the database has not had yet.
+    ///
+    /// Returns [`Error::Parse`] if the database is from a newer version of
+    /// the crate.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn migrate(&mut self) -> Result<()> {
+        let version = self.version()?;
+
+        if version > MIGRATIONS.len() {
+            return Err(Error::Parse(format!("unknown database schema version {}", version)));
+        }
+
+        for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", idx + 1))?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Inserts or updates a beatmap.
+    pub fn upsert_beatmap(&mut self, beatmap: &Beatmap) -> Result<()> {
+        self.upsert_beatmaps(::std::slice::from_ref(beatmap))
+    }
+
+    /// Inserts or updates beatmaps, such as from `get_beatmaps`.
+    pub fn upsert_beatmaps(&mut self, beatmaps: &[Beatmap]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(&upsert_sql("beatmaps", BEATMAP_COLUMNS, 1))?;
+
+            for b in beatmaps {
+                stmt.execute(params![
+                    b.beatmap_id, b.beatmapset_id, code(&b.approved)?, b.approved_date, b.artist,
+                    b.bpm, b.creator, b.difficulty_rating, b.diff_approach, b.diff_drain,
+                    b.diff_overall, b.diff_size, b.favourite_count, b.file_md5, code(&b.genre_id)?,
+                    b.hit_length, code(&b.language_id)?, b.last_update, b.max_combo, b.mode,
+                    b.pass_count, b.play_count, b.source, b.tags, b.title, b.total_length,
+                    b.version,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Retrieves a beatmap by its ID.
+    pub fn get_beatmap(&self, beatmap_id: i64) -> Result<Option<Beatmap>> {
+        let sql = select_sql("beatmaps", BEATMAP_COLUMNS, "beatmap_id = ?");
+
+        Ok(query(&self.conn, &sql, &[&beatmap_id], beatmap_from_row)?.pop())
+    }
+
+    /// Retrieves the beatmaps of a set, ordered by ID.
+    pub fn get_beatmapset(&self, beatmapset_id: i64) -> Result<Vec<Beatmap>> {
+        let sql = select_sql("beatmaps", BEATMAP_COLUMNS, "beatmapset_id = ? ORDER BY beatmap_id");
+
+        query(&self.conn, &sql, &[&beatmapset_id], beatmap_from_row)
+    }
+
+    /// Inserts or updates a user's statistics in a mode, such as from
+    /// `get_user`, along with their events.
+    ///
+    /// Events are kept when they are no longer returned by the API.
+    pub fn upsert_user(&mut self, user: &User, mode: PlayMode) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(&upsert_sql("users", USER_COLUMNS, 2), params![
+            user.id, code(&mode)?, user.accuracy, user.count_100, user.count_300, user.count_50,
+            user.count_rank_a, user.count_rank_s, user.count_rank_ss, user.country, user.level,
+            user.play_count, user.pp_country_rank, user.pp_rank, user.pp_raw, user.ranked_score,
+            user.total_score, user.username,
+        ])?;
+
+        {
+            let mut stmt = tx.prepare(&upsert_sql("user_events", USER_EVENT_COLUMNS, 3))?;
+
+            for event in &user.events {
+                stmt.execute(params![
+                    user.id, event.date, event.display_html, event.beatmap_id,
+                    event.beatmapset_id, event.epic_factor,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Retrieves a user's statistics in a mode, with all of their stored
+    /// events, latest first.
+    pub fn get_user(&self, user_id: i64, mode: PlayMode) -> Result<Option<User>> {
+        let sql = select_sql("users", USER_COLUMNS, "user_id = ? AND mode = ?");
+        let mut user = match query(&self.conn, &sql, &[&user_id, &code(&mode)?], user_from_row)?.pop() {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        let sql = select_sql("user_events", USER_EVENT_COLUMNS, "user_id = ? ORDER BY date DESC");
+        user.events = query(&self.conn, &sql, &[&user_id], |row| Ok(UserEvent {
+            beatmap_id: row.get("beatmap_id")?,
+            beatmapset_id: row.get("beatmapset_id")?,
+            date: row.get("date")?,
+            display_html: row.get("display_html")?,
+            epic_factor: row.get("epic_factor")?,
+        }))?;
+
+        Ok(Some(user))
+    }
+
+    /// Inserts or updates top plays in a mode, such as from
+    /// `get_user_best`.
+    pub fn upsert_performances(&mut self, mode: PlayMode, performances: &[Performance]) -> Result<()> {
+        let mode = code(&mode)?;
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(&upsert_sql("performances", PERFORMANCE_COLUMNS, 5))?;
+
+            for p in performances {
+                stmt.execute(params![
+                    p.user_id, p.beatmap_id, p.enabled_mods.bits(), p.score, p.date, mode,
+                    p.count_100, p.count_300, p.count_50, p.count_geki, p.count_katu,
+                    p.count_miss, p.max_combo, p.perfect, p.pp, p.rank,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Retrieves a user's stored top plays in a mode, best first.
+    pub fn get_performances(&self, user_id: i64, mode: PlayMode) -> Result<Vec<Performance>> {
+        let sql = select_sql("performances", PERFORMANCE_COLUMNS, "user_id = ? AND mode = ? ORDER BY pp DESC");
+
+        query(&self.conn, &sql, &[&user_id, &code(&mode)?], |row| Ok(Performance {
+            beatmap_id: row.get("beatmap_id")?,
+            count_100: row.get("count_100")?,
+            count_300: row.get("count_300")?,
+            count_50: row.get("count_50")?,
+            count_geki: row.get("count_geki")?,
+            count_katu: row.get("count_katu")?,
+            count_miss: row.get("count_miss")?,
+            date: row.get("date")?,
+            enabled_mods: Mods::from_bits_truncate(row.get("enabled_mods")?),
+            max_combo: row.get("max_combo")?,
+            perfect: row.get("perfect")?,
+            pp: row.get("pp")?,
+            rank: row.get("rank")?,
+            score: row.get("score")?,
+            user_id: row.get("user_id")?,
+        }))
+    }
+
+    /// Inserts or updates recent plays in a mode, such as from
+    /// `get_user_recent`.
+    pub fn upsert_recent_plays(&mut self, mode: PlayMode, plays: &[RecentPlay]) -> Result<()> {
+        let mode = code(&mode)?;
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut stmt = tx.prepare(&upsert_sql("recent_plays", RECENT_PLAY_COLUMNS, 5))?;
+
+            for p in plays {
+                stmt.execute(params![
+                    p.user_id, p.beatmap_id, p.enabled_mods.bits(), p.score, p.date, mode,
+                    p.count_100, p.count_300, p.count_50, p.count_geki, p.count_katu,
+                    p.count_miss, p.max_combo, p.perfect, p.rank,
+                ])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Retrieves a user's stored recent plays in a mode, latest first.
+    pub fn get_recent_plays(&self, user_id: i64, mode: PlayMode) -> Result<Vec<RecentPlay>> {
+        let sql = select_sql("recent_plays", RECENT_PLAY_COLUMNS, "user_id = ? AND mode = ? ORDER BY date DESC");
+
+        query(&self.conn, &sql, &[&user_id, &code(&mode)?], |row| Ok(RecentPlay {
+            beatmap_id: row.get("beatmap_id")?,
+            count_100: row.get("count_100")?,
+            count_300: row.get("count_300")?,
+            count_50: row.get("count_50")?,
+            count_geki: row.get("count_geki")?,
+            count_katu: row.get("count_katu")?,
+            count_miss: row.get("count_miss")?,
+            date: row.get("date")?,
+            enabled_mods: Mods::from_bits_truncate(row.get("enabled_mods")?),
+            max_combo: row.get("max_combo")?,
+            perfect: row.get("perfect")?,
+            rank: row.get("rank")?,
+            score: row.get("score")?,
+            user_id: row.get("user_id")?,
+        }))
+    }
+
+    /// Inserts or updates a match, such as from `get_match`.
+    pub fn upsert_match(&mut self, m: &Match) -> Result<()> {
+        self.conn.execute(&upsert_sql("matches", MATCH_COLUMNS, 1), params![
+            m.match_id, m.name, m.start_time, m.end_time,
+        ])?;
+
+        Ok(())
+    }
+
+    /// Retrieves a match by its ID.
+    pub fn get_match(&self, match_id: i64) -> Result<Option<Match>> {
+        let sql = select_sql("matches", MATCH_COLUMNS, "match_id = ?");
+
+        Ok(query(&self.conn, &sql, &[&match_id], |row| Ok(Match {
+            end_time: row.get("end_time")?,
+            match_id: row.get("match_id")?,
+            name: row.get("name")?,
+            start_time: row.get("start_time")?,
+        }))?.pop())
+    }
+
+    /// Inserts or updates the games of a match, along with their scores.
+    pub fn upsert_games(&mut self, match_id: i64, games: &[Game]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+
+        {
+            let mut games_stmt = tx.prepare(&upsert_sql("games", GAME_COLUMNS, 1))?;
+            let mut scores_stmt = tx.prepare(&upsert_sql("game_scores", GAME_SCORE_COLUMNS, 2))?;
+
+            for g in games {
+                games_stmt.execute(params![
+                    g.game_id, match_id, g.beatmap_id, g.start_time, g.end_time, g.match_type,
+                    g.mods.bits(), code(&g.play_mode)?, code(&g.scoring_type)?,
+                    code(&g.team_type)?,
+                ])?;
+
+                for s in &g.scores {
+                    scores_stmt.execute(params![
+                        g.game_id, s.user_id, s.count_100, s.count_300, s.count_50, s.count_geki,
+                        s.count_katu, s.count_miss, s.date, s.enabled_mods.bits(), s.max_combo,
+                        s.perfect, s.pp, s.rank, s.score, s.username,
+                    ])?;
+                }
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Retrieves the games of a match in the order they were played, with
+    /// their scores.
+    pub fn get_games(&self, match_id: i64) -> Result<Vec<Game>> {
+        let sql = select_sql("games", GAME_COLUMNS, "match_id = ? ORDER BY start_time, game_id");
+        let mut games = query(&self.conn, &sql, &[&match_id], |row| Ok(Game {
+            beatmap_id: row.get("beatmap_id")?,
+            end_time: row.get("end_time")?,
+            game_id: row.get("game_id")?,
+            match_type: row.get("match_type")?,
+            mods: Mods::from_bits_truncate(row.get("mods")?),
+            play_mode: from_code(row.get("play_mode")?)?,
+            scores: vec![],
+            scoring_type: from_code(row.get("scoring_type")?)?,
+            start_time: row.get("start_time")?,
+            team_type: from_code(row.get("team_type")?)?,
+        }))?;
+
+        let sql = select_sql("game_scores", GAME_SCORE_COLUMNS, "game_id = ? ORDER BY score DESC");
+
+        for game in &mut games {
+            game.scores = query(&self.conn, &sql, &[&game.game_id], |row| Ok(GameScore {
+                count_100: row.get("count_100")?,
+                count_300: row.get("count_300")?,
+                count_50: row.get("count_50")?,
+                count_geki: row.get("count_geki")?,
+                count_katu: row.get("count_katu")?,
+                count_miss: row.get("count_miss")?,
+                date: row.get("date")?,
+                enabled_mods: Mods::from_bits_truncate(row.get("enabled_mods")?),
+                max_combo: row.get("max_combo")?,
+                perfect: row.get("perfect")?,
+                pp: row.get("pp")?,
+                rank: row.get("rank")?,
+                score: row.get("score")?,
+                user_id: row.get("user_id")?,
+                username: row.get("username")?,
+            }))?;
+        }
+
+        Ok(games)
+    }
+}
+
+fn beatmap_from_row(row: &Row) -> Result<Beatmap> {
+    Ok(Beatmap {
+        approved: from_code(row.get("approved")?)?,
+        approved_date: row.get("approved_date")?,
+        artist: row.get("artist")?,
+        beatmap_id: row.get("beatmap_id")?,
+        beatmapset_id: row.get("beatmapset_id")?,
+        bpm: row.get("bpm")?,
+        creator: row.get("creator")?,
+        difficulty_rating: row.get("difficulty_rating")?,
+        diff_approach: row.get("diff_approach")?,
+        diff_drain: row.get("diff_drain")?,
+        diff_overall: row.get("diff_overall")?,
+        diff_size: row.get("diff_size")?,
+        favourite_count: row.get("favourite_count")?,
+        file_md5: row.get("file_md5")?,
+        genre_id: from_code(row.get("genre_id")?)?,
+        hit_length: row.get("hit_length")?,
+        language_id: from_code(row.get("language_id")?)?,
+        last_update: row.get("last_update")?,
+        max_combo: row.get("max_combo")?,
+        mode: row.get("mode")?,
+        pass_count: row.get("pass_count")?,
+        play_count: row.get("play_count")?,
+        source: row.get("source")?,
+        tags: row.get("tags")?,
+        title: row.get("title")?,
+        total_length: row.get("total_length")?,
+        version: row.get("version")?,
+    })
+}
+
+fn user_from_row(row: &Row) -> Result<User> {
+    Ok(User {
+        id: row.get("user_id")?,
+        accuracy: row.get("accuracy")?,
+        count_100: row.get("count_100")?,
+        count_300: row.get("count_300")?,
+        count_50: row.get("count_50")?,
+        count_rank_a: row.get("count_rank_a")?,
+        count_rank_s: row.get("count_rank_s")?,
+        count_rank_ss: row.get("count_rank_ss")?,
+        country: row.get("country")?,
+        events: vec![],
+        level: row.get("level")?,
+        play_count: row.get("play_count")?,
+        pp_country_rank: row.get("pp_country_rank")?,
+        pp_rank: row.get("pp_rank")?,
+        pp_raw: row.get("pp_raw")?,
+        ranked_score: row.get("ranked_score")?,
+        total_score: row.get("total_score")?,
+        username: row.get("username")?,
+    })
+}
+
+/// Runs a query, converting each row.
+fn query<T, F>(conn: &Connection, sql: &str, params: &[&ToSql], f: F) -> Result<Vec<T>>
+    where F: Fn(&Row) -> Result<T> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(params)?;
+    let mut values = vec![];
+
+    while let Some(row) = rows.next()? {
+        values.push(f(row)?);
+    }
+
+    Ok(values)
+}
+
+/// Builds an upsert into a table, keyed on its first columns.
+fn upsert_sql(table: &str, columns: &[&str], key_len: usize) -> String {
+    let placeholders = vec!["?"; columns.len()].join(", ");
+    let updates = columns[key_len..].iter()
+        .map(|column| format!("{0} = excluded.{0}", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {}",
+        table,
+        columns.join(", "),
+        placeholders,
+        columns[..key_len].join(", "),
+        updates,
+    )
+}
+
+fn select_sql(table: &str, columns: &[&str], filter: &str) -> String {
+    format!("SELECT {} FROM {} WHERE {}", columns.join(", "), table, filter)
+}
+
+/// The integer that the API uses for an enum variant.
+fn code<T: Serialize>(value: &T) -> Result<i64> {
+    match serde_json::to_value(value)? {
+        Value::String(ref code) => code.parse().map_err(|_| Error::Parse(format!("invalid code {:?}", code))),
+        other => Err(Error::Parse(format!("invalid code {}", other))),
+    }
+}
+
+/// The enum variant for an integer that the API uses.
+fn from_code<T: DeserializeOwned>(code: i64) -> Result<T> {
+    Ok(serde_json::from_value(Value::String(code.to_string()))?)
+}
@@ -0,0 +1,214 @@
+//! The stacking of osu!standard hit objects.
+//!
+//! Objects placed on top of each other within a short time are drawn as a
+//! stack, each offset up and to the left of the next. The game decides the
+//! height of each object in a stack from the difficulty's `StackLeniency`,
+//! using a newer algorithm for files from format version 6 onwards and an
+//! older one before it.
+//!
+//! Stacked positions are what is actually played, so difficulty calculation
+//! and replay analysis should use them over the positions in the file.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use osu::hitobject::{HitObject, Position};
+//! use osu::stacking;
+//!
+//! let objects = ["128,300,1000,5,0", "128,300,1200,1,0"].iter()
+//!     .map(|line| line.parse::<HitObject>().unwrap())
+//!     .collect::<Vec<_>>();
+//! let heights = stacking::stack_heights(&objects, &[None, None], 5.0, 0.7, 14);
+//!
+//! assert_eq!(heights, vec![1, 0]);
+//! assert_eq!(stacking::stack_offset(1, 5.0), Position::new(-3.2, -3.2));
+//! ```
+
+use ::hitobject::{HitObject, HitObjectKind, Position};
+use ::slider::Slider;
+
+/// The distance under which objects are stacked, in osu!pixels.
+const STACK_DISTANCE: f64 = 3.0;
+
+/// The time before an object that it appears, in milliseconds, for an
+/// approach rate.
+pub fn preempt(approach_rate: f64) -> f64 {
+    if approach_rate < 5.0 {
+        1800.0 - 120.0 * approach_rate
+    } else {
+        1200.0 - 150.0 * (approach_rate - 5.0)
+    }
+}
+
+/// The offset of an object from its position for its stack height and the
+/// circle size.
+pub fn stack_offset(stack_height: i32, circle_size: f64) -> Position {
+    let scale = (1.0 - 0.7 * (circle_size - 5.0) / 5.0) / 2.0;
+    let offset = f64::from(stack_height) * scale * -6.4;
+
+    Position::new(offset, offset)
+}
+
+/// Calculates the stack height of each object, which must be ordered by
+/// time.
+///
+/// `sliders` gives the calculated slider for each object that is one, in
+/// the same order, as their end times and positions are needed. Objects on
+/// the end of a slider have negative heights, and are offset down and to the
+/// right of it.
+pub fn stack_heights(
+    objects: &[HitObject],
+    sliders: &[Option<Slider>],
+    approach_rate: f64,
+    stack_leniency: f64,
+    format_version: u32,
+) -> Vec<i32> {
+    let items = objects.iter().enumerate().map(|(idx, object)| {
+        let slider = sliders.get(idx).and_then(Option::as_ref);
+
+        Item {
+            kind: match object.kind {
+                HitObjectKind::Slider { .. } => Kind::Slider,
+                HitObjectKind::Spinner { .. } => Kind::Spinner,
+                _ => Kind::Circle,
+            },
+            time: object.time,
+            end_time: slider.map_or_else(|| object.end_time(), Slider::end_time),
+            position: object.position,
+            end_position: slider.map_or(object.position, Slider::end_position),
+            path_end: slider.map_or(object.position, |slider| slider.path.position_at(1.0)),
+        }
+    }).collect::<Vec<_>>();
+    let threshold = preempt(approach_rate) * stack_leniency;
+
+    if format_version >= 6 {
+        stack_new(&items, threshold)
+    } else {
+        stack_old(&items, threshold)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Kind {
+    Circle,
+    Slider,
+    Spinner,
+}
+
+struct Item {
+    kind: Kind,
+    time: f64,
+    end_time: f64,
+    position: Position,
+    end_position: Position,
+    /// The position at the end of a slider's path, regardless of repeats.
+    path_end: Position,
+}
+
+fn near(a: Position, b: Position) -> bool {
+    a.distance(b) < STACK_DISTANCE
+}
+
+/// Stacks objects by walking back from each object that is not yet stacked.
+fn stack_new(items: &[Item], threshold: f64) -> Vec<i32> {
+    let mut heights = vec![0; items.len()];
+
+    for i in (1..items.len()).rev() {
+        let mut current = i;
+
+        if heights[current] != 0 || items[current].kind == Kind::Spinner {
+            continue;
+        }
+
+        match items[current].kind {
+            Kind::Circle => {
+                for n in (0..i).rev() {
+                    let other = &items[n];
+
+                    if other.kind == Kind::Spinner {
+                        continue;
+                    }
+
+                    if items[current].time - other.end_time > threshold {
+                        break;
+                    }
+
+                    // Objects on the end of a slider are moved the other
+                    // way, below it.
+                    if other.kind == Kind::Slider && near(other.end_position, items[current].position) {
+                        let offset = heights[current] - heights[n] + 1;
+
+                        for j in n + 1..=i {
+                            if near(other.end_position, items[j].position) {
+                                heights[j] -= offset;
+                            }
+                        }
+
+                        break;
+                    }
+
+                    if near(other.position, items[current].position) {
+                        heights[n] = heights[current] + 1;
+                        current = n;
+                    }
+                }
+            },
+            Kind::Slider => {
+                for n in (0..i).rev() {
+                    let other = &items[n];
+
+                    if other.kind == Kind::Spinner {
+                        continue;
+                    }
+
+                    if items[current].time - other.time > threshold {
+                        break;
+                    }
+
+                    if near(other.end_position, items[current].position) {
+                        heights[n] = heights[current] + 1;
+                        current = n;
+                    }
+                }
+            },
+            Kind::Spinner => {},
+        }
+    }
+
+    heights
+}
+
+/// Stacks objects by walking forward from each object, as files before
+/// format version 6 are.
+fn stack_old(items: &[Item], threshold: f64) -> Vec<i32> {
+    let mut heights = vec![0; items.len()];
+
+    for (i, current) in items.iter().enumerate() {
+        if heights[i] != 0 && current.kind != Kind::Slider {
+            continue;
+        }
+
+        let mut start_time = current.end_time;
+        let mut slider_stack = 0;
+        let end = if current.kind == Kind::Slider { current.path_end } else { current.position };
+
+        for (j, other) in items.iter().enumerate().skip(i + 1) {
+            if other.time - threshold > start_time {
+                break;
+            }
+
+            if near(other.position, current.position) {
+                heights[i] += 1;
+                start_time = other.end_time;
+            } else if near(other.position, end) {
+                // As in the game, an object on the end of the slider only
+                // extends the stack from its start time.
+                slider_stack += 1;
+                heights[j] -= slider_stack;
+                start_time = other.time;
+            }
+        }
+    }
+
+    heights
+}
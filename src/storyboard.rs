@@ -0,0 +1,967 @@
+//! Parsing and evaluation of storyboards.
+//!
+//! Storyboards are read from the `[Events]` section of either a `.osb` file
+//! or a `.osu` file, with any `[Variables]` substituted beforehand. Lines in
+//! the `[Events]` section that do not belong to the storyboard, such as the
+//! background, video and breaks of a `.osu` file, are skipped.
+//!
+//! # Examples
+//!
+//! Parse a storyboard and retrieve the state of its first sprite one second
+//! in:
+//!
+//! ```rust
+//! use osu::storyboard::Storyboard;
+//!
+//! let storyboard = Storyboard::parse(r#"[Events]
+//! Sprite,Foreground,Centre,"sb/star.png",320,240
+//!  F,0,0,2000,0,1
+//! "#).unwrap();
+//!
+//! let state = storyboard.sprites[0].state_at(1000).unwrap();
+//!
+//! assert_eq!(state.opacity, 0.5);
+//! ```
+
+use std::cmp::Reverse;
+use std::f64::consts::PI;
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use ::{Error, Result};
+
+/// A parsed storyboard, consisting of its sprites and samples in the order
+/// that they were defined.
+#[derive(Clone, Debug, Default)]
+pub struct Storyboard {
+    pub samples: Vec<Sample>,
+    pub sprites: Vec<Sprite>,
+}
+
+impl Storyboard {
+    /// Parses the storyboard out of the contents of a `.osb` or `.osu` file.
+    ///
+    /// Returns [`Error::Parse`] if an object or command is malformed, with
+    /// the line number that it was on.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    pub fn parse(content: &str) -> Result<Storyboard> {
+        let variables = parse_variables(content);
+        let mut storyboard = Storyboard::default();
+        let mut in_events = false;
+
+        for (idx, raw) in content.lines().enumerate() {
+            let trimmed = raw.trim();
+
+            if trimmed.starts_with('[') {
+                in_events = trimmed == "[Events]";
+
+                continue;
+            }
+
+            if !in_events || trimmed.is_empty() || trimmed.starts_with("//") {
+                continue;
+            }
+
+            let line = substitute(raw.trim_end(), &variables);
+
+            storyboard.parse_line(&line).map_err(|why| {
+                Error::Parse(format!("line {}: {}", idx + 1, why))
+            })?;
+        }
+
+        Ok(storyboard)
+    }
+
+    /// Appends the objects of another storyboard after this one's.
+    ///
+    /// This is how the client combines a beatmap set's shared `.osb` with the
+    /// difficulty-specific storyboard in a `.osu` file.
+    pub fn extend(&mut self, other: Storyboard) {
+        self.samples.extend(other.samples);
+        self.sprites.extend(other.sprites);
+    }
+
+    fn parse_line(&mut self, line: &str) -> StdResult<(), String> {
+        let depth = line.chars().take_while(|&c| c == ' ' || c == '_').count();
+        let fields = line[depth..].split(',').map(str::trim).collect::<Vec<_>>();
+
+        match depth {
+            0 => self.parse_object(&fields),
+            1 => {
+                let sprite = self.sprites.last_mut()
+                    .ok_or_else(|| "command without a sprite".to_owned())?;
+
+                sprite.parse_command(&fields)
+            },
+            2 => {
+                let sprite = self.sprites.last_mut()
+                    .ok_or_else(|| "command without a sprite".to_owned())?;
+
+                match sprite.commands.last_mut() {
+                    Some(&mut Command::Loop { ref mut events, .. })
+                    | Some(&mut Command::Trigger { ref mut events, .. }) => {
+                        parse_event(&fields, events)
+                    },
+                    _ => Err("nested command outside of a loop or trigger".to_owned()),
+                }
+            },
+            _ => Err(format!("unexpected indentation depth {}", depth)),
+        }
+    }
+
+    fn parse_object(&mut self, fields: &[&str]) -> StdResult<(), String> {
+        match fields[0] {
+            "Sprite" | "4" => {
+                expect_len(fields, 6)?;
+
+                self.sprites.push(Sprite {
+                    layer: fields[1].parse()?,
+                    origin: fields[2].parse()?,
+                    path: unquote(fields[3]),
+                    x: parse_num(fields[4])?,
+                    y: parse_num(fields[5])?,
+                    animation: None,
+                    commands: vec![],
+                });
+            },
+            "Animation" | "6" => {
+                expect_len(fields, 8)?;
+
+                let loop_type = match fields.get(8) {
+                    Some(value) => value.parse()?,
+                    None => LoopType::LoopForever,
+                };
+
+                self.sprites.push(Sprite {
+                    layer: fields[1].parse()?,
+                    origin: fields[2].parse()?,
+                    path: unquote(fields[3]),
+                    x: parse_num(fields[4])?,
+                    y: parse_num(fields[5])?,
+                    animation: Some(Animation {
+                        frame_count: parse_num(fields[6])?,
+                        frame_delay: parse_num(fields[7])?,
+                        loop_type,
+                    }),
+                    commands: vec![],
+                });
+            },
+            "Sample" | "5" => {
+                expect_len(fields, 4)?;
+
+                self.samples.push(Sample {
+                    time: parse_time(fields[1])?,
+                    layer: fields[2].parse()?,
+                    path: unquote(fields[3]),
+                    volume: match fields.get(4) {
+                        Some(volume) => parse_num(volume)?,
+                        None => 100,
+                    },
+                });
+            },
+            // Backgrounds, videos, breaks and background colour changes of
+            // `.osu` files.
+            "0" | "1" | "2" | "3" | "Video" | "Break" => {},
+            other => return Err(format!("unknown object type {:?}", other)),
+        }
+
+        Ok(())
+    }
+}
+
+/// The layer that an object is drawn or played on.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Layer {
+    Background,
+    Fail,
+    Pass,
+    Foreground,
+    Overlay,
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Layer, String> {
+        Ok(match s {
+            "Background" | "0" => Layer::Background,
+            "Fail" | "1" => Layer::Fail,
+            "Pass" | "2" => Layer::Pass,
+            "Foreground" | "3" => Layer::Foreground,
+            "Overlay" | "4" => Layer::Overlay,
+            other => return Err(format!("unknown layer {:?}", other)),
+        })
+    }
+}
+
+/// The point of a sprite's image that its position refers to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Origin {
+    TopLeft,
+    TopCentre,
+    TopRight,
+    CentreLeft,
+    Centre,
+    CentreRight,
+    BottomLeft,
+    BottomCentre,
+    BottomRight,
+    /// Behaves the same as [`TopLeft`].
+    ///
+    /// [`TopLeft`]: #variant.TopLeft
+    Custom,
+}
+
+impl FromStr for Origin {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Origin, String> {
+        Ok(match s {
+            "TopLeft" | "0" => Origin::TopLeft,
+            "Centre" | "1" => Origin::Centre,
+            "CentreLeft" | "2" => Origin::CentreLeft,
+            "TopRight" | "3" => Origin::TopRight,
+            "BottomCentre" | "4" => Origin::BottomCentre,
+            "TopCentre" | "5" => Origin::TopCentre,
+            "Custom" | "6" => Origin::Custom,
+            "CentreRight" | "7" => Origin::CentreRight,
+            "BottomLeft" | "8" => Origin::BottomLeft,
+            "BottomRight" | "9" => Origin::BottomRight,
+            other => return Err(format!("unknown origin {:?}", other)),
+        })
+    }
+}
+
+/// Whether an animation repeats once it reaches its last frame.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LoopType {
+    LoopForever,
+    LoopOnce,
+}
+
+impl FromStr for LoopType {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<LoopType, String> {
+        Ok(match s {
+            "LoopForever" | "0" => LoopType::LoopForever,
+            "LoopOnce" | "1" => LoopType::LoopOnce,
+            other => return Err(format!("unknown loop type {:?}", other)),
+        })
+    }
+}
+
+/// The frame information of an animated sprite.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub frame_count: u32,
+    /// The time each frame is shown for, in milliseconds.
+    pub frame_delay: f64,
+    pub loop_type: LoopType,
+}
+
+/// An audio sample played at a point in time.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub time: i32,
+    pub layer: Layer,
+    pub path: String,
+    /// The volume, from `0` to `100`.
+    pub volume: u8,
+}
+
+/// A static or animated image, along with the commands that transform it.
+#[derive(Clone, Debug)]
+pub struct Sprite {
+    pub layer: Layer,
+    pub origin: Origin,
+    /// The path of the image, relative to the beatmap set's directory.
+    ///
+    /// For animations, the frame number is inserted before the extension;
+    /// refer to [`frame_path`].
+    ///
+    /// [`frame_path`]: #method.frame_path
+    pub path: String,
+    pub x: f64,
+    pub y: f64,
+    /// The frame information if the sprite is an animation.
+    pub animation: Option<Animation>,
+    pub commands: Vec<Command>,
+}
+
+impl Sprite {
+    /// The path of an animation's frame, such as `sb/star2.png` for frame `2`
+    /// of `sb/star.png`.
+    pub fn frame_path(&self, frame: u32) -> String {
+        match self.path.rfind('.') {
+            Some(idx) => format!("{}{}{}", &self.path[..idx], frame, &self.path[idx..]),
+            None => format!("{}{}", self.path, frame),
+        }
+    }
+
+    /// The time span between the earliest start and latest end of the
+    /// sprite's events, with loops unrolled.
+    ///
+    /// Returns `None` if the sprite has no events, in which case it is never
+    /// shown.
+    pub fn lifetime(&self) -> Option<(i32, i32)> {
+        let events = self.events();
+        let start = events.iter().map(|e| e.start_time).min();
+        let end = events.iter().map(|e| e.end_time).max();
+
+        match (start, end) {
+            (Some(start), Some(end)) => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    /// Returns the sprite's events with loops unrolled into absolute times.
+    ///
+    /// Trigger events are not included, as whether they run depends on
+    /// gameplay.
+    pub fn events(&self) -> Vec<Event> {
+        let mut events = vec![];
+
+        for command in &self.commands {
+            match *command {
+                Command::Event(ref event) => events.push(event.clone()),
+                Command::Loop { start_time, count, events: ref inner } => {
+                    // Each iteration lasts from the earliest inner start to
+                    // the latest inner end, as in the game.
+                    let first = inner.iter().map(|e| e.start_time).min().unwrap_or(0);
+                    let last = inner.iter().map(|e| e.end_time).max().unwrap_or(0);
+                    let duration = last - first;
+
+                    for iteration in 0..count.max(1) as i32 {
+                        let offset = start_time + iteration * duration;
+
+                        events.extend(inner.iter().map(|event| Event {
+                            start_time: event.start_time + offset,
+                            end_time: event.end_time + offset,
+                            ..event.clone()
+                        }));
+                    }
+                },
+                Command::Trigger { .. } => {},
+            }
+        }
+
+        events
+    }
+
+    /// Evaluates the state of the sprite at a given time.
+    ///
+    /// Before a property's first event, the property takes that event's
+    /// starting value. Between events, it keeps the ending value of the one
+    /// that most recently started.
+    ///
+    /// Returns `None` if the time is outside of the sprite's [`lifetime`].
+    ///
+    /// [`lifetime`]: #method.lifetime
+    pub fn state_at(&self, time: i32) -> Option<SpriteState> {
+        let (start, end) = self.lifetime()?;
+
+        if time < start || time > end {
+            return None;
+        }
+
+        let mut events = self.events();
+        events.sort_by_key(|event| event.start_time);
+
+        let mut state = SpriteState {
+            x: self.x,
+            y: self.y,
+            scale: 1.0,
+            vector_scale: (1.0, 1.0),
+            rotation: 0.0,
+            opacity: 1.0,
+            colour: [255.0, 255.0, 255.0],
+            flip_horizontal: false,
+            flip_vertical: false,
+            additive: false,
+            frame: None,
+        };
+
+        let time = f64::from(time);
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::Move(from, to) => Some((from.0, to.0)),
+            EventKind::MoveX(from, to) => Some((from, to)),
+            _ => None,
+        }) {
+            state.x = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::Move(from, to) => Some((from.1, to.1)),
+            EventKind::MoveY(from, to) => Some((from, to)),
+            _ => None,
+        }) {
+            state.y = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::Scale(from, to) => Some((from, to)),
+            _ => None,
+        }) {
+            state.scale = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::VectorScale(from, to) => Some((from.0, to.0)),
+            _ => None,
+        }) {
+            state.vector_scale.0 = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::VectorScale(from, to) => Some((from.1, to.1)),
+            _ => None,
+        }) {
+            state.vector_scale.1 = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::Rotate(from, to) => Some((from, to)),
+            _ => None,
+        }) {
+            state.rotation = value;
+        }
+
+        if let Some(value) = resolve(&events, time, |kind| match *kind {
+            EventKind::Fade(from, to) => Some((from, to)),
+            _ => None,
+        }) {
+            state.opacity = value;
+        }
+
+        for channel in 0..3 {
+            if let Some(value) = resolve(&events, time, |kind| match *kind {
+                EventKind::Colour(from, to) => Some((from[channel], to[channel])),
+                _ => None,
+            }) {
+                state.colour[channel] = value;
+            }
+        }
+
+        for event in &events {
+            let parameter = match event.kind {
+                EventKind::Parameter(parameter) => parameter,
+                _ => continue,
+            };
+
+            let start = f64::from(event.start_time);
+            let end = f64::from(event.end_time);
+
+            // A parameter with no duration stays applied once it starts.
+            if time < start || (end > start && time > end) {
+                continue;
+            }
+
+            match parameter {
+                Parameter::Additive => state.additive = true,
+                Parameter::FlipHorizontal => state.flip_horizontal = true,
+                Parameter::FlipVertical => state.flip_vertical = true,
+            }
+        }
+
+        if let Some(ref animation) = self.animation {
+            let count = animation.frame_count.max(1);
+            let elapsed = time - f64::from(start);
+            let frame = if animation.frame_delay > 0.0 {
+                (elapsed / animation.frame_delay) as u32
+            } else {
+                0
+            };
+
+            state.frame = Some(match animation.loop_type {
+                LoopType::LoopForever => frame % count,
+                LoopType::LoopOnce => frame.min(count - 1),
+            });
+        }
+
+        Some(state)
+    }
+
+    fn parse_command(&mut self, fields: &[&str]) -> StdResult<(), String> {
+        match fields[0] {
+            "L" => {
+                expect_len(fields, 3)?;
+
+                self.commands.push(Command::Loop {
+                    start_time: parse_time(fields[1])?,
+                    count: parse_num(fields[2])?,
+                    events: vec![],
+                });
+            },
+            "T" => {
+                expect_len(fields, 4)?;
+
+                self.commands.push(Command::Trigger {
+                    trigger: fields[1].to_owned(),
+                    start_time: parse_time(fields[2])?,
+                    end_time: parse_time(fields[3])?,
+                    group: match fields.get(4) {
+                        Some(group) => Some(parse_num(group)?),
+                        None => None,
+                    },
+                    events: vec![],
+                });
+            },
+            _ => {
+                let mut events = vec![];
+                parse_event(fields, &mut events)?;
+
+                self.commands.extend(events.into_iter().map(Command::Event));
+            },
+        }
+
+        Ok(())
+    }
+}
+
+/// The state of a sprite at a point in time, as produced by
+/// [`Sprite::state_at`].
+///
+/// [`Sprite::state_at`]: struct.Sprite.html#method.state_at
+#[derive(Clone, Debug)]
+pub struct SpriteState {
+    pub x: f64,
+    pub y: f64,
+    pub scale: f64,
+    pub vector_scale: (f64, f64),
+    /// The rotation in radians, clockwise.
+    pub rotation: f64,
+    pub opacity: f64,
+    /// The red, green and blue tint, from `0` to `255`.
+    pub colour: [f64; 3],
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub additive: bool,
+    /// The frame being shown, if the sprite is an animation.
+    pub frame: Option<u32>,
+}
+
+/// A top-level command of a sprite.
+#[derive(Clone, Debug)]
+pub enum Command {
+    /// A single event.
+    Event(Event),
+    /// A group of events repeated `count` times, with times relative to the
+    /// loop's start time.
+    Loop {
+        start_time: i32,
+        count: u32,
+        events: Vec<Event>,
+    },
+    /// A group of events that run when a gameplay trigger, such as
+    /// `HitSoundClap` or `Passing`, fires between the start and end times.
+    Trigger {
+        trigger: String,
+        start_time: i32,
+        end_time: i32,
+        group: Option<i32>,
+        events: Vec<Event>,
+    },
+}
+
+/// A change of one of a sprite's properties over a span of time.
+///
+/// Shorthand commands that chain several values are expanded into one event
+/// per step.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub easing: Easing,
+    pub start_time: i32,
+    pub end_time: i32,
+    pub kind: EventKind,
+}
+
+/// The property that an [`Event`] changes, with its starting and ending
+/// values.
+///
+/// [`Event`]: struct.Event.html
+#[derive(Clone, Copy, Debug)]
+pub enum EventKind {
+    /// `F`
+    Fade(f64, f64),
+    /// `M`
+    Move((f64, f64), (f64, f64)),
+    /// `MX`
+    MoveX(f64, f64),
+    /// `MY`
+    MoveY(f64, f64),
+    /// `S`
+    Scale(f64, f64),
+    /// `V`
+    VectorScale((f64, f64), (f64, f64)),
+    /// `R`
+    Rotate(f64, f64),
+    /// `C`
+    Colour([f64; 3], [f64; 3]),
+    /// `P`
+    Parameter(Parameter),
+}
+
+/// A flag applied to a sprite for the duration of a `P` event.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Parameter {
+    /// `A`
+    Additive,
+    /// `H`
+    FlipHorizontal,
+    /// `V`
+    FlipVertical,
+}
+
+/// The easing function used to interpolate an event's values.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Easing {
+    Linear,
+    Out,
+    In,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticHalfOut,
+    ElasticQuarterOut,
+    ElasticInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    /// Retrieves an easing by its numeric ID, as used in storyboard scripts.
+    pub fn from_id(id: u8) -> Option<Easing> {
+        use self::Easing::*;
+
+        const ALL: [Easing; 35] = [
+            Linear, Out, In, QuadIn, QuadOut, QuadInOut, CubicIn, CubicOut,
+            CubicInOut, QuartIn, QuartOut, QuartInOut, QuintIn, QuintOut,
+            QuintInOut, SineIn, SineOut, SineInOut, ExpoIn, ExpoOut,
+            ExpoInOut, CircIn, CircOut, CircInOut, ElasticIn, ElasticOut,
+            ElasticHalfOut, ElasticQuarterOut, ElasticInOut, BackIn, BackOut,
+            BackInOut, BounceIn, BounceOut, BounceInOut,
+        ];
+
+        ALL.get(id as usize).cloned()
+    }
+
+    /// Maps linear progress, from `0.0` to `1.0`, onto the eased progress.
+    pub fn apply(&self, t: f64) -> f64 {
+        use self::Easing::*;
+
+        const BACK: f64 = 1.70158;
+
+        match *self {
+            Linear => t,
+            In | QuadIn => t * t,
+            Out | QuadOut => t * (2.0 - t),
+            QuadInOut => in_out(t, |t| t * t),
+            CubicIn => t.powi(3),
+            CubicOut => reverse(t, |t| t.powi(3)),
+            CubicInOut => in_out(t, |t| t.powi(3)),
+            QuartIn => t.powi(4),
+            QuartOut => reverse(t, |t| t.powi(4)),
+            QuartInOut => in_out(t, |t| t.powi(4)),
+            QuintIn => t.powi(5),
+            QuintOut => reverse(t, |t| t.powi(5)),
+            QuintInOut => in_out(t, |t| t.powi(5)),
+            SineIn => 1.0 - (t * PI / 2.0).cos(),
+            SineOut => (t * PI / 2.0).sin(),
+            SineInOut => 0.5 - (t * PI).cos() / 2.0,
+            ExpoIn => expo_in(t),
+            ExpoOut => reverse(t, expo_in),
+            ExpoInOut => in_out(t, expo_in),
+            CircIn => circ_in(t),
+            CircOut => reverse(t, circ_in),
+            CircInOut => in_out(t, circ_in),
+            ElasticIn => reverse(t, |t| elastic_out(t, 1.0)),
+            ElasticOut => elastic_out(t, 1.0),
+            ElasticHalfOut => elastic_out(t, 0.5),
+            ElasticQuarterOut => elastic_out(t, 0.25),
+            ElasticInOut => {
+                let t = 2.0 * t;
+                let shift = ELASTIC_SHIFT * 1.5;
+                let period = ELASTIC_PERIOD * 1.5;
+
+                if t < 1.0 {
+                    -0.5 * 2f64.powf(10.0 * (t - 1.0))
+                        * ((1.0 - shift - t) * 2.0 * PI / period).sin()
+                } else {
+                    let t = t - 1.0;
+
+                    0.5 * 2f64.powf(-10.0 * t)
+                        * ((t - shift) * 2.0 * PI / period).sin() + 1.0
+                }
+            },
+            BackIn => t * t * ((BACK + 1.0) * t - BACK),
+            BackOut => reverse(t, |t| t * t * ((BACK + 1.0) * t - BACK)),
+            BackInOut => {
+                let s = BACK * 1.525;
+
+                in_out(t, |t| t * t * ((s + 1.0) * t - s))
+            },
+            BounceIn => reverse(t, bounce_out),
+            BounceOut => bounce_out(t),
+            BounceInOut => if t < 0.5 {
+                (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+            } else {
+                (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+            },
+        }
+    }
+}
+
+fn reverse<F: Fn(f64) -> f64>(t: f64, f: F) -> f64 {
+    1.0 - f(1.0 - t)
+}
+
+fn in_out<F: Fn(f64) -> f64>(t: f64, f: F) -> f64 {
+    if t < 0.5 {
+        f(2.0 * t) / 2.0
+    } else {
+        1.0 - f(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+fn expo_in(t: f64) -> f64 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2f64.powf(10.0 * (t - 1.0))
+    }
+}
+
+fn circ_in(t: f64) -> f64 {
+    1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+const ELASTIC_PERIOD: f64 = 0.3;
+const ELASTIC_SHIFT: f64 = 0.075;
+
+fn elastic_out(t: f64, frequency: f64) -> f64 {
+    2f64.powf(-10.0 * t)
+        * ((frequency * t - ELASTIC_SHIFT) * 2.0 * PI / ELASTIC_PERIOD).sin()
+        + 1.0
+}
+
+fn bounce_out(t: f64) -> f64 {
+    if t < 1.0 / 2.75 {
+        7.5625 * t * t
+    } else if t < 2.0 / 2.75 {
+        let t = t - 1.5 / 2.75;
+
+        7.5625 * t * t + 0.75
+    } else if t < 2.5 / 2.75 {
+        let t = t - 2.25 / 2.75;
+
+        7.5625 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / 2.75;
+
+        7.5625 * t * t + 0.984375
+    }
+}
+
+/// Resolves the value of a single property at a time from the events that
+/// affect it, which must be sorted by start time.
+fn resolve<F>(events: &[Event], time: f64, values: F) -> Option<f64>
+    where F: Fn(&EventKind) -> Option<(f64, f64)> {
+    let mut relevant = events.iter()
+        .filter_map(|event| values(&event.kind).map(|v| (event, v)))
+        .peekable();
+
+    let mut current = match relevant.peek() {
+        Some(&(event, (from, _))) if time < f64::from(event.start_time) => {
+            return Some(from);
+        },
+        Some(&(event, values)) => (event, values),
+        None => return None,
+    };
+
+    for (event, values) in relevant {
+        if f64::from(event.start_time) > time {
+            break;
+        }
+
+        current = (event, values);
+    }
+
+    let (event, (from, to)) = current;
+    let start = f64::from(event.start_time);
+    let end = f64::from(event.end_time);
+
+    if time >= end {
+        return Some(to);
+    }
+
+    let progress = event.easing.apply((time - start) / (end - start));
+
+    Some(from + (to - from) * progress)
+}
+
+fn parse_event(fields: &[&str], events: &mut Vec<Event>) -> StdResult<(), String> {
+    expect_len(fields, 4)?;
+
+    let easing = parse_num::<u8>(fields[1])?;
+    let easing = Easing::from_id(easing)
+        .ok_or_else(|| format!("unknown easing {}", easing))?;
+    let start_time = parse_time(fields[2])?;
+    let end_time = if fields[3].is_empty() {
+        start_time
+    } else {
+        parse_time(fields[3])?
+    };
+
+    if fields[0] == "P" {
+        expect_len(fields, 5)?;
+
+        let parameter = match fields[4] {
+            "A" => Parameter::Additive,
+            "H" => Parameter::FlipHorizontal,
+            "V" => Parameter::FlipVertical,
+            other => return Err(format!("unknown parameter {:?}", other)),
+        };
+
+        events.push(Event {
+            easing,
+            start_time,
+            end_time,
+            kind: EventKind::Parameter(parameter),
+        });
+
+        return Ok(());
+    }
+
+    let arity = match fields[0] {
+        "F" | "MX" | "MY" | "S" | "R" => 1,
+        "M" | "V" => 2,
+        "C" => 3,
+        other => return Err(format!("unknown command {:?}", other)),
+    };
+
+    let values = fields[4..].iter()
+        .map(|value| parse_num::<f64>(value))
+        .collect::<StdResult<Vec<_>, _>>()?;
+
+    if values.len() < arity || values.len() % arity != 0 {
+        return Err(format!("wrong number of values for {:?}", fields[0]));
+    }
+
+    let steps = (values.len() / arity).max(2) - 1;
+    let duration = end_time - start_time;
+
+    for step in 0..steps {
+        let from = &values[step * arity..step * arity + arity];
+        let to = values.get((step + 1) * arity..(step + 2) * arity).unwrap_or(from);
+
+        let kind = match fields[0] {
+            "F" => EventKind::Fade(from[0], to[0]),
+            "M" => EventKind::Move((from[0], from[1]), (to[0], to[1])),
+            "MX" => EventKind::MoveX(from[0], to[0]),
+            "MY" => EventKind::MoveY(from[0], to[0]),
+            "S" => EventKind::Scale(from[0], to[0]),
+            "V" => EventKind::VectorScale((from[0], from[1]), (to[0], to[1])),
+            "R" => EventKind::Rotate(from[0], to[0]),
+            _ => EventKind::Colour(
+                [from[0], from[1], from[2]],
+                [to[0], to[1], to[2]],
+            ),
+        };
+
+        events.push(Event {
+            easing,
+            start_time: start_time + duration * step as i32,
+            end_time: end_time + duration * step as i32,
+            kind,
+        });
+    }
+
+    Ok(())
+}
+
+fn parse_variables(content: &str) -> Vec<(String, String)> {
+    let mut variables = vec![];
+    let mut in_variables = false;
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_variables = line == "[Variables]";
+
+            continue;
+        }
+
+        if !in_variables || !line.starts_with('$') {
+            continue;
+        }
+
+        if let Some(idx) = line.find('=') {
+            variables.push((line[..idx].to_owned(), line[idx + 1..].to_owned()));
+        }
+    }
+
+    // Substitute longer names first so that `$ab` isn't replaced by `$a`.
+    variables.sort_by_key(|variable| Reverse(variable.0.len()));
+
+    variables
+}
+
+fn substitute(line: &str, variables: &[(String, String)]) -> String {
+    let mut line = line.to_owned();
+
+    if line.contains('$') {
+        for variable in variables {
+            line = line.replace(&*variable.0, &variable.1);
+        }
+    }
+
+    line
+}
+
+fn expect_len(fields: &[&str], len: usize) -> StdResult<(), String> {
+    if fields.len() < len {
+        return Err(format!("expected at least {} fields, found {}", len, fields.len()));
+    }
+
+    Ok(())
+}
+
+fn parse_num<T: FromStr>(value: &str) -> StdResult<T, String> {
+    value.parse().map_err(|_| format!("invalid number {:?}", value))
+}
+
+/// Parses a time, truncating any fractional milliseconds.
+fn parse_time(value: &str) -> StdResult<i32, String> {
+    value.parse::<i32>()
+        .or_else(|_| value.parse::<f64>().map(|time| time as i32))
+        .map_err(|_| format!("invalid time {:?}", value))
+}
+
+fn unquote(value: &str) -> String {
+    value.trim_matches('"').to_owned()
+}
@@ -0,0 +1,244 @@
+//! Parsing and formatting of editor timestamps.
+//!
+//! Timestamps are copied from the beatmap editor into modding comments, in
+//! the form `00:45:123 (1,2,3) - `: minutes, seconds and milliseconds,
+//! followed by the selected objects. Objects are selected by their combo
+//! numbers, or for mania by their `time|column`.
+//!
+//! # Examples
+//!
+//! Find the timestamps in a comment and link to the first in the editor:
+//!
+//! ```rust
+//! use osu::timestamp::{Selection, Timestamp};
+//!
+//! let timestamps = Timestamp::find_all("00:45:123 (1,2,3) - spacing, and 01:02:003 -");
+//!
+//! assert_eq!(timestamps.len(), 2);
+//! assert_eq!(timestamps[0].time, 45_123);
+//! assert_eq!(timestamps[0].selection[2], Selection::Combo(3));
+//! assert_eq!(timestamps[0].edit_link(), "osu://edit/00:45:123%20(1,2,3)%20-");
+//! ```
+//!
+//! A timestamp's selection is matched to a difficulty's hit objects with
+//! [`Timestamp::resolve`].
+//!
+//! [`Timestamp::resolve`]: struct.Timestamp.html#method.resolve
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::result::Result as StdResult;
+use std::str::FromStr;
+use ::hitobject::{self, HitObject};
+use ::{Error, Result};
+
+/// A position in the editor, with any selected objects.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Timestamp {
+    /// The time in milliseconds.
+    pub time: u32,
+    pub selection: Vec<Selection>,
+}
+
+impl Timestamp {
+    /// Creates a timestamp without a selection.
+    pub fn new(time: u32) -> Timestamp {
+        Timestamp {
+            time,
+            selection: vec![],
+        }
+    }
+
+    /// Finds the timestamps in a piece of text, such as a modding comment.
+    pub fn find_all(text: &str) -> Vec<Timestamp> {
+        let mut timestamps = vec![];
+        let mut rest = text;
+
+        while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+            rest = &rest[start..];
+
+            match parse_prefix(rest) {
+                Some((timestamp, len)) => {
+                    timestamps.push(timestamp);
+                    rest = &rest[len..];
+                },
+                None => {
+                    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                    rest = &rest[digits..];
+                },
+            }
+        }
+
+        timestamps
+    }
+
+    /// Finds the selected objects among a difficulty's hit objects, which
+    /// must be ordered by time, returning the index of each or `None` if it
+    /// is not found.
+    ///
+    /// Combo numbers are matched in order from the first object at the
+    /// timestamp's time, as the editor selects them. Mania selections are
+    /// matched by their time and column, for a number of keys given by the
+    /// difficulty's circle size.
+    pub fn resolve(&self, objects: &[HitObject], keys: u32) -> Vec<Option<usize>> {
+        let combo_numbers = hitobject::combo_numbers(objects);
+        // Times in timestamps are rounded to whole milliseconds.
+        let mut next = objects.iter()
+            .position(|object| object.time.round() >= f64::from(self.time))
+            .unwrap_or(objects.len());
+
+        self.selection.iter().map(|selection| match *selection {
+            Selection::Combo(number) => {
+                let found = (next..objects.len()).find(|&idx| combo_numbers[idx] == number);
+
+                if let Some(idx) = found {
+                    next = idx + 1;
+                }
+
+                found
+            },
+            Selection::Mania { time, column } => objects.iter().position(|object| {
+                object.time.round() == f64::from(time) && object.column(keys) == column
+            }),
+        }).collect()
+    }
+
+    /// A link that opens the editor at the timestamp, selecting its objects.
+    pub fn edit_link(&self) -> String {
+        format!("osu://edit/{}", self.to_string().replace(' ', "%20"))
+    }
+}
+
+impl Display for Timestamp {
+    /// Formats the timestamp as the editor does, such as `00:45:123 (1,2) -`.
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(
+            f,
+            "{:02}:{:02}:{:03}",
+            self.time / 60_000,
+            self.time / 1000 % 60,
+            self.time % 1000,
+        )?;
+
+        if !self.selection.is_empty() {
+            f.write_str(" (")?;
+
+            for (idx, selection) in self.selection.iter().enumerate() {
+                if idx > 0 {
+                    f.write_str(",")?;
+                }
+
+                match *selection {
+                    Selection::Combo(number) => write!(f, "{}", number)?,
+                    Selection::Mania { time, column } => write!(f, "{}|{}", time, column)?,
+                }
+            }
+
+            f.write_str(")")?;
+        }
+
+        f.write_str(" -")
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = Error;
+
+    /// Parses a timestamp, optionally followed by a ` -` separator, or an
+    /// `osu://edit/` link.
+    ///
+    /// Returns [`Error::Parse`] if it is malformed.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn from_str(s: &str) -> Result<Timestamp> {
+        let decoded = s.trim().trim_start_matches("osu://edit/").replace("%20", " ");
+
+        if let Some((timestamp, len)) = parse_prefix(&decoded) {
+            if decoded[len..].trim().trim_start_matches('-').trim().is_empty() {
+                return Ok(timestamp);
+            }
+        }
+
+        Err(Error::Parse(format!("invalid timestamp {:?}", s)))
+    }
+}
+
+/// A selected object.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Selection {
+    /// An object by its combo number.
+    Combo(u32),
+    /// A mania object by its time in milliseconds and its column, starting
+    /// from 0.
+    Mania {
+        time: u32,
+        column: u32,
+    },
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> StdResult<Selection, String> {
+        let invalid = || format!("invalid selection {:?}", s);
+
+        match s.find('|') {
+            Some(idx) => Ok(Selection::Mania {
+                time: s[..idx].parse().map_err(|_| invalid())?,
+                column: s[idx + 1..].parse().map_err(|_| invalid())?,
+            }),
+            None => s.parse().map(Selection::Combo).map_err(|_| invalid()),
+        }
+    }
+}
+
+/// Parses a timestamp at the start of a string, returning it and its length.
+///
+/// The selection is optional, and is not included if it is malformed.
+fn parse_prefix(s: &str) -> Option<(Timestamp, usize)> {
+    let bytes = s.as_bytes();
+    let minutes_len = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let len = minutes_len + 7;
+
+    if minutes_len == 0 || bytes.len() < len {
+        return None;
+    }
+
+    let seconds = s.get(minutes_len + 1..minutes_len + 3)?;
+    let millis = s.get(minutes_len + 4..len)?;
+    let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+
+    if bytes[minutes_len] != b':' || bytes[minutes_len + 3] != b':' || !digits(seconds) || !digits(millis) {
+        return None;
+    }
+
+    // A fourth digit means this is not a timestamp.
+    if let Some(byte) = bytes.get(len) {
+        if byte.is_ascii_digit() {
+            return None;
+        }
+    }
+
+    let seconds = seconds.parse::<u32>().ok().filter(|&seconds| seconds < 60)?;
+    let time = s[..minutes_len].parse::<u32>().ok()?
+        .checked_mul(60_000)?
+        .checked_add(seconds * 1000 + millis.parse::<u32>().ok()?)?;
+    let mut timestamp = Timestamp::new(time);
+
+    let rest = &s[len..];
+
+    if rest.starts_with(" (") {
+        if let Some(end) = rest.find(')') {
+            let selection = rest[2..end].split(',')
+                .map(|item| item.trim().parse())
+                .collect::<StdResult<Vec<Selection>, _>>();
+
+            if let Ok(selection) = selection {
+                timestamp.selection = selection;
+
+                return Some((timestamp, len + end + 1));
+            }
+        }
+    }
+
+    Some((timestamp, len))
+}
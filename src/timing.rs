@@ -0,0 +1,370 @@
+//! Timing points from the `[TimingPoints]` section of a `.osu` file.
+//!
+//! Uninherited (red) timing points set the beat length and meter, while
+//! inherited (green) points change the slider velocity, hitsounds and kiai
+//! time from their own time onwards.
+//!
+//! A [`Timing`] answers which of these apply at a given time, snaps times to
+//! beat divisors and finds unsnapped objects.
+//!
+//! # Examples
+//!
+//! Find the BPM and slider velocity at a time:
+//!
+//! ```rust
+//! use osu::timing::{Timing, TimingPoint};
+//!
+//! let timing = Timing::new(vec![
+//!     "1000,416.565418127,4,2,1,60,1,0".parse::<TimingPoint>().unwrap(),
+//!     "5999,-50,4,2,1,60,0,1".parse::<TimingPoint>().unwrap(),
+//! ]);
+//!
+//! assert_eq!(timing.bpm_at(2000.0).map(f64::round), Some(144.0));
+//! assert_eq!(timing.slider_velocity_at(2000.0), 1.0);
+//! assert_eq!(timing.slider_velocity_at(6000.0), 2.0);
+//! assert!(timing.kiai_at(6000.0));
+//! ```
+//!
+//! [`Timing`]: struct.Timing.html
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::str::FromStr;
+use ::hitobject::HitObject;
+use ::slider::Slider;
+use ::{Error, Result};
+
+/// The beat divisors that the editor can snap to, from 1/1 to 1/16.
+pub const DIVISORS: [u32; 8] = [1, 2, 3, 4, 6, 8, 12, 16];
+
+/// A timing point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimingPoint {
+    /// The time in milliseconds.
+    pub time: f64,
+    /// The length of a beat in milliseconds for uninherited points, or a
+    /// negative inverse slider velocity percentage for inherited points, such
+    /// as `-50` for a velocity of 2x.
+    pub beat_length: f64,
+    /// The number of beats in a measure.
+    pub meter: u32,
+    /// The default sample set: `0` for the beatmap's default, then normal,
+    /// soft and drum.
+    pub sample_set: u8,
+    /// The custom sample index, with `0` for the skin's samples.
+    pub sample_index: u32,
+    /// The volume percentage of hitsounds.
+    pub volume: u32,
+    /// Whether this is a red timing point, rather than a green one.
+    pub uninherited: bool,
+    /// Whether kiai time is enabled.
+    pub kiai: bool,
+    /// Whether the first bar line of the measure is omitted, in taiko and
+    /// mania.
+    pub omit_first_bar_line: bool,
+}
+
+impl TimingPoint {
+    /// The beats per minute of an uninherited point.
+    pub fn bpm(&self) -> f64 {
+        60_000.0 / self.beat_length
+    }
+
+    /// The slider velocity multiplier of an inherited point, or `1` for an
+    /// uninherited point.
+    #[allow(clippy::manual_clamp)]
+    pub fn slider_velocity(&self) -> f64 {
+        if self.uninherited || self.beat_length >= 0.0 {
+            1.0
+        } else {
+            (-100.0 / self.beat_length).max(0.1).min(10.0)
+        }
+    }
+}
+
+impl FromStr for TimingPoint {
+    type Err = Error;
+
+    /// Parses a line of the `[TimingPoints]` section.
+    ///
+    /// Fields that older files omit take their defaults, and a point without
+    /// an `uninherited` field is inherited if its beat length is negative.
+    ///
+    /// Returns [`Error::Parse`] if a field is malformed.
+    ///
+    /// [`Error::Parse`]: ../error/enum.Error.html#variant.Parse
+    fn from_str(s: &str) -> Result<TimingPoint> {
+        let invalid = || Error::Parse(format!("invalid timing point {:?}", s));
+        let fields = s.trim().split(',').map(str::trim).collect::<Vec<_>>();
+
+        if fields.len() < 2 {
+            return Err(invalid());
+        }
+
+        let time = fields[0].parse::<f64>().map_err(|_| invalid())?;
+        let beat_length = fields[1].parse::<f64>().map_err(|_| invalid())?;
+        let integer = |idx: usize, default: u32| match fields.get(idx) {
+            Some(field) => field.parse::<u32>().map_err(|_| invalid()),
+            None => Ok(default),
+        };
+        let effects = integer(7, 0)?;
+
+        Ok(TimingPoint {
+            time,
+            beat_length,
+            meter: integer(2, 4)?.max(1),
+            sample_set: integer(3, 0)? as u8,
+            sample_index: integer(4, 0)?,
+            volume: integer(5, 100)?,
+            uninherited: match fields.get(6) {
+                Some(&"0") => false,
+                Some(_) => true,
+                None => beat_length >= 0.0,
+            },
+            kiai: effects & 1 != 0,
+            omit_first_bar_line: effects & 8 != 0,
+        })
+    }
+}
+
+/// The timing points of a beatmap, ordered by time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timing {
+    points: Vec<TimingPoint>,
+}
+
+impl Timing {
+    /// Creates a timing from its points, sorting them by time.
+    ///
+    /// Points at the same time keep their order, so that a green point
+    /// listed after a red one takes effect over it.
+    pub fn new(mut points: Vec<TimingPoint>) -> Timing {
+        points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(Ordering::Equal));
+
+        Timing { points }
+    }
+
+    pub fn points(&self) -> &[TimingPoint] {
+        &self.points
+    }
+
+    /// The uninherited point that applies at a time.
+    ///
+    /// As in the game, the first uninherited point also applies before its
+    /// own time.
+    pub fn uninherited_at(&self, time: f64) -> Option<&TimingPoint> {
+        let mut reds = self.points.iter().filter(|point| point.uninherited);
+        let first = reds.next()?;
+
+        Some(reds.take_while(|point| point.time <= time).last().unwrap_or(first))
+    }
+
+    /// The point of either kind that applies at a time, if any is at or
+    /// before it.
+    pub fn point_at(&self, time: f64) -> Option<&TimingPoint> {
+        self.points.iter().take_while(|point| point.time <= time).last()
+    }
+
+    /// The length of a beat in milliseconds at a time.
+    pub fn beat_length_at(&self, time: f64) -> Option<f64> {
+        self.uninherited_at(time).map(|point| point.beat_length)
+    }
+
+    /// The beats per minute at a time.
+    pub fn bpm_at(&self, time: f64) -> Option<f64> {
+        self.uninherited_at(time).map(TimingPoint::bpm)
+    }
+
+    /// The slider velocity multiplier at a time, which is reset to `1` by
+    /// each uninherited point.
+    pub fn slider_velocity_at(&self, time: f64) -> f64 {
+        self.point_at(time).map_or(1.0, TimingPoint::slider_velocity)
+    }
+
+    /// Whether kiai time is enabled at a time.
+    pub fn kiai_at(&self, time: f64) -> bool {
+        match self.point_at(time) {
+            Some(point) => point.kiai,
+            None => false,
+        }
+    }
+
+    /// The start and end times of each kiai section, with a section that is
+    /// still enabled at the last point ending at `end_time`.
+    pub fn kiai_ranges(&self, end_time: f64) -> Vec<(f64, f64)> {
+        let mut ranges = vec![];
+        let mut start = None;
+
+        for point in &self.points {
+            match (start, point.kiai) {
+                (None, true) => start = Some(point.time),
+                (Some(time), false) => {
+                    ranges.push((time, point.time));
+                    start = None;
+                },
+                _ => {},
+            }
+        }
+
+        if let Some(time) = start {
+            ranges.push((time, end_time.max(time)));
+        }
+
+        ranges
+    }
+
+    /// The nearest time to `time` on a beat divisor, such as `4` for 1/4
+    /// beats, counting from the uninherited point that applies.
+    ///
+    /// Returns `None` if there are no uninherited points, or if the divisor
+    /// is not between 1 and 16.
+    pub fn snap(&self, time: f64, divisor: u32) -> Option<f64> {
+        if divisor == 0 || divisor > 16 {
+            return None;
+        }
+
+        let point = self.uninherited_at(time)?;
+        let step = point.beat_length / f64::from(divisor);
+
+        if step <= 0.0 {
+            return None;
+        }
+
+        Some(point.time + ((time - point.time) / step).round() * step)
+    }
+
+    /// The nearest snap to a time over each of the editor's [`DIVISORS`],
+    /// preferring the coarsest divisor when several are as near.
+    ///
+    /// [`DIVISORS`]: constant.DIVISORS.html
+    pub fn nearest_snap(&self, time: f64) -> Option<Snap> {
+        let mut nearest: Option<Snap> = None;
+
+        for &divisor in &DIVISORS {
+            let snapped = self.snap(time, divisor)?;
+            let closer = match nearest {
+                Some(ref snap) => (snapped - time).abs() < snap.offset().abs() - 1e-6,
+                None => true,
+            };
+
+            if closer {
+                nearest = Some(Snap {
+                    time: snapped,
+                    divisor,
+                    unsnapped_time: time,
+                });
+            }
+        }
+
+        nearest
+    }
+
+    /// Finds the objects whose start or end time is more than `leniency`
+    /// milliseconds from its nearest snap.
+    ///
+    /// `sliders` gives the calculated slider for each object that is one, in
+    /// the same order, such as from [`Difficulty::sliders`], as the end time
+    /// of a slider depends on its length and velocity. Sliders missing from
+    /// it only have their start time checked.
+    ///
+    /// The editor rounds snapped times to whole milliseconds, so a leniency
+    /// of `1` reports only objects that were moved off the beat.
+    ///
+    /// [`Difficulty::sliders`]: ../osz/struct.Difficulty.html#method.sliders
+    pub fn unsnapped(&self, objects: &[HitObject], sliders: &[Option<Slider>], leniency: f64) -> Vec<Unsnapped> {
+        let mut unsnapped = vec![];
+
+        for (index, object) in objects.iter().enumerate() {
+            let end_time = match sliders.get(index).and_then(Option::as_ref) {
+                Some(slider) => slider.end_time(),
+                None => object.end_time(),
+            };
+            let mut times = vec![object.time];
+
+            if end_time != object.time {
+                times.push(end_time);
+            }
+
+            for time in times {
+                if let Some(snap) = self.nearest_snap(time) {
+                    if snap.offset().abs() > leniency {
+                        unsnapped.push(Unsnapped { index, snap });
+                    }
+                }
+            }
+        }
+
+        unsnapped
+    }
+
+    /// The most common BPM, weighted by how long each uninherited point
+    /// lasts until `last_time`, usually the end of the last object.
+    ///
+    /// This is what the API gives as [`Beatmap::bpm`]. The first point is
+    /// counted from the start of the song, and points after `last_time` are
+    /// not counted.
+    ///
+    /// [`Beatmap::bpm`]: ../struct.Beatmap.html#structfield.bpm
+    pub fn dominant_bpm(&self, last_time: f64) -> Option<f64> {
+        let reds = self.points.iter().filter(|point| point.uninherited).collect::<Vec<_>>();
+        let mut durations: HashMap<i64, f64> = HashMap::new();
+        let mut order = vec![];
+
+        for (idx, point) in reds.iter().enumerate() {
+            let start = if idx == 0 { 0.0 } else { point.time };
+            let end = reds.get(idx + 1).map_or(last_time, |next| next.time);
+            let duration = if point.time > last_time { 0.0 } else { (end - start).max(0.0) };
+
+            // Beat lengths that only differ by rounding are the same BPM.
+            let key = (point.beat_length * 1000.0).round() as i64;
+
+            if !durations.contains_key(&key) {
+                order.push(key);
+            }
+
+            *durations.entry(key).or_insert(0.0) += duration;
+        }
+
+        let mut dominant: Option<(i64, f64)> = None;
+
+        for key in order {
+            let duration = durations[&key];
+
+            match dominant {
+                Some((_, longest)) if longest >= duration => {},
+                _ => dominant = Some((key, duration)),
+            }
+        }
+
+        dominant.map(|(key, _)| 60_000.0 / (key as f64 / 1000.0))
+    }
+}
+
+/// A time snapped to a beat divisor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snap {
+    /// The snapped time in milliseconds.
+    pub time: f64,
+    /// The divisor, such as `4` for 1/4 beats.
+    pub divisor: u32,
+    /// The time before snapping.
+    pub unsnapped_time: f64,
+}
+
+impl Snap {
+    /// How far the time before snapping is after the snap, in milliseconds.
+    pub fn offset(&self) -> f64 {
+        self.unsnapped_time - self.time
+    }
+}
+
+/// An object that is off its nearest snap, as found by
+/// [`Timing::unsnapped`].
+///
+/// [`Timing::unsnapped`]: struct.Timing.html#method.unsnapped
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unsnapped {
+    /// The index of the object.
+    pub index: usize,
+    pub snap: Snap,
+}
@@ -0,0 +1,331 @@
+//! Watching users for new recent plays.
+//!
+//! A [`RecentWatcher`] polls `get_user_recent` for a list of users and yields
+//! only the plays that it has not seen before, deduplicated by their date,
+//! beatmap and score. What has been seen is kept in a serializable
+//! [`Cursor`], so a watcher can be restored after a restart without
+//! replaying old plays.
+//!
+//! The first poll of a user without a cursor records their existing plays
+//! without yielding them.
+//!
+//! Plays can be polled with any client through [`RecentWatcher::poll`], or
+//! watched on an interval with [`RecentWatcher::stream`] for hyper and
+//! [`RecentWatcher::iter`] for reqwest.
+//!
+//! # Examples
+//!
+//! Poll a user with a client of your own, and save the cursor:
+//!
+//! ```rust
+//! extern crate osu;
+//! extern crate serde_json;
+//!
+//! use osu::watch::RecentWatcher;
+//!
+//! # fn main() {
+//! let mut watcher = RecentWatcher::new(vec!["cookiezi".into()]);
+//!
+//! for play in watcher.poll(|_user| Ok(vec![])) {
+//!     println!("New play on {}", play.unwrap().beatmap_id);
+//! }
+//!
+//! let saved = serde_json::to_string(watcher.cursor()).unwrap();
+//! # let _ = saved;
+//! # }
+//! ```
+//!
+//! [`Cursor`]: struct.Cursor.html
+//! [`RecentWatcher`]: struct.RecentWatcher.html
+//! [`RecentWatcher::iter`]: struct.RecentWatcher.html#method.iter
+//! [`RecentWatcher::poll`]: struct.RecentWatcher.html#method.poll
+//! [`RecentWatcher::stream`]: struct.RecentWatcher.html#method.stream
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use ::{GetBeatmapUser, RecentPlay, Result};
+
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use futures::{Future, Stream, future, stream};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use hyper::client::{Client, Connect};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use hyper::Error as HyperError;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use std::cell::RefCell;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use std::rc::Rc;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use std::sync::Arc;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use tokio_core::reactor::{Handle, Interval, Timeout};
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use ::bridge::hyper::OsuHyperRequester;
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+use ::Error;
+
+#[cfg(feature = "reqwest")]
+use reqwest::Client as ReqwestClient;
+#[cfg(feature = "reqwest")]
+use std::collections::VecDeque;
+#[cfg(feature = "reqwest")]
+use std::thread;
+#[cfg(feature = "reqwest")]
+use ::bridge::reqwest::OsuReqwestRequester;
+
+#[cfg(any(feature = "reqwest", all(feature = "hyper", feature = "tokio-core")))]
+use std::time::{Duration, Instant};
+#[cfg(any(feature = "reqwest", all(feature = "hyper", feature = "tokio-core")))]
+use ::ratelimit::RateLimiter;
+
+/// What a watcher has seen of each user's plays.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Cursor {
+    /// The position for each user, by their ID or lowercase username
+    /// prefixed with `name:`.
+    pub users: BTreeMap<String, UserCursor>,
+}
+
+/// What a watcher has seen of a user's plays.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UserCursor {
+    /// The date of the latest play seen, or an empty string if no plays have
+    /// been seen.
+    pub date: String,
+    /// The beatmap IDs and scores of the plays seen at [`date`].
+    ///
+    /// [`date`]: #structfield.date
+    pub seen: Vec<(i64, i64)>,
+}
+
+/// Watches users for new recent plays.
+#[derive(Clone, Debug)]
+pub struct RecentWatcher {
+    users: Vec<GetBeatmapUser>,
+    cursor: Cursor,
+}
+
+impl RecentWatcher {
+    /// Creates a watcher for users that have not been watched before.
+    pub fn new(users: Vec<GetBeatmapUser>) -> RecentWatcher {
+        RecentWatcher::with_cursor(users, Cursor::default())
+    }
+
+    /// Creates a watcher that continues from a saved cursor.
+    pub fn with_cursor(users: Vec<GetBeatmapUser>, cursor: Cursor) -> RecentWatcher {
+        RecentWatcher { users, cursor }
+    }
+
+    /// The users being watched.
+    pub fn users(&self) -> &[GetBeatmapUser] {
+        &self.users
+    }
+
+    /// The cursor to save to continue watching later.
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    /// Filters the plays retrieved for a user down to the new ones, oldest
+    /// first, and records them as seen.
+    pub fn process(&mut self, user: &GetBeatmapUser, plays: Vec<RecentPlay>) -> Vec<RecentPlay> {
+        let cursor = match self.cursor.users.entry(user_key(user)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let cursor = entry.insert(UserCursor::default());
+                record(cursor, &plays);
+
+                return vec![];
+            },
+        };
+
+        let mut new = plays.into_iter().filter(|play| {
+            play.date > cursor.date
+                || (play.date == cursor.date && !cursor.seen.contains(&(play.beatmap_id, play.score)))
+        }).collect::<Vec<_>>();
+
+        new.sort_by(|a, b| a.date.cmp(&b.date));
+        new.dedup_by(|a, b| a.date == b.date && a.beatmap_id == b.beatmap_id && a.score == b.score);
+        record(cursor, &new);
+
+        new
+    }
+
+    /// Polls each user once, retrieving their plays with the given function,
+    /// such as a call to `get_user_recent`.
+    ///
+    /// Returns the new plays of each user in turn, with an error in place
+    /// of the plays of any user that could not be retrieved.
+    pub fn poll<F>(&mut self, mut fetch: F) -> Vec<Result<RecentPlay>>
+        where F: FnMut(&GetBeatmapUser) -> Result<Vec<RecentPlay>> {
+        let mut results = vec![];
+
+        for user in self.users.clone() {
+            match fetch(&user) {
+                Ok(plays) => results.extend(self.process(&user, plays).into_iter().map(Ok)),
+                Err(why) => results.push(Err(why)),
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(all(feature = "hyper", feature = "tokio-core"))]
+impl RecentWatcher {
+    /// Polls the users of a watcher on an interval, starting immediately,
+    /// and streams their new plays.
+    ///
+    /// Requests are made one at a time, each waiting for the rate limiter.
+    /// Failed requests are yielded as errors, and the stream continues to be
+    /// polled afterwards. The watcher is shared so that its cursor can be
+    /// saved while it is streamed.
+    ///
+    /// Requires the `hyper-support` feature.
+    pub fn stream<B, C>(
+        watcher: &Rc<RefCell<RecentWatcher>>,
+        client: Client<C, B>,
+        key: String,
+        interval: Duration,
+        limiter: Arc<RateLimiter>,
+        handle: &Handle,
+    ) -> Box<Stream<Item = RecentPlay, Error = Error>>
+        where B: Stream<Error = HyperError> + 'static,
+              B::Item: AsRef<[u8]>,
+              C: Connect {
+        let ticks = match Interval::new_at(Instant::now(), interval, handle) {
+            Ok(ticks) => ticks,
+            Err(why) => return Box::new(stream::once(Err(Error::Io(why)))),
+        };
+        let handle = handle.clone();
+        let users = watcher.clone();
+        let watcher = watcher.clone();
+
+        Box::new(ticks
+            .from_err::<Error>()
+            .map(move |_| stream::iter_ok::<_, Error>(users.borrow().users.clone()))
+            .flatten()
+            .and_then(move |user| {
+                let (client, key) = (client.clone(), key.clone());
+
+                future::result(Timeout::new(limiter.reserve(), &handle))
+                    .flatten()
+                    .from_err()
+                    .and_then(move |_| {
+                        client.get_user_recent(key, user.clone(), |req| req)
+                            .map(move |plays| (user, plays))
+                    })
+            })
+            .map(move |(user, plays)| {
+                stream::iter_ok::<_, Error>(watcher.borrow_mut().process(&user, plays))
+            })
+            .flatten())
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl RecentWatcher {
+    /// Polls the users of the watcher on an interval, starting immediately,
+    /// and iterates over their new plays.
+    ///
+    /// Requests are made one at a time, each waiting for the rate limiter,
+    /// and the thread sleeps between polls. Failed requests are yielded as
+    /// errors. The iterator never ends unless there are no users.
+    ///
+    /// Requires the `reqwest-support` feature.
+    pub fn iter<'a>(
+        self,
+        client: &'a ReqwestClient,
+        key: &'a str,
+        interval: Duration,
+        limiter: &'a RateLimiter,
+    ) -> RecentPlays<'a> {
+        RecentPlays {
+            buffer: VecDeque::new(),
+            client,
+            interval,
+            key,
+            last_poll: None,
+            limiter,
+            watcher: self,
+        }
+    }
+}
+
+/// An iterator over the new plays of watched users, created by
+/// [`RecentWatcher::iter`].
+///
+/// [`RecentWatcher::iter`]: struct.RecentWatcher.html#method.iter
+#[cfg(feature = "reqwest")]
+pub struct RecentPlays<'a> {
+    buffer: VecDeque<Result<RecentPlay>>,
+    client: &'a ReqwestClient,
+    interval: Duration,
+    key: &'a str,
+    last_poll: Option<Instant>,
+    limiter: &'a RateLimiter,
+    watcher: RecentWatcher,
+}
+
+#[cfg(feature = "reqwest")]
+impl<'a> RecentPlays<'a> {
+    /// The watcher, whose cursor can be saved between plays.
+    pub fn watcher(&self) -> &RecentWatcher {
+        &self.watcher
+    }
+}
+
+#[cfg(feature = "reqwest")]
+impl<'a> Iterator for RecentPlays<'a> {
+    type Item = Result<RecentPlay>;
+
+    fn next(&mut self) -> Option<Result<RecentPlay>> {
+        if self.watcher.users.is_empty() {
+            return None;
+        }
+
+        while self.buffer.is_empty() {
+            if let Some(last_poll) = self.last_poll {
+                let elapsed = last_poll.elapsed();
+
+                if elapsed < self.interval {
+                    thread::sleep(self.interval - elapsed);
+                }
+            }
+
+            self.last_poll = Some(Instant::now());
+
+            let (client, key, limiter) = (self.client, self.key, self.limiter);
+            let results = self.watcher.poll(|user| {
+                limiter.wait();
+
+                client.get_user_recent(key, user.clone(), |req| req)
+            });
+
+            self.buffer.extend(results);
+        }
+
+        self.buffer.pop_front()
+    }
+}
+
+/// Records plays, sorted oldest first, as seen.
+fn record(cursor: &mut UserCursor, plays: &[RecentPlay]) {
+    for play in plays {
+        if play.date > cursor.date {
+            cursor.date = play.date.clone();
+            cursor.seen.clear();
+        }
+
+        if play.date == cursor.date {
+            cursor.seen.push((play.beatmap_id, play.score));
+        }
+    }
+}
+
+fn user_key(user: &GetBeatmapUser) -> String {
+    match *user {
+        GetBeatmapUser::UserId(id) => id.to_string(),
+        GetBeatmapUser::Username(ref name) => format!("name:{}", name.to_lowercase()),
+    }
+}
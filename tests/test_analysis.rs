@@ -0,0 +1,133 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::analysis::*;
+use osu::{Game, MatchScore, ScoringType, TeamType};
+use std::collections::HashMap;
+use std::fs::File;
+
+fn games() -> Vec<Game> {
+    let f = File::open("./tests/resources/games_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+fn score(user_id: i64, slot: i64, team: i64, score: i64, count_100: i64, pass: bool) -> MatchScore {
+    serde_json::from_value(serde_json::json!({
+        "slot": slot.to_string(),
+        "team": team.to_string(),
+        "user_id": user_id.to_string(),
+        "score": score.to_string(),
+        "maxcombo": "500",
+        "rank": "0",
+        "count50": "0",
+        "count100": count_100.to_string(),
+        "count300": (300 - count_100).to_string(),
+        "countmiss": "0",
+        "countgeki": "0",
+        "countkatu": "0",
+        "perfect": "0",
+        "pass": if pass { "1" } else { "0" },
+    })).unwrap()
+}
+
+/// A team match of four games: Blue wins the first and third on score, and
+/// Red the second. The last game was aborted.
+fn team_match() -> Vec<(Game, Vec<MatchScore>)> {
+    let template = games().remove(0);
+
+    (0..4).map(|idx| {
+        let mut game = template.clone();
+        game.game_id = 100 + idx;
+
+        let scores = match idx {
+            0 => vec![score(1, 0, 1, 900_000, 0, true), score(2, 1, 1, 100_000, 10, true),
+                      score(3, 2, 2, 600_000, 0, true), score(4, 3, 2, 300_000, 0, true)],
+            1 => vec![score(1, 0, 1, 400_000, 0, true), score(2, 1, 1, 100_000, 0, false),
+                      score(3, 2, 2, 600_000, 0, true), score(4, 3, 2, 300_000, 0, true)],
+            2 => vec![score(1, 0, 1, 800_000, 0, true), score(2, 1, 1, 200_000, 0, true),
+                      score(3, 2, 2, 500_000, 0, true), score(4, 3, 2, 300_000, 0, true)],
+            _ => {
+                game.end_time = String::new();
+
+                vec![]
+            },
+        };
+
+        (game, scores)
+    }).collect()
+}
+
+#[test]
+fn test_team_vs() {
+    let analysis = MatchAnalyzer::default().analyze(&team_match());
+
+    let statuses = analysis.games.iter().map(|game| game.status).collect::<Vec<_>>();
+    assert_eq!(statuses, vec![GameStatus::Counted, GameStatus::Counted, GameStatus::Counted, GameStatus::Aborted]);
+
+    let winners = analysis.games.iter().map(|game| game.winner.clone()).collect::<Vec<_>>();
+    assert_eq!(winners, vec![Some("Blue".to_owned()), Some("Red".to_owned()), Some("Blue".to_owned()), None]);
+
+    let first = &analysis.games[0];
+    assert_eq!(first.teams[0].team, "Blue");
+    assert_eq!(first.teams[0].players, vec![1, 2]);
+    assert_eq!(first.teams[0].score, 1_000_000);
+    assert_eq!(first.teams[0].combo, 1000);
+    assert_eq!(first.series["Blue"], 1);
+    assert_eq!(first.series["Red"], 0);
+
+    assert_eq!(analysis.series["Blue"], 2);
+    assert_eq!(analysis.series["Red"], 1);
+    assert_eq!(analysis.leader(), Some("Blue"));
+
+    let player = &analysis.players[0];
+    assert_eq!(player.user_id, 1);
+    assert_eq!(player.team, "Blue");
+    assert_eq!(player.games, 3);
+    assert_eq!(player.average_score, 700_000.0);
+    assert_eq!(player.average_accuracy, 1.0);
+}
+
+#[test]
+fn test_rules() {
+    let rules = Rules {
+        warmups: 1,
+        ignored: vec![102],
+        ignore_aborted: true,
+        count_failed: false,
+    };
+    let analysis = MatchAnalyzer::new(HashMap::new(), rules).analyze(&team_match());
+
+    let statuses = analysis.games.iter().map(|game| game.status).collect::<Vec<_>>();
+    assert_eq!(statuses, vec![GameStatus::Warmup, GameStatus::Counted, GameStatus::Ignored, GameStatus::Aborted]);
+    assert!(analysis.games[0].winner.is_none());
+
+    // The failed score of player 2 is not counted.
+    assert_eq!(analysis.games[1].teams[1].players, vec![1]);
+    assert_eq!(analysis.series["Red"], 1);
+    assert_eq!(analysis.series["Blue"], 0);
+    assert_eq!(analysis.players.len(), 3);
+}
+
+#[test]
+fn test_roster_and_accuracy() {
+    let mut games = team_match();
+    games.truncate(1);
+    games[0].0.scoring_type = ScoringType::Accuracy;
+    games[0].0.team_type = TeamType::HeadToHead;
+
+    let mut roster = HashMap::new();
+    roster.insert(1, "South Korea".to_owned());
+    roster.insert(2, "South Korea".to_owned());
+
+    let analysis = MatchAnalyzer::new(roster, Rules::default()).analyze(&games);
+    let teams = &analysis.games[0].teams;
+    let names = teams.iter().map(|team| team.team.as_str()).collect::<Vec<_>>();
+
+    // Players 3 and 4 are tied on accuracy, but are each their own team.
+    assert_eq!(names[2], "South Korea");
+    assert!(teams[2].accuracy < 1.0);
+    assert_eq!(teams[2].players, vec![1, 2]);
+    assert!(analysis.games[0].winner.is_none());
+    assert!(analysis.leader().is_none());
+}
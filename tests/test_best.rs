@@ -0,0 +1,77 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::best::*;
+use osu::{GetBeatmapUser, Performance, PlayMode};
+use std::fs::File;
+
+fn plays() -> Vec<Performance> {
+    let f = File::open("./tests/resources/user_best_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+#[test]
+fn test_diff() {
+    let current = plays();
+
+    // Before, the play at index 2 was a worse play on the same beatmap.
+    let mut previous = current.clone();
+    let mut old = previous.remove(2);
+    old.pp = 600.0;
+    old.score -= 1_000_000;
+    old.date = "2017-01-01 00:00:00".to_owned();
+    previous.push(old);
+
+    let diff = BestDiff::new(&previous, &current);
+    assert_eq!(diff.new.len(), 1);
+    assert_eq!(diff.new[0].index, 2);
+    assert_eq!(diff.new[0].performance.beatmap_id, 795627);
+    assert_eq!(diff.new[0].replaced.as_ref().unwrap().pp, 600.0);
+    assert_eq!(diff.displaced.len(), 1);
+    assert_eq!(diff.displaced[0].score, current[2].score - 1_000_000);
+    // Each play after the new one moves up a place, and the 600pp play is
+    // replaced: 0.95^2 (775.699 - 750.437) + ... + 0.95^9 (699.839 - 600).
+    assert!((diff.pp_gain - 126.191_125_004_195_36).abs() < 1e-9, "{}", diff.pp_gain);
+    assert!(diff.pp_gain > 0.0);
+
+    assert!(BestDiff::new(&current, &current).is_empty());
+}
+
+#[test]
+fn test_pp_gain() {
+    let plays = plays();
+    let with_pp = |idx: usize, pp: f64| {
+        let mut play = plays[idx].clone();
+        play.pp = pp;
+        play
+    };
+    let previous = vec![with_pp(0, 300.0), with_pp(2, 200.0), with_pp(3, 100.0)];
+    let current = vec![with_pp(0, 300.0), with_pp(1, 250.0), with_pp(2, 200.0)];
+
+    // 300 + 0.95 * 250 + 0.95^2 * 200 = 718, and 300 + 0.95 * 200 +
+    // 0.95^2 * 100 = 580.25.
+    assert!((weighted_pp(&current) - 718.0).abs() < 1e-9);
+    assert!((BestDiff::new(&previous, &current).pp_gain - 137.75).abs() < 1e-9);
+}
+
+#[test]
+fn test_tracker() {
+    let user = GetBeatmapUser::UserId(124493);
+    let plays = plays();
+    let mut tracker = BestTracker::new(MemoryStorage::default());
+
+    // The first snapshot is only saved.
+    let diff = tracker.update(&user, PlayMode::Standard, plays[1..].to_vec()).unwrap();
+    assert!(diff.is_empty());
+
+    let diff = tracker.update(&user, PlayMode::Standard, plays.clone()).unwrap();
+    assert_eq!(diff.new.len(), 1);
+    assert_eq!(diff.new[0].index, 0);
+    assert!(diff.new[0].replaced.is_none());
+    assert!(diff.displaced.is_empty());
+
+    // Snapshots are kept per mode.
+    assert!(tracker.update(&user, PlayMode::Taiko, vec![]).unwrap().is_empty());
+    assert_eq!(tracker.storage().snapshots.len(), 2);
+}
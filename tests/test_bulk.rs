@@ -0,0 +1,77 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::bulk::*;
+use osu::{Error, GetBeatmapUser, User};
+use std::fs::File;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+fn user(id: u64) -> User {
+    let f = File::open("./tests/resources/user_01.json").unwrap();
+    let mut user = serde_json::from_reader::<File, User>(f).unwrap();
+    user.id = id as i64;
+    user
+}
+
+#[test]
+fn test_lookup_users() {
+    let users = (1..21).map(GetBeatmapUser::UserId).collect::<Vec<_>>();
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(Mutex::new(0));
+    let (fetch_active, fetch_peak) = (active.clone(), peak.clone());
+
+    let results = lookup_users(users.clone(), 4, move |requested| {
+        let now = fetch_active.fetch_add(1, Ordering::SeqCst) + 1;
+        {
+            let mut peak = fetch_peak.lock().unwrap();
+            *peak = (*peak).max(now);
+        }
+        thread::sleep(Duration::from_millis(5));
+        fetch_active.fetch_sub(1, Ordering::SeqCst);
+
+        match *requested {
+            GetBeatmapUser::UserId(id) if id % 7 == 0 => Err(Error::Parse("unavailable".to_owned())),
+            GetBeatmapUser::UserId(id) if id % 5 == 0 => Ok(vec![]),
+            GetBeatmapUser::UserId(id) => Ok(vec![user(id)]),
+            GetBeatmapUser::Username(_) => unreachable!(),
+        }
+    });
+
+    assert!(*peak.lock().unwrap() <= 4);
+    assert_eq!(results.iter().map(|(user, _)| user.clone()).collect::<Vec<_>>(), users);
+
+    for (user, lookup) in results {
+        let id = match user {
+            GetBeatmapUser::UserId(id) => id,
+            GetBeatmapUser::Username(_) => unreachable!(),
+        };
+
+        match lookup {
+            UserLookup::Failed(_) => assert_eq!(id % 7, 0),
+            UserLookup::NotFound => assert_eq!(id % 5, 0),
+            UserLookup::Found(found) => assert_eq!(found.id as u64, id),
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "a user lookup panicked")]
+fn test_lookup_users_panic() {
+    lookup_users(vec![GetBeatmapUser::UserId(1)], 1, |_| panic!("lookup failed"));
+}
+
+#[test]
+fn test_from_result() {
+    assert!(UserLookup::from_result(Ok(vec![])).is_not_found());
+    assert_eq!(UserLookup::from_result(Ok(vec![user(2)])).found().unwrap().id, 2);
+
+    match UserLookup::from_result(Err(Error::Parse("unavailable".to_owned()))) {
+        UserLookup::Failed(Error::Parse(_)) => {},
+        other => panic!("unexpected {:?}", other),
+    }
+
+    assert!(lookup_users(vec![], 4, |_| Ok(vec![])).is_empty());
+}
@@ -0,0 +1,126 @@
+#![cfg(feature = "osz-support")]
+
+extern crate osu;
+
+use osu::convert::{self, CatchKind, LegacyRandom, TaikoKind};
+use osu::osz::Difficulty;
+use osu::Mods;
+use std::fs;
+
+fn difficulty(name: &str) -> Difficulty {
+    let bytes = fs::read(format!("./tests/resources/{}", name)).unwrap();
+
+    Difficulty::parse(name, &bytes).unwrap()
+}
+
+fn columns(notes: &[convert::ManiaObject]) -> String {
+    notes.iter().map(|note| note.column.to_string()).collect()
+}
+
+#[test]
+fn test_legacy_random() {
+    let mut random = LegacyRandom::new(1337);
+
+    assert_eq!(random.next_u32(), 274_941_776);
+    assert_eq!(random.next_u32(), 2_661_595_948);
+    assert_eq!(random.next_u32(), 3_085_529_888);
+    assert_eq!(LegacyRandom::new(1337).next_in(0, 7), 0);
+}
+
+#[test]
+fn test_taiko() {
+    let objects = convert::taiko(&difficulty("beatmap_01.osu"));
+
+    assert_eq!(objects.len(), 50);
+    assert_eq!(objects.iter().filter(|object| object.kind == TaikoKind::Centre).count(), 47);
+    assert_eq!(objects.iter().filter(|object| object.kind == TaikoKind::Rim).count(), 1);
+    assert_eq!(objects.iter().filter(|object| object.is_hit()).count(), 48);
+    assert!(objects.iter().all(|object| !object.strong));
+    assert!(objects.windows(2).all(|pair| pair[0].time < pair[1].time));
+
+    let swells = objects.iter().filter_map(|object| match object.kind {
+        TaikoKind::Swell { required_hits, .. } => Some(required_hits),
+        _ => None,
+    }).collect::<Vec<_>>();
+    assert_eq!(swells, vec![17]);
+}
+
+#[test]
+fn test_catch() {
+    let objects = convert::catch(&difficulty("beatmap_01.osu"), Mods::empty());
+    let count = |kind| objects.iter().filter(|object| object.kind == kind).count();
+
+    assert_eq!(objects.len(), 114);
+    assert_eq!(count(CatchKind::Fruit), 51);
+    assert_eq!(count(CatchKind::Droplet), 3);
+    assert_eq!(count(CatchKind::TinyDroplet), 27);
+    assert_eq!(count(CatchKind::Banana), 33);
+
+    assert!(objects.iter().all(|object| object.x >= 0.0 && object.x <= 512.0));
+
+    let x = objects.iter().map(|object| object.x).sum::<f64>();
+    assert!((x - 25_973.015_952_655_17).abs() < 1e-6, "{}", x);
+}
+
+#[test]
+fn test_catch_hard_rock() {
+    let normal = difficulty("beatmap_01.osu");
+    let objects = convert::catch(&normal, Mods::HARD_ROCK);
+    let x = objects.iter().filter(|object| object.adds_combo()).map(|object| object.x).sum::<f64>();
+
+    assert_eq!(objects.len(), 114);
+    assert!((x - 12_559.353_771_433_547).abs() < 1e-6, "{}", x);
+
+    // Juice streams are not moved.
+    let streams = |mods| convert::catch(&normal, mods).into_iter()
+        .filter(|object| object.kind == CatchKind::Droplet)
+        .collect::<Vec<_>>();
+    assert_eq!(streams(Mods::HARD_ROCK), streams(Mods::empty()));
+}
+
+#[test]
+fn test_mania_keys() {
+    let normal = difficulty("beatmap_01.osu");
+
+    assert_eq!(convert::mania_keys(&normal, Mods::empty()), 7);
+    assert_eq!(convert::mania_keys(&normal, Mods::KEY4 | Mods::HIDDEN), 4);
+    assert_eq!(convert::mania_keys(&difficulty("beatmap_02.osu"), Mods::KEY4), 7);
+}
+
+#[test]
+fn test_mania_convert() {
+    let notes = convert::mania(&difficulty("beatmap_01.osu"), Mods::empty());
+
+    assert_eq!(notes.len(), 47);
+    assert_eq!(notes.iter().filter(|note| note.end_time.is_some()).count(), 7);
+    assert_eq!(columns(&notes), "34553121015121123344532223344442424242424242423");
+    assert!(notes.windows(2).all(|pair| pair[0].time <= pair[1].time));
+}
+
+#[test]
+fn test_mania_convert_key_mods() {
+    let normal = difficulty("beatmap_01.osu");
+    let expected = [
+        (Mods::KEY1, "0000000000000000000000000000000000000000000000"),
+        (Mods::KEY2, "1111010101000000111100000111110101010101010101"),
+        (Mods::KEY3, "1122101002010011111210111111211111111111111110"),
+        (Mods::KEY4, "223320103212110111222201111222221212121212121210"),
+        (Mods::KEY5, "23332021013110112223321122223322222222222222221"),
+        (Mods::KEY6, "33443021014121122333421222333432323232323232323"),
+        (Mods::KEY8, "45664232126232234455643334455553535353535353534"),
+        (Mods::KEY9, "45664132026231233455642334455653535353535353534"),
+    ];
+
+    for &(mods, sequence) in &expected {
+        assert_eq!(columns(&convert::mania(&normal, mods)), sequence, "{:?}", mods);
+    }
+}
+
+#[test]
+fn test_mania_native() {
+    let notes = convert::mania(&difficulty("beatmap_02.osu"), Mods::empty());
+
+    assert_eq!(notes.len(), 30);
+    assert_eq!(notes.iter().filter(|note| note.end_time.is_some()).count(), 4);
+    assert!(notes.iter().all(|note| note.column < 7));
+}
@@ -0,0 +1,145 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::db::*;
+use osu::{Approval, Beatmap, Mods, Performance, PlayMode};
+use std::fs::File;
+
+fn osu_db(name: &str) -> OsuDb {
+    let f = File::open(format!("./tests/resources/{}", name)).unwrap();
+
+    OsuDb::read(f).unwrap()
+}
+
+#[test]
+fn test_osu_db() {
+    let db = osu_db("osu_01.db");
+
+    assert_eq!(db.version, 20250108);
+    assert_eq!(db.player_name, "player");
+    assert!(db.permissions.contains(Permissions::NORMAL | Permissions::SUPPORTER));
+    assert_eq!(db.beatmaps.len(), 2);
+
+    let entry = &db.beatmaps[0];
+    assert_eq!(entry.artist, "Someone");
+    assert_eq!(entry.version, "Hard");
+    assert_eq!(entry.beatmap_id, 123);
+    assert_eq!(entry.beatmapset_id, 45);
+    assert_eq!(entry.approved, Some(Approval::Ranked));
+    assert_eq!(entry.last_update, "2018-01-20 12:30:15");
+    assert_eq!(entry.diff_approach, 8.5);
+    assert_eq!(entry.stars(PlayMode::Standard, Mods::DOUBLE_TIME), Some(6.25));
+    assert_eq!(entry.stars(PlayMode::Taiko, Mods::empty()), None);
+    assert_eq!(entry.timing_points.len(), 2);
+    assert!(!entry.timing_points[1].uninherited);
+    assert_eq!(entry.grade_standard, Some("S".to_owned()));
+    assert_eq!(entry.last_played, Some("2019-05-01 00:00:00".to_owned()));
+
+    let unsubmitted = &db.beatmaps[1];
+    assert_eq!(unsubmitted.approved, None);
+    assert_eq!(unsubmitted.grade_standard, None);
+    assert_eq!(unsubmitted.last_played, None);
+}
+
+#[test]
+fn test_osu_db_versions() {
+    // Entries are prefixed by their size and ratings stored as doubles.
+    let db = osu_db("osu_02.db");
+    let entry = &db.beatmaps[0];
+    assert_eq!(entry.approved, Some(Approval::Loved));
+    assert_eq!(entry.stars(PlayMode::Standard, Mods::empty()), Some(4.5));
+    assert_eq!(entry.grade_standard, Some("XH".to_owned()));
+
+    // Difficulty settings are stored as bytes, without star ratings.
+    let db = osu_db("osu_03.db");
+    let entry = &db.beatmaps[0];
+    assert_eq!(entry.diff_approach, 8.0);
+    assert!(entry.star_ratings.is_empty());
+    assert_eq!(entry.folder_name, "123 Someone - Song");
+}
+
+#[test]
+fn test_osu_db_truncated() {
+    let bytes = include_bytes!("resources/osu_01.db");
+
+    assert!(OsuDb::read(&bytes[..bytes.len() - 10]).is_err());
+}
+
+#[test]
+fn test_collection_db() {
+    let f = File::open("./tests/resources/collection_01.db").unwrap();
+    let db = CollectionDb::read(f).unwrap();
+
+    assert_eq!(db.version, 20150203);
+    assert_eq!(db.collections.len(), 2);
+    assert_eq!(db.collections[0].name, "Mappool NM");
+    assert_eq!(db.collections[0].beatmap_md5s.len(), 2);
+    assert!(db.collections[1].beatmap_md5s.is_empty());
+
+    let mut bytes = vec![];
+    db.write(&mut bytes).unwrap();
+
+    assert_eq!(&bytes[..], &include_bytes!("resources/collection_01.db")[..]);
+}
+
+#[test]
+fn test_collection_from_api() {
+    let f = File::open("./tests/resources/beatmaps_01.json").unwrap();
+    let beatmaps = serde_json::from_reader::<File, Vec<Beatmap>>(f).unwrap();
+    let f = File::open("./tests/resources/performances_01.json").unwrap();
+    let mut performances = serde_json::from_reader::<File, Vec<Performance>>(f).unwrap();
+    performances[0].beatmap_id = beatmaps[1].beatmap_id;
+    performances[1].beatmap_id = beatmaps[1].beatmap_id;
+
+    let mut collection = Collection::from_performances("Top plays", &performances, &beatmaps);
+    assert_eq!(collection.beatmap_md5s, vec![beatmaps[1].file_md5.clone()]);
+    assert!(collection.contains(&beatmaps[1]));
+    assert_eq!(Collection::missing(&performances, &beatmaps).len(), 8);
+
+    collection.push(beatmaps[1].file_md5.to_uppercase());
+    assert_eq!(collection.beatmap_md5s.len(), 1);
+
+    let db = CollectionDb::new(vec![
+        collection,
+        Collection::from_beatmaps("All", &beatmaps[..3]),
+    ]);
+    let mut bytes = vec![];
+    db.write(&mut bytes).unwrap();
+
+    let read = CollectionDb::read(&bytes[..]).unwrap();
+    assert_eq!(read.collections[1].name, "All");
+    assert_eq!(read.collections[1].beatmap_md5s, db.collections[1].beatmap_md5s);
+}
+
+#[test]
+fn test_scores_db() {
+    let f = File::open("./tests/resources/scores_01.db").unwrap();
+    let db = ScoresDb::read(f).unwrap();
+
+    assert_eq!(db.version, 20250108);
+    assert_eq!(db.beatmaps.len(), 2);
+
+    let scores = db.scores("D41D8CD98F00B204E9800998ECF8427E");
+    assert_eq!(scores.len(), 2);
+    assert_eq!(scores[0].mode, PlayMode::Standard);
+    assert_eq!(scores[0].count_300, 500);
+    assert_eq!(scores[0].count_miss, 2);
+    assert_eq!(scores[0].score, 12345678);
+    assert_eq!(scores[0].max_combo, 700);
+    assert_eq!(scores[0].enabled_mods, Mods::HIDDEN | Mods::DOUBLE_TIME);
+    assert_eq!(scores[0].date, "2020-03-04 05:06:07");
+    assert_eq!(scores[0].online_score_id, 3000000000);
+    assert!(scores[0].replay.is_none());
+
+    // Older scores have 32-bit score IDs.
+    assert_eq!(scores[1].online_score_id, 123456);
+    assert_eq!(scores[1].replay, Some(vec![0x5d, 0, 0]));
+
+    let mania = &db.scores("0cc175b9c0f1b6a831c399e269772661")[0];
+    assert_eq!(mania.mode, PlayMode::Mania);
+    assert!(mania.perfect);
+    assert_eq!(mania.enabled_mods, Mods::KEY4);
+    assert_eq!(mania.target_practice_accuracy, Some(0.95));
+
+    assert!(db.scores("missing").is_empty());
+}
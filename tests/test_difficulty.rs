@@ -0,0 +1,124 @@
+#![cfg(feature = "osz-support")]
+
+extern crate osu;
+
+use osu::difficulty;
+use osu::osz::Difficulty;
+use osu::{Mods, PlayMode};
+use std::fs;
+
+/// The fixture has the IDs of beatmap 191904 but only 46 of its own objects,
+/// so the API's star rating and max combo for that beatmap do not apply to
+/// it. The reference values below guard the calculators against unintended
+/// changes.
+fn normal() -> Difficulty {
+    let bytes = fs::read("./tests/resources/beatmap_01.osu").unwrap();
+
+    Difficulty::parse("beatmap_01.osu", &bytes).unwrap()
+}
+
+fn assert_reference(value: f64, reference: f64) {
+    assert!((value - reference).abs() < 1e-6, "{} != {}", value, reference);
+}
+
+#[test]
+fn test_standard_counts() {
+    let attrs = difficulty::standard(&normal(), Mods::empty());
+
+    assert_eq!(attrs.circle_count, 40);
+    assert_eq!(attrs.slider_count, 5);
+    assert_eq!(attrs.spinner_count, 1);
+    assert_eq!(attrs.max_combo, 55);
+    assert_eq!(attrs.approach_rate, 5.0);
+    assert_eq!(attrs.overall_difficulty, 8.0);
+}
+
+#[test]
+fn test_standard_reference() {
+    let normal = normal();
+
+    let attrs = difficulty::standard(&normal, Mods::empty());
+    assert_reference(attrs.stars, 1.526588160643465);
+    assert_reference(attrs.aim_strain, 0.760195053125889);
+    assert_reference(attrs.speed_strain, 0.7643270893870138);
+
+    let attrs = difficulty::standard(&normal, Mods::HARD_ROCK);
+    assert_reference(attrs.stars, 1.5704438618260206);
+    assert_reference(attrs.aim_strain, 0.7877649354386873);
+    assert_reference(attrs.speed_strain, 0.7775929173359792);
+
+    // Hidden does not change the star rating.
+    let attrs = difficulty::standard(&normal, Mods::HIDDEN | Mods::DOUBLE_TIME);
+    assert_reference(attrs.stars, 2.03226289133735);
+    assert_reference(attrs.aim_strain, 0.9648188772020097);
+    assert_reference(attrs.speed_strain, 1.0332356351575633);
+}
+
+#[test]
+fn test_taiko_reference() {
+    let normal = normal();
+
+    let attrs = difficulty::taiko(&normal, Mods::empty());
+    assert_reference(attrs.stars, 1.3049623252597866);
+    assert_eq!(attrs.max_combo, 48);
+    assert_eq!(attrs.overall_difficulty, 8.0);
+
+    // HardRock does not change taiko's star rating.
+    assert_reference(difficulty::taiko(&normal, Mods::HARD_ROCK).stars, 1.3049623252597866);
+    assert_reference(difficulty::taiko(&normal, Mods::DOUBLE_TIME).stars, 1.6336929124616666);
+    assert_reference(difficulty::taiko(&normal, Mods::EASY | Mods::HALF_TIME).stars, 1.0982743922832992);
+}
+
+#[test]
+fn test_catch_reference() {
+    let normal = normal();
+
+    let attrs = difficulty::catch(&normal, Mods::empty());
+    assert_reference(attrs.stars, 0.7993779665193039);
+    assert_eq!(attrs.max_combo, 54);
+    assert_eq!(attrs.approach_rate, 5.0);
+
+    assert_reference(difficulty::catch(&normal, Mods::HARD_ROCK).stars, 1.1248725017100505);
+    assert_reference(difficulty::catch(&normal, Mods::DOUBLE_TIME).stars, 1.0724403873060309);
+    assert_reference(difficulty::catch(&normal, Mods::EASY | Mods::HALF_TIME).stars, 0.6566146021737751);
+}
+
+#[test]
+fn test_mania_reference() {
+    let normal = normal();
+
+    let attrs = difficulty::mania(&normal, Mods::empty());
+    assert_reference(attrs.stars, 0.9940805179841999);
+    assert_eq!(attrs.note_count, 47);
+    assert_eq!(attrs.overall_difficulty, 8.0);
+
+    assert_reference(difficulty::mania(&normal, Mods::DOUBLE_TIME).stars, 1.2031756959293927);
+    assert_reference(difficulty::mania(&normal, Mods::EASY | Mods::HALF_TIME).stars, 0.8752405811794487);
+
+    let attrs = difficulty::mania(&normal, Mods::KEY4);
+    assert_reference(attrs.stars, 1.0484504103993721);
+    assert_eq!(attrs.note_count, 48);
+
+    let bytes = fs::read("./tests/resources/beatmap_02.osu").unwrap();
+    let mania = Difficulty::parse("beatmap_02.osu", &bytes).unwrap();
+    let attrs = difficulty::mania(&mania, Mods::empty());
+    assert_reference(attrs.stars, 1.1130291749647765);
+    assert_eq!(attrs.note_count, 30);
+}
+
+#[test]
+fn test_calculate_converts() {
+    let normal = normal();
+
+    for &mode in &[PlayMode::Standard, PlayMode::Taiko, PlayMode::CatchTheBeat, PlayMode::Mania] {
+        let attrs = difficulty::calculate(&normal, mode, Mods::empty()).unwrap();
+        assert_eq!(attrs.mode(), mode);
+    }
+
+    // Only standard beatmaps are converted.
+    let bytes = fs::read("./tests/resources/beatmap_02.osu").unwrap();
+    let mania = Difficulty::parse("beatmap_02.osu", &bytes).unwrap();
+    assert!(difficulty::calculate(&mania, PlayMode::Mania, Mods::empty()).is_some());
+    assert!(difficulty::calculate(&mania, PlayMode::Taiko, Mods::empty()).is_none());
+    assert!(difficulty::calculate(&mania, PlayMode::Standard, Mods::empty()).is_none());
+}
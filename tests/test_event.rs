@@ -0,0 +1,129 @@
+extern crate osu;
+
+use osu::event::*;
+use osu::{Approval, GetBeatmapUser, PlayMode, UserEvent};
+
+fn kind(html: &str) -> EventKind {
+    UserEvent {
+        beatmap_id: 0,
+        beatmapset_id: 0,
+        date: "2018-01-01 00:00:00".to_owned(),
+        display_html: html.to_owned(),
+        epic_factor: 1,
+    }.kind()
+}
+
+fn user(id: u64, username: &str) -> EventUser {
+    EventUser {
+        user: GetBeatmapUser::UserId(id),
+        username: username.to_owned(),
+    }
+}
+
+#[test]
+fn test_scores() {
+    let html = "<img src='/images/A_small.png'/> <b><a href='/u/124493'>name</a></b> achieved rank #37 on \
+                <a href='/b/123?m=3'>Artist - Title [Hard]</a> (osu!mania)";
+
+    assert_eq!(kind(html), EventKind::Rank {
+        user: user(124493, "name"),
+        rank: 37,
+        grade: Some("A".to_owned()),
+        beatmap_id: 123,
+        title: "Artist - Title [Hard]".to_owned(),
+        mode: PlayMode::Mania,
+    });
+
+    let html = "<img src='/images/XH_small.png'/> <b><a href='/u/2'>peppy</a></b> achieved <b>rank #1</b> on \
+                <a href='/b/75'>Kenji Ninuma - DISCO &amp;PRINCE [Normal]</a> (Taiko)";
+
+    match kind(html) {
+        EventKind::Rank { rank, grade, title, mode, .. } => {
+            assert_eq!(rank, 1);
+            assert_eq!(grade, Some("XH".to_owned()));
+            assert_eq!(title, "Kenji Ninuma - DISCO &PRINCE [Normal]");
+            assert_eq!(mode, PlayMode::Taiko);
+        },
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let html = "<b><a href='/u/2'>peppy</a></b> has lost first place on <a href='/b/75?m=0'>Song [Hard]</a> (osu!)";
+    match kind(html) {
+        EventKind::LostFirstPlace { beatmap_id, mode, .. } => {
+            assert_eq!(beatmap_id, 75);
+            assert_eq!(mode, PlayMode::Standard);
+        },
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_beatmapsets() {
+    let html = "<a href='/s/456'>Artist - Title</a> by <b><a href='/u/3'>mapper</a></b> has just been ranked!";
+    assert_eq!(kind(html), EventKind::BeatmapSetApproved {
+        user: user(3, "mapper"),
+        beatmapset_id: 456,
+        title: "Artist - Title".to_owned(),
+        approval: Approval::Ranked,
+    });
+
+    let html = "<a href='/s/456'>Artist - Title</a> by <b><a href='/u/3'>mapper</a></b> has just been qualified!";
+    match kind(html) {
+        EventKind::BeatmapSetApproved { approval, .. } => assert_eq!(approval, Approval::Qualified),
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let html = "<a href='/s/456'>Artist - Title</a> by <b><a href='/u/3'>mapper</a></b> has just been approved!";
+    match kind(html) {
+        EventKind::BeatmapSetApproved { approval, .. } => assert_eq!(approval, Approval::Approved),
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let html = "<b><a href='/u/3'>mapper</a></b> has submitted a new beatmap \"<a href='/s/456'>Artist - Title</a>\"";
+    match kind(html) {
+        EventKind::BeatmapSetSubmitted { beatmapset_id, .. } => assert_eq!(beatmapset_id, 456),
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let html = "<b><a href='/u/3'>mapper</a></b> has updated the beatmap \"<a href='/s/456'>Artist - Title</a>\"";
+    match kind(html) {
+        EventKind::BeatmapSetUpdated { title, .. } => assert_eq!(title, "Artist - Title"),
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_users() {
+    let html = "<b><a href='/u/4'>newbie</a></b> has become an osu!supporter!";
+    assert_eq!(kind(html), EventKind::Supporter {
+        user: user(4, "newbie"),
+        kind: SupporterKind::New,
+    });
+
+    let html = "<b><a href='/u/4'>newbie</a></b> has received the gift of osu!supporter!";
+    match kind(html) {
+        EventKind::Supporter { kind, .. } => assert_eq!(kind, SupporterKind::Gift),
+        other => panic!("unexpected event {:?}", other),
+    }
+
+    let html = "<b><a href='/u/4'>old name</a></b> has changed their username to new name!";
+    assert_eq!(kind(html), EventKind::UsernameChange {
+        user: user(4, "old name"),
+        username: "new name".to_owned(),
+    });
+
+    let html = "<b><a href='/u/4'>newbie</a></b> unlocked the \"<b>500 Combo</b>\" medal!";
+    let event = kind(html);
+    assert_eq!(event, EventKind::Medal {
+        user: user(4, "newbie"),
+        name: "500 Combo".to_owned(),
+    });
+    assert_eq!(event.user().unwrap().username, "newbie");
+}
+
+#[test]
+fn test_unknown() {
+    let html = "<b><a href='/u/4'>newbie</a></b> did something new";
+    assert_eq!(kind(html), EventKind::Unknown(html.to_owned()));
+    assert!(kind(html).user().is_none());
+}
@@ -0,0 +1,87 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::history::*;
+use osu::{Error, PlayMode, User};
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+fn user() -> User {
+    let f = File::open("./tests/resources/user_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+fn snapshot(user: User, days_ago: u64) -> Snapshot {
+    Snapshot {
+        user,
+        mode: PlayMode::Standard,
+        taken: SystemTime::now() - Duration::from_secs(days_ago * 24 * 60 * 60),
+    }
+}
+
+#[test]
+fn test_diff() {
+    let before = user();
+    let mut after = before.clone();
+    after.pp_raw += 12.5;
+    after.pp_rank = 3;
+    after.play_count += 40;
+    after.count_rank_s -= 1;
+    after.count_rank_ss += 1;
+
+    let diff = UserDiff::new(&snapshot(before, 7), &snapshot(after, 0));
+    assert!((diff.pp_raw - 12.5).abs() < 1e-6);
+    assert_eq!(diff.pp_rank, 2);
+    assert_eq!(diff.play_count, 40);
+    assert_eq!(diff.count_rank_s, -1);
+    assert_eq!(diff.count_rank_ss, 1);
+    assert_eq!(diff.ranked_score, 0);
+    assert_eq!(diff.accuracy, 0.0);
+    assert!(diff.elapsed >= Duration::from_secs(7 * 24 * 60 * 60 - 1));
+}
+
+#[test]
+fn test_history() {
+    let week_ago = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+    let mut storage = MemoryHistory::default();
+
+    assert!(History::new(storage.clone()).diff_since(124493, PlayMode::Standard, week_ago).unwrap().is_none());
+
+    // Pushed out of order, to be kept oldest first.
+    for &(days_ago, play_count) in &[(8, 100), (1, 200), (10, 0)] {
+        let mut user = user();
+        user.play_count = play_count;
+        storage.push(snapshot(user, days_ago)).unwrap();
+    }
+
+    let history = History::new(storage);
+
+    // The snapshot from 8 days ago is the last one at or before a week ago.
+    let diff = history.diff_since(124493, PlayMode::Standard, week_ago).unwrap().unwrap();
+    assert_eq!(diff.play_count, 100);
+
+    // Snapshots from before the first are compared from the first.
+    let long_ago = SystemTime::now() - Duration::from_secs(30 * 24 * 60 * 60);
+    let diff = history.diff_since(124493, PlayMode::Standard, long_ago).unwrap().unwrap();
+    assert_eq!(diff.play_count, 200);
+
+    let latest = history.latest(124493, PlayMode::Standard).unwrap().unwrap();
+    assert_eq!(latest.user.play_count, 200);
+}
+
+#[test]
+fn test_take() {
+    let mut history = History::new(MemoryHistory::default());
+
+    let taken = history.take(|mode| match mode {
+        PlayMode::Mania => Ok(vec![]),
+        _ => Ok(vec![user()]),
+    }).unwrap();
+    assert_eq!(taken.len(), 3);
+    assert_eq!(history.snapshots(124493, PlayMode::Taiko).unwrap().len(), 1);
+    assert!(history.snapshots(124493, PlayMode::Mania).unwrap().is_empty());
+
+    let failed = history.take(|_| Err(Error::Parse("unavailable".to_owned())));
+    assert!(failed.is_err());
+}
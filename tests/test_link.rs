@@ -0,0 +1,72 @@
+extern crate osu;
+
+use osu::builder::{GetBeatmapsRequest, GetUserRequest};
+use osu::link::Link;
+use osu::{GetBeatmapUser, PlayMode};
+
+fn parse(link: &str) -> Link {
+    link.parse().unwrap()
+}
+
+#[test]
+fn test_beatmaps() {
+    let beatmap = Link::Beatmap {
+        beatmap_id: 123,
+        beatmapset_id: None,
+        mode: None,
+    };
+
+    assert_eq!(parse("osu.ppy.sh/b/123"), beatmap);
+    assert_eq!(parse("https://osu.ppy.sh/beatmaps/123"), beatmap);
+    assert_eq!(parse("osu://b/123"), beatmap);
+    assert_eq!(parse("http://old.ppy.sh/b/123?m=1").mode(), Some(PlayMode::Taiko));
+
+    let link = parse("https://osu.ppy.sh/beatmapsets/456#osu/789");
+    assert_eq!(link.beatmap_id(), Some(789));
+    assert_eq!(link.beatmapset_id(), Some(456));
+    assert_eq!(link.mode(), Some(PlayMode::Standard));
+
+    assert_eq!(parse("/s/456"), Link::BeatmapSet {
+        beatmapset_id: 456,
+        mode: None,
+    });
+    assert_eq!(parse("osu.ppy.sh/beatmapsets/456#fruits").mode(), Some(PlayMode::CatchTheBeat));
+    assert_eq!(parse("osu://dl/456").beatmapset_id(), Some(456));
+}
+
+#[test]
+fn test_users_and_matches() {
+    assert_eq!(parse("osu.ppy.sh/u/cookiezi").user(), Some(GetBeatmapUser::Username("cookiezi".to_owned())));
+    assert_eq!(parse("osu.ppy.sh/u/some%20one").user(), Some(GetBeatmapUser::Username("some one".to_owned())));
+
+    let link = parse("https://osu.ppy.sh/users/124493/taiko");
+    assert_eq!(link.user(), Some(GetBeatmapUser::UserId(124493)));
+    assert_eq!(link.mode(), Some(PlayMode::Taiko));
+
+    assert_eq!(parse("https://osu.ppy.sh/community/matches/71641").match_id(), Some(71641));
+    assert_eq!(parse("osu.ppy.sh/mp/71641").match_id(), Some(71641));
+}
+
+#[test]
+fn test_invalid() {
+    assert!("https://example.com/b/123".parse::<Link>().is_err());
+    assert!("osu.ppy.sh/b/abc".parse::<Link>().is_err());
+    assert!("osu.ppy.sh/beatmapsets/456#catch/1".parse::<Link>().is_err());
+    assert!("osu.ppy.sh/home".parse::<Link>().is_err());
+}
+
+#[test]
+fn test_requests() {
+    let link = parse("https://osu.ppy.sh/beatmapsets/456#mania/789");
+    let request = GetBeatmapsRequest::default().link(&link);
+    assert_eq!(request.0["b"], "789");
+    assert_eq!(request.0["m"], GetBeatmapsRequest::default().mode(PlayMode::Mania).0["m"]);
+    assert!(!request.0.contains_key("s"));
+
+    let request = GetBeatmapsRequest::default().link(&parse("/s/456"));
+    assert_eq!(request.0["s"], "456");
+    assert!(!request.0.contains_key("m"));
+
+    let request = GetUserRequest::default().link(&parse("/users/124493/mania"));
+    assert_eq!(request.0["m"], GetUserRequest::default().mode(PlayMode::Mania).0["m"]);
+}
@@ -0,0 +1,140 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::mappool::*;
+use osu::{Beatmap, Error, Game, Mods};
+use std::fs::File;
+
+fn beatmaps() -> Vec<Beatmap> {
+    let f = File::open("./tests/resources/beatmaps_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+fn games() -> Vec<Game> {
+    let f = File::open("./tests/resources/games_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+fn slot(name: &str) -> Slot {
+    name.parse().unwrap()
+}
+
+#[test]
+fn test_slots() {
+    assert_eq!(slot("HD2"), Slot::new(Category::Hidden, 2));
+    assert_eq!(slot("dt3"), Slot::new(Category::DoubleTime, 3));
+    assert_eq!(slot("TB"), Slot::new(Category::TieBreaker, 0));
+    assert_eq!(slot("FM").to_string(), "FM");
+    assert_eq!(slot("nm1").to_string(), "NM1");
+
+    assert!("XX1".parse::<Slot>().is_err());
+    assert!("HDx".parse::<Slot>().is_err());
+    assert!("H".parse::<Slot>().is_err());
+
+    let mut slots = [slot("TB"), slot("HR1"), slot("NM2"), slot("FM1"), slot("NM1")];
+    slots.sort();
+    let names = slots.iter().map(|slot| slot.to_string()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["NM1", "NM2", "HR1", "FM1", "TB"]);
+}
+
+#[test]
+fn test_validate() {
+    let games = games();
+
+    // The first game was played with NoFail, and Hidden by one player.
+    let mut pool = Mappool::new();
+    pool.insert(slot("NM1"), 774965);
+    pool.insert(slot("HD1"), 129891);
+
+    assert_eq!(pool.slot(774965), Some(slot("NM1")));
+    assert_eq!(pool.validate(&games[0]), vec![Violation::PlayerMods {
+        user_id: 124493,
+        mods: Mods::HIDDEN,
+    }]);
+    assert_eq!(pool.validate_slot(slot("HD1"), &games[0]), vec![
+        Violation::WrongBeatmap { expected: 129891, found: 774965 },
+        Violation::PlayerMods { user_id: 2558286, mods: Mods::NONE },
+    ]);
+
+    // The second game was set up with HardRock.
+    assert_eq!(pool.validate(&games[1]), vec![Violation::GameMods(Mods::HARD_ROCK)]);
+
+    pool.insert(slot("FM1"), 774965);
+    assert!(pool.validate_slot(slot("FM1"), &games[0]).is_empty());
+
+    pool.free_mods = Mods::HARD_ROCK;
+    assert_eq!(pool.validate_slot(slot("FM1"), &games[0]).len(), 1);
+
+    pool.insert(slot("DT1"), 774965);
+    let mut game = games[0].clone();
+    game.mods = Mods::DOUBLE_TIME | Mods::NIGHTCORE;
+    game.scores.truncate(1);
+    game.scores[0].enabled_mods = Mods::NONE;
+    assert!(pool.validate_slot(slot("DT1"), &game).is_empty());
+
+    game.beatmap_id = 1;
+    assert_eq!(pool.validate(&game), vec![Violation::UnknownBeatmap(1)]);
+}
+
+#[test]
+fn test_fetch() {
+    let all = beatmaps();
+    let mut pool = Mappool::new();
+    pool.insert(slot("DT1"), 191904);
+    pool.insert(slot("HR1"), 466343);
+    pool.insert(slot("FM1"), 466343);
+
+    let mut requests = 0;
+    let mut fetch = |id| {
+        requests += 1;
+
+        Ok(all.iter().filter(|beatmap| beatmap.beatmap_id == id).cloned().collect())
+    };
+    pool.fetch(&mut fetch).unwrap();
+    pool.fetch(&mut fetch).unwrap();
+    assert_eq!(requests, 2);
+    assert!(pool.missing().is_empty());
+
+    let dt = pool.beatmap(slot("DT1")).unwrap();
+    assert_eq!(dt.mods, Mods::DOUBLE_TIME);
+    assert!((dt.bpm - 216.0525).abs() < 1e-9);
+    assert!((dt.approach_rate - 23.0 / 3.0).abs() < 1e-9);
+    assert_eq!(dt.hit_length, 55);
+    assert_eq!(dt.total_length, 98);
+
+    let hr = pool.beatmap(slot("HR1")).unwrap();
+    assert!((hr.approach_rate - 8.4).abs() < 1e-9);
+    assert!((hr.circle_size - 6.5).abs() < 1e-9);
+    assert!((hr.drain_rate - 7.0).abs() < 1e-9);
+
+    let names = pool.beatmaps().iter().map(|beatmap| beatmap.slot.to_string()).collect::<Vec<_>>();
+    assert_eq!(names, vec!["HR1", "DT1", "FM1"]);
+    assert_eq!(pool.beatmaps()[2].approach_rate, 6.0);
+
+    pool.insert(slot("TB"), 1);
+    match pool.fetch(|_| Ok(vec![])) {
+        Err(Error::Parse(_)) => {},
+        other => panic!("expected a parse error, found {:?}", other),
+    }
+}
+
+#[cfg(feature = "osz-support")]
+#[test]
+fn test_stars() {
+    use osu::difficulty;
+    use osu::osz::Difficulty;
+
+    let bytes = std::fs::read("./tests/resources/beatmap_01.osu").unwrap();
+    let normal = Difficulty::parse("beatmap_01.osu", &bytes).unwrap();
+    let beatmap = beatmaps().into_iter().find(|beatmap| beatmap.beatmap_id == 191904).unwrap();
+
+    let dt = PoolBeatmap::new(slot("DT1"), beatmap.clone());
+    let stars = dt.stars(&normal).unwrap();
+    assert_eq!(stars, difficulty::standard(&normal, Mods::DOUBLE_TIME).stars);
+    assert!(stars > difficulty::standard(&normal, Mods::empty()).stars);
+
+    // The API's rating is kept as it is.
+    assert_eq!(dt.beatmap.difficulty_rating, beatmap.difficulty_rating);
+}
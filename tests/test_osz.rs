@@ -0,0 +1,155 @@
+#![cfg(feature = "osz-support")]
+
+extern crate osu;
+extern crate serde_json;
+
+use osu::hitobject::Position;
+use osu::osz::*;
+use osu::{Beatmap, Mods};
+use std::fs::{self, File};
+
+fn set() -> BeatmapSet {
+    let f = File::open("./tests/resources/set_01.osz").unwrap();
+
+    BeatmapSet::read(f).unwrap()
+}
+
+#[test]
+fn test_read() {
+    let set = set();
+
+    assert_eq!(set.difficulties.len(), 2);
+    assert_eq!(set.beatmapset_id(), Some(45));
+    assert_eq!(set.storyboard.as_ref().unwrap().sprites.len(), 1);
+    assert_eq!(set.files_of_kind(FileKind::Audio).len(), 1);
+    assert_eq!(set.files_of_kind(FileKind::Background).len(), 1);
+    assert_eq!(set.files_of_kind(FileKind::Other)[0].name, "soft-hitclap.wav");
+
+    let hard = &set.difficulties[0];
+    assert_eq!(hard.beatmap_id, Some(123));
+    assert_eq!(hard.version, "Hard");
+    assert_eq!(hard.file_md5, "da4aeadd7fe29c772d6e9e5efa4c3163");
+    assert_eq!(hard.diff_approach, 8.5);
+    assert_eq!(hard.storyboard.sprites.len(), 1);
+    assert!(hard.timing_points.is_empty());
+    assert_eq!(hard.hit_objects.len(), 1);
+
+    // Old files have no IDs and share approach rate with overall difficulty.
+    let normal = &set.difficulties[1];
+    assert_eq!(normal.beatmap_id, None);
+    assert_eq!(normal.diff_approach, 4.0);
+}
+
+#[test]
+fn test_timing_points_and_hit_objects() {
+    let f = File::open("./tests/resources/set_02.osz").unwrap();
+    let set = BeatmapSet::read(f).unwrap();
+
+    let normal = &set.difficulties[0];
+    assert_eq!(normal.beatmap_id, Some(191904));
+    assert_eq!(normal.timing_points.len(), 6);
+    assert_eq!(normal.timing_points.iter().filter(|point| point.uninherited).count(), 3);
+    assert_eq!(normal.hit_objects.len(), 46);
+    assert_eq!(normal.hit_objects.iter().filter(|object| object.is_slider()).count(), 5);
+    assert_eq!(normal.hit_objects.iter().filter(|object| object.is_spinner()).count(), 1);
+    assert_eq!(normal.hit_objects[1].time, 1417.0);
+
+    assert_eq!(normal.format_version, 14);
+    assert_eq!(normal.stack_leniency, 0.7);
+
+    let mania = &set.difficulties[1];
+    assert_eq!(mania.mode, 3);
+    assert_eq!(mania.timing_points.len(), 3);
+    assert_eq!(mania.hit_objects.len(), 30);
+    assert_eq!(mania.hit_objects[0].column(7), 0);
+    assert_eq!(mania.hit_objects.iter().map(|object| object.end_time()).fold(0.0, f64::max), 4666.0);
+}
+
+#[test]
+fn test_stacked_positions() {
+    let f = File::open("./tests/resources/set_02.osz").unwrap();
+    let set = BeatmapSet::read(f).unwrap();
+    let normal = &set.difficulties[0];
+
+    // Three circles on (128, 300) at circle size 2, each offset by 4.544
+    // osu!pixels.
+    let positions = normal.stacked_positions(Mods::empty());
+    let stack = &positions[8..11];
+    assert!((stack[0].x - 118.912).abs() < 1e-9 && (stack[0].y - 290.912).abs() < 1e-9);
+    assert!((stack[1].x - 123.456).abs() < 1e-9 && (stack[1].y - 295.456).abs() < 1e-9);
+    assert_eq!(stack[2], normal.hit_objects[10].position);
+    assert_eq!(normal.stack_heights(Mods::empty()).iter().filter(|&&height| height != 0).count(), 2);
+
+    // HardRock flips the playfield and raises the circle size to 2.6.
+    let positions = normal.stacked_positions(Mods::HARD_ROCK);
+    let offset = 2.0 * (1.0 - 0.7 * (2.6 - 5.0) / 5.0) / 2.0 * -6.4;
+    assert!((positions[8].x - (128.0 + offset)).abs() < 1e-9);
+    assert!((positions[8].y - (84.0 + offset)).abs() < 1e-9);
+}
+
+#[test]
+fn test_stacked_positions_by_version() {
+    let bytes = fs::read("./tests/resources/beatmap_03.osu").unwrap();
+    let mut difficulty = Difficulty::parse("beatmap_03.osu", &bytes).unwrap();
+    let near = |position: Position, x: f64, y: f64| (position.x - x).abs() < 1e-9 && (position.y - y).abs() < 1e-9;
+
+    // Two stacked circles, and then a slider starting on the end of another
+    // with a circle on its head after it ends. Old stacking only extends the
+    // stack from the start of the second slider, so the last circle is too
+    // late to be moved below the first slider. Each height at circle size 4
+    // is an offset of 3.648 osu!pixels.
+    assert_eq!(difficulty.format_version, 5);
+    assert_eq!(difficulty.stack_heights(Mods::empty()), vec![1, 0, 0, 0, 0]);
+
+    let positions = difficulty.stacked_positions(Mods::empty());
+    assert!(near(positions[0], 96.352, 96.352));
+    assert!(near(positions[3], 400.0, 100.0));
+    assert!(near(positions[4], 400.0, 100.0));
+
+    // New stacking moves both below the end of the first slider.
+    difficulty.format_version = 14;
+    assert_eq!(difficulty.stack_heights(Mods::empty()), vec![1, 0, 0, -1, -2]);
+
+    let positions = difficulty.stacked_positions(Mods::empty());
+    assert!(near(positions[0], 96.352, 96.352));
+    assert!(near(positions[3], 403.648, 103.648));
+    assert!(near(positions[4], 407.296, 107.296));
+}
+
+#[test]
+fn test_verify() {
+    let f = File::open("./tests/resources/beatmaps_01.json").unwrap();
+    let mut beatmaps = serde_json::from_reader::<File, Vec<Beatmap>>(f).unwrap();
+    beatmaps.truncate(2);
+
+    beatmaps[0].beatmap_id = 123;
+    beatmaps[0].file_md5 = "da4aeadd7fe29c772d6e9e5efa4c3163".to_owned();
+    beatmaps[0].version = "Hard".to_owned();
+    beatmaps[1].beatmap_id = 124;
+    beatmaps[1].version = "Insane".to_owned();
+
+    let results = set().verify(&beatmaps);
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_match());
+
+    match results[1] {
+        Verification::Unlisted { ref file_name } => assert!(file_name.ends_with("[Normal].osu")),
+        ref other => panic!("expected an unlisted difficulty, found {:?}", other),
+    }
+
+    match results[2] {
+        Verification::Missing { beatmap_id } => assert_eq!(beatmap_id, 124),
+        ref other => panic!("expected a missing beatmap, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_breaks() {
+    let osu = "osu file format v14\n\n[Events]\n0,0,\"bg.jpg\",0,0\n2,4000,6500\nBreak,9000,12000\n";
+    let difficulty = Difficulty::parse("Breaks.osu", osu.as_bytes()).unwrap();
+    assert_eq!(difficulty.breaks, vec![(4000.0, 6500.0), (9000.0, 12000.0)]);
+
+    let osu = "osu file format v14\n\n[Events]\n2,4000,end\n";
+    assert!(Difficulty::parse("Breaks.osu", osu.as_bytes()).is_err());
+}
@@ -0,0 +1,97 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::builder::GetBeatmapsRequest;
+use osu::paginate::*;
+use osu::{Beatmap, Result};
+use std::fs::File;
+
+/// Creates beatmaps approved in groups of three per minute.
+fn beatmaps(count: usize) -> Vec<Beatmap> {
+    let f = File::open("./tests/resources/beatmaps_01.json").unwrap();
+    let template = serde_json::from_reader::<File, Vec<Beatmap>>(f).unwrap().remove(0);
+
+    (0..count).map(|idx| {
+        let mut beatmap = template.clone();
+        beatmap.beatmap_id = 1000 + idx as i64;
+        beatmap.approved_date = Some(format!("2018-01-01 {:02}:{:02}:00", idx / 3 / 60, idx / 3 % 60));
+        beatmap
+    }).collect()
+}
+
+/// Answers a request as the API does, from the beatmaps approved at or
+/// after `since`.
+fn api(beatmaps: &[Beatmap], cursor: &BeatmapCursor) -> Result<Vec<Beatmap>> {
+    let request = cursor.request(GetBeatmapsRequest::default());
+    let limit = request.0["limit"].parse::<usize>().unwrap();
+    let since = request.0.get("since");
+
+    Ok(beatmaps.iter()
+        .filter(|beatmap| match since {
+            Some(since) => beatmap.approved_date.as_ref().unwrap() >= since,
+            None => true,
+        })
+        .take(limit)
+        .cloned()
+        .collect())
+}
+
+#[test]
+fn test_pages() {
+    let all = beatmaps(1203);
+    let mut requests = 0;
+    let mut pages = BeatmapPages::new(BeatmapCursor::new(), |cursor| {
+        requests += 1;
+
+        api(&all, cursor)
+    });
+
+    let ids = pages.by_ref().map(|beatmap| beatmap.unwrap().beatmap_id).collect::<Vec<_>>();
+    assert_eq!(ids, all.iter().map(|beatmap| beatmap.beatmap_id).collect::<Vec<_>>());
+    assert_eq!(pages.cursor().since, all[1202].approved_date);
+    assert_eq!(pages.cursor().seen, vec![2200, 2201, 2202]);
+    drop(pages);
+    assert_eq!(requests, 3);
+}
+
+#[test]
+fn test_resume() {
+    let all = beatmaps(1203);
+    let mut pages = BeatmapPages::new(BeatmapCursor::new(), |cursor| api(&all, cursor));
+    let first = pages.by_ref().take(701).map(|beatmap| beatmap.unwrap().beatmap_id).collect::<Vec<_>>();
+
+    let saved = serde_json::to_string(pages.cursor()).unwrap();
+    let cursor = serde_json::from_str::<BeatmapCursor>(&saved).unwrap();
+    let rest = BeatmapPages::new(cursor, |cursor| api(&all, cursor))
+        .map(|beatmap| beatmap.unwrap().beatmap_id)
+        .collect::<Vec<_>>();
+
+    assert_eq!(first.len() + rest.len(), all.len());
+    assert_eq!(first[700] + 1, rest[0]);
+}
+
+#[test]
+fn test_filter() {
+    let mut page = beatmaps(4);
+    page[0].approved_date = None;
+    page.swap(1, 3);
+
+    let mut cursor = BeatmapCursor::since("2018-01-01 00:00:00");
+    cursor.seen.push(1001);
+
+    let ids = cursor.filter(page).iter().map(|beatmap| beatmap.beatmap_id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![1002, 1003]);
+}
+
+#[test]
+fn test_same_date() {
+    let mut all = beatmaps(600);
+
+    for beatmap in &mut all {
+        beatmap.approved_date = Some("2018-01-01 00:00:00".to_owned());
+    }
+
+    let results = BeatmapPages::new(BeatmapCursor::new(), |cursor| api(&all, cursor)).collect::<Vec<_>>();
+    assert_eq!(results.len(), PAGE_LIMIT as usize + 1);
+    assert!(results[PAGE_LIMIT as usize].is_err());
+}
@@ -0,0 +1,163 @@
+extern crate osu;
+
+use osu::pp::*;
+use osu::{Mods, PlayMode};
+
+fn standard() -> DifficultyAttributes {
+    DifficultyAttributes::Standard(StandardAttributes {
+        stars: 5.5,
+        aim_strain: 2.8,
+        speed_strain: 2.5,
+        approach_rate: 9.0,
+        overall_difficulty: 8.5,
+        max_combo: 1200,
+        circle_count: 500,
+        slider_count: 300,
+        spinner_count: 2,
+    })
+}
+
+fn play() -> Score {
+    Score {
+        count_300: 760,
+        count_100: 30,
+        count_50: 2,
+        count_miss: 10,
+        max_combo: 700,
+        score: 30_000_000,
+        ..Score::default()
+    }
+}
+
+#[test]
+fn test_accuracy() {
+    let score = Score {
+        count_300: 90,
+        count_100: 6,
+        count_50: 3,
+        count_miss: 1,
+        ..Score::default()
+    };
+
+    let acc = accuracy(PlayMode::Standard, &score);
+
+    assert!((acc - (90.0 * 6.0 + 12.0 + 3.0) / 600.0).abs() < 1e-9);
+    assert_eq!(accuracy(PlayMode::Taiko, &Score::default()), 0.0);
+}
+
+#[test]
+fn test_fc_and_ss() {
+    let attrs = standard();
+    let pp = calculate(&attrs, Mods::empty(), &play());
+    let fc = calculate(&attrs, Mods::empty(), &play().fc(&attrs));
+    let ss = calculate(&attrs, Mods::empty(), &play().ss(&attrs, Mods::empty()));
+
+    assert!(pp > 0.0);
+    assert!(fc > pp);
+    assert!(ss > fc);
+}
+
+#[test]
+fn test_mods() {
+    let attrs = standard();
+    let pp = calculate(&attrs, Mods::empty(), &play());
+
+    assert!(calculate(&attrs, Mods::HIDDEN, &play()) > pp);
+    assert!(calculate(&attrs, Mods::NO_FAIL, &play()) < pp);
+    assert_eq!(calculate(&attrs, Mods::RELAX, &play()), 0.0);
+}
+
+#[test]
+fn test_adjusted_difficulty() {
+    assert!((approach_rate(9.0, Mods::DOUBLE_TIME) - 10.33).abs() < 0.01);
+    assert!((approach_rate(9.0, Mods::HARD_ROCK) - 10.0).abs() < 1e-9);
+    assert!((overall_difficulty(8.0, Mods::HALF_TIME) - 6.22).abs() < 0.01);
+}
+
+#[test]
+fn test_mania_score_threshold() {
+    let attrs = DifficultyAttributes::Mania(ManiaAttributes {
+        stars: 4.0,
+        overall_difficulty: 8.0,
+        note_count: 1500,
+    });
+    let low = Score { score: 400_000, ..Score::default() };
+    let high = Score { score: 990_000, ..Score::default() };
+
+    assert_eq!(calculate(&attrs, Mods::empty(), &low), 0.0);
+    assert!(calculate(&attrs, Mods::empty(), &high) > 0.0);
+}
+
+/// Asserts that a value matches a reference value from osu-performance's
+/// formulas, evaluated in double precision.
+fn assert_reference(value: f64, reference: f64) {
+    assert!((value - reference).abs() < 1e-6, "{} != {}", value, reference);
+}
+
+#[test]
+fn test_standard_reference() {
+    let attrs = standard();
+
+    assert_reference(calculate(&attrs, Mods::empty(), &play()), 94.30707828388884);
+    assert_reference(calculate(&attrs, Mods::HIDDEN | Mods::DOUBLE_TIME, &play()), 119.48331997487767);
+}
+
+#[test]
+fn test_taiko_reference() {
+    let attrs = DifficultyAttributes::Taiko(TaikoAttributes {
+        stars: 4.0,
+        overall_difficulty: 6.0,
+        max_combo: 800,
+    });
+    let score = Score {
+        count_300: 760,
+        count_100: 30,
+        count_miss: 10,
+        max_combo: 500,
+        ..Score::default()
+    };
+
+    assert_reference(calculate(&attrs, Mods::HIDDEN, &score), 127.6596268166771);
+}
+
+#[test]
+fn test_catch_reference() {
+    let attrs = DifficultyAttributes::CatchTheBeat(CatchAttributes {
+        stars: 5.0,
+        approach_rate: 9.0,
+        max_combo: 1000,
+    });
+    let score = Score {
+        count_300: 980,
+        count_100: 10,
+        count_50: 90,
+        count_katu: 5,
+        count_miss: 10,
+        max_combo: 700,
+        ..Score::default()
+    };
+
+    assert_reference(calculate(&attrs, Mods::HIDDEN, &score), 162.76837193709662);
+}
+
+#[test]
+fn test_mania_reference() {
+    let attrs = DifficultyAttributes::Mania(ManiaAttributes {
+        stars: 4.0,
+        overall_difficulty: 8.0,
+        note_count: 1500,
+    });
+    let score = Score {
+        count_geki: 1200,
+        count_300: 250,
+        count_katu: 30,
+        count_100: 15,
+        count_50: 3,
+        count_miss: 2,
+        score: 965_000,
+        ..Score::default()
+    };
+
+    assert_reference(calculate(&attrs, Mods::empty(), &score), 145.84722938288897);
+    assert_reference(calculate(&attrs, Mods::DOUBLE_TIME, &score), 144.6319142805171);
+}
@@ -0,0 +1,187 @@
+#![cfg(feature = "replay-support")]
+
+extern crate osu;
+
+use osu::convert::{self, ManiaObject};
+use osu::db::LocalScore;
+use osu::osz::Difficulty;
+use osu::replay::{self, Judgement, Key, Keys, Mismatch, ReplayAnalysis, ReplayFrame};
+use osu::{Mods, PlayMode};
+use std::fs::{self, File};
+
+fn difficulty(name: &str) -> Difficulty {
+    let bytes = fs::read(format!("./tests/resources/{}", name)).unwrap();
+
+    Difficulty::parse(name, &bytes).unwrap()
+}
+
+fn score(name: &str) -> LocalScore {
+    let f = File::open(format!("./tests/resources/{}", name)).unwrap();
+
+    LocalScore::from_replay(f).unwrap()
+}
+
+fn analyze(beatmap: &str, score: &LocalScore) -> ReplayAnalysis {
+    let frames = replay::frames(score.replay.as_ref().unwrap()).unwrap();
+
+    replay::analyze(&difficulty(beatmap), score.mode, score.enabled_mods, &frames).unwrap()
+}
+
+fn judgements(analysis: &ReplayAnalysis) -> Vec<Judgement> {
+    analysis.hits.iter().map(|hit| hit.judgement).collect()
+}
+
+#[test]
+fn test_replay_header() {
+    let score = score("replay_01.osr");
+
+    assert_eq!(score.mode, PlayMode::Standard);
+    assert_eq!(score.version, 20190601);
+    assert_eq!(score.beatmap_md5, "9ca3fce4212784b0108f6c9cab1b801c");
+    assert_eq!(score.username, "Player");
+    assert_eq!(score.count_300, 41);
+    assert_eq!(score.count_miss, 2);
+    assert_eq!(score.date, "2019-06-01 00:00:00");
+    assert_eq!(score.enabled_mods, Mods::empty());
+}
+
+#[test]
+fn test_frames() {
+    let score = score("replay_01.osr");
+    let frames = replay::frames(score.replay.as_ref().unwrap()).unwrap();
+
+    // The seed frame is skipped, and times are summed.
+    assert!(frames.iter().all(|frame| frame.x != 0.0 || frame.y != 0.0));
+    assert_eq!(frames[0].time, 0.0);
+    assert_eq!(frames[1].time, -1.0);
+    assert_eq!(frames[3].time, 988.0);
+    assert_eq!(frames[3].keys, Keys::M1 | Keys::K1);
+
+    assert!(replay::frames(b"not lzma").is_err());
+}
+
+#[test]
+fn test_standard() {
+    let score = score("replay_01.osr");
+    let analysis = analyze("beatmap_01.osu", &score);
+    let judgements = judgements(&analysis);
+
+    assert_eq!(analysis.hits.len(), 46);
+    assert_eq!(&judgements[..4], &[Judgement::Hit300, Judgement::Hit100, Judgement::Hit50, Judgement::Miss]);
+    // A press outside of the circle is ignored.
+    assert_eq!(judgements[9], Judgement::Miss);
+    // A slider released after its head, and one hit late but followed.
+    assert_eq!(judgements[6], Judgement::Hit50);
+    assert_eq!(judgements[7], Judgement::Hit300);
+    assert_eq!(analysis.hits[7].error, Some(100.0));
+    // A spinner spun 12 times, of the 10 needed.
+    assert_eq!(judgements[20], Judgement::Hit300);
+    assert_eq!(analysis.hits[20].error, None);
+
+    assert_eq!(analysis.count(Judgement::Hit300), 41);
+    assert_eq!(analysis.count(Judgement::Hit100), 1);
+    assert_eq!(analysis.count(Judgement::Hit50), 2);
+    assert_eq!(analysis.count(Judgement::Miss), 2);
+    assert!(analysis.validate(&score).is_empty());
+
+    assert!((analysis.mean_error().unwrap() - 0.837_209_302_325_581_4).abs() < 1e-9);
+    assert!((analysis.unstable_rate().unwrap() - 246.886_945_528_441_1).abs() < 1e-9);
+    assert!((analysis.tapping_bpm.unwrap() - 35.377_358_490_566_04).abs() < 1e-9);
+
+    let presses = analysis.key_presses.iter().map(|(&key, &count)| (key, count)).collect::<Vec<_>>();
+    assert_eq!(presses, vec![(Key::M1, 1), (Key::M2, 1), (Key::K1, 25), (Key::K2, 18)]);
+}
+
+#[test]
+fn test_mania() {
+    let score = score("replay_02.osr");
+    let analysis = analyze("beatmap_02.osu", &score);
+    let judgements = judgements(&analysis);
+
+    assert_eq!(analysis.hits.len(), 30);
+    assert_eq!(&judgements[..6], &[
+        Judgement::Geki,
+        Judgement::Hit300,
+        Judgement::Katu,
+        Judgement::Hit100,
+        Judgement::Hit50,
+        Judgement::Miss,
+    ]);
+    // Hold notes released at the end, 200ms early and late.
+    assert_eq!(judgements[7], Judgement::Hit300);
+    assert_eq!(judgements[14], Judgement::Hit100);
+    assert_eq!(judgements[22], Judgement::Hit300);
+    assert_eq!(judgements[29], Judgement::Geki);
+
+    assert!(analysis.validate(&score).is_empty());
+    assert!((analysis.mean_error().unwrap() - 4.827_586_206_896_552).abs() < 1e-9);
+    assert!((analysis.unstable_rate().unwrap() - 359.521_415_270_425_8).abs() < 1e-9);
+    assert!((analysis.tapping_bpm.unwrap() - 94.936_708_860_759_5).abs() < 1e-9);
+
+    let presses = analysis.key_presses.values().cloned().collect::<Vec<_>>();
+    assert_eq!(analysis.key_presses.keys().next(), Some(&Key::Column(0)));
+    assert_eq!(presses, vec![5, 4, 4, 5, 3, 3, 5]);
+
+    // HardRock narrows the windows.
+    let frames = replay::frames(score.replay.as_ref().unwrap()).unwrap();
+    let hard_rock = replay::analyze(&difficulty("beatmap_02.osu"), PlayMode::Mania, Mods::HARD_ROCK, &frames).unwrap();
+    assert!(hard_rock.count(Judgement::Geki) < analysis.count(Judgement::Geki));
+}
+
+#[test]
+fn test_mania_convert() {
+    let normal = difficulty("beatmap_01.osu");
+    let notes = convert::mania(&normal, Mods::empty());
+    let (first, second) = {
+        let mut singles = notes.iter().filter(|note| note.end_time.is_none());
+        (singles.next().unwrap(), singles.next().unwrap())
+    };
+    let frame = |time: f64, columns: u32| ReplayFrame { time, x: f64::from(columns), y: 0.0, keys: Keys::empty() };
+
+    // At overall difficulty 8, converts have a 34ms window for a 300 and a
+    // 97ms window for a 100, narrower than the 40ms and 103ms that the
+    // beatmap would have in mania.
+    let frames = vec![
+        frame(first.time + 36.0, 1 << first.column),
+        frame(first.time + 60.0, 0),
+        frame(second.time + 100.0, 1 << second.column),
+        frame(second.time + 120.0, 0),
+    ];
+    let analysis = replay::analyze(&normal, PlayMode::Mania, Mods::empty(), &frames).unwrap();
+    let hit = |note: &ManiaObject| analysis.hits.iter().find(|hit| hit.time == note.time && hit.error.is_some()).unwrap();
+
+    assert_eq!(hit(first).judgement, Judgement::Katu);
+    assert_eq!(hit(second).judgement, Judgement::Hit50);
+}
+
+#[test]
+fn test_validate() {
+    let mut score = score("replay_02.osr");
+    let analysis = analyze("beatmap_02.osu", &score);
+
+    score.count_geki -= 1;
+    score.count_miss += 1;
+
+    assert_eq!(analysis.validate(&score), vec![
+        Mismatch { judgement: Judgement::Geki, expected: 16, found: 17 },
+        Mismatch { judgement: Judgement::Miss, expected: 2, found: 1 },
+    ]);
+
+    // Gekis and katus count combos in standard, and are not checked.
+    let mut score = self::score("replay_01.osr");
+    score.count_geki = 0;
+    assert!(analyze("beatmap_01.osu", &score).validate(&score).is_empty());
+}
+
+#[test]
+fn test_unsupported_modes() {
+    let normal = difficulty("beatmap_01.osu");
+
+    assert!(replay::analyze(&normal, PlayMode::Taiko, Mods::empty(), &[]).is_none());
+    assert!(replay::analyze(&difficulty("beatmap_02.osu"), PlayMode::Standard, Mods::empty(), &[]).is_none());
+
+    let empty = replay::analyze(&normal, PlayMode::Standard, Mods::empty(), &[]).unwrap();
+    assert_eq!(empty.count(Judgement::Miss), 46);
+    assert_eq!(empty.unstable_rate(), None);
+    assert_eq!(empty.tapping_bpm, None);
+}
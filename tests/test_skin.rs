@@ -0,0 +1,98 @@
+extern crate osu;
+
+use osu::skin::*;
+use osu::PlayMode;
+use std::fs::File;
+use std::io::Read;
+
+fn skin() -> SkinIni {
+    let mut content = String::new();
+    let mut f = File::open("./tests/resources/skin_01.ini").unwrap();
+    f.read_to_string(&mut content).unwrap();
+
+    SkinIni::parse(&content)
+}
+
+#[test]
+fn test_parse() {
+    let skin = skin();
+
+    assert_eq!(skin.general.name, "Example Skin");
+    assert_eq!(skin.general.version, "2.5");
+    assert_eq!(skin.general.animation_framerate, Some(30));
+    assert!(!skin.general.cursor_expand);
+    assert!(!skin.general.hit_circle_overlay_above_number);
+    assert_eq!(skin.general.custom_combo_burst_sounds, vec![50, 100, 200]);
+
+    assert_eq!(skin.colours.combos.len(), 2);
+    assert_eq!(skin.colours.combos[&1], Colour::rgb(255, 192, 0));
+    assert_eq!(skin.colours.slider_border.alpha, 128);
+    assert_eq!(skin.colours.slider_track_override, Some(Colour::rgb(20, 20, 20)));
+    assert_eq!(skin.colours.menu_glow, Colour::rgb(0, 78, 155));
+
+    assert_eq!(skin.fonts.hit_circle_overlap, 3);
+    assert_eq!(skin.fonts.combo_prefix, "score");
+    assert_eq!(skin.catch_the_beat.hyper_dash, Colour::rgb(255, 0, 255));
+
+    assert_eq!(skin.mania.len(), 2);
+    let mania = skin.mania(4).unwrap();
+    assert_eq!(mania.column_width, vec![40.0; 4]);
+    assert_eq!(mania.hit_position, 420);
+    assert_eq!(mania.colours.len(), 2);
+    assert_eq!(mania.images["NoteImage0H"], "Mania\\note1H");
+    assert!(!mania.images.contains_key("KeyImage4"));
+    assert_eq!(skin.mania(7).unwrap().special_style, 1);
+}
+
+#[test]
+fn test_issues() {
+    let issues = skin().issues;
+    let lines = issues.iter().map(|issue| issue.line).collect::<Vec<_>>();
+
+    assert_eq!(lines, vec![10, 11, 16, 19, 35, 39, 43, 50]);
+    assert_eq!(issues[0].kind, IssueKind::UnknownKey {
+        section: "General".to_owned(),
+        key: "SliderStyle".to_owned(),
+    });
+    assert_eq!(issues[3].kind, IssueKind::InvalidValue {
+        key: "MenuGlow".to_owned(),
+        value: "300,0,0".to_owned(),
+    });
+    assert_eq!(issues[6].to_string(), "line 43: [Mania] is missing Keys");
+    assert_eq!(issues[7].kind, IssueKind::UnknownSection("Extra".to_owned()));
+
+    assert!(SkinIni::parse("Name: Outside\n").issues[0].kind == IssueKind::Malformed);
+}
+
+#[test]
+fn test_elements() {
+    let skin = skin();
+    let files = [
+        "cursor@2x.png",
+        "Fonts/Default-1.png",
+        "fonts/score-0.png",
+        "sliderb-0.png",
+        "taikohitcircle.png",
+        "Mania/note1.png",
+    ];
+
+    let find = |elements: &[Element], name: &str| {
+        elements.iter().find(|e| e.name == name).unwrap().file.clone()
+    };
+
+    let standard = skin.elements(PlayMode::Standard, &files);
+    assert_eq!(find(&standard, "cursor"), Some("cursor@2x.png".to_owned()));
+    assert_eq!(find(&standard, "fonts\\default-1"), Some("Fonts/Default-1.png".to_owned()));
+    assert_eq!(find(&standard, "fonts\\default-2"), None);
+    assert_eq!(find(&standard, "fonts\\score-0"), Some("fonts/score-0.png".to_owned()));
+    assert_eq!(find(&standard, "sliderb"), Some("sliderb-0.png".to_owned()));
+    assert_eq!(find(&standard, "hitcircle"), None);
+    assert!(standard.iter().all(|e| e.name != "taikohitcircle"));
+
+    let taiko = skin.elements(PlayMode::Taiko, &files);
+    assert_eq!(find(&taiko, "taikohitcircle"), Some("taikohitcircle.png".to_owned()));
+
+    let mania = skin.elements(PlayMode::Mania, &files);
+    assert_eq!(find(&mania, "Mania\\note1"), Some("Mania/note1.png".to_owned()));
+    assert_eq!(find(&mania, "Mania\\stage-left"), None);
+}
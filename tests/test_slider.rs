@@ -0,0 +1,178 @@
+#![cfg(feature = "osz-support")]
+
+extern crate osu;
+
+use osu::hitobject::{CurveType, HitObject, Position};
+use osu::osz::Difficulty;
+use osu::slider::*;
+use osu::timing::Timing;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Read;
+
+fn difficulty() -> Difficulty {
+    let mut bytes = vec![];
+    File::open("./tests/resources/beatmap_01.osu").unwrap().read_to_end(&mut bytes).unwrap();
+
+    Difficulty::parse("beatmap_01.osu", &bytes).unwrap()
+}
+
+fn sliders(difficulty: &Difficulty) -> Vec<Slider> {
+    let timing = difficulty.timing();
+
+    difficulty.hit_objects.iter()
+        .filter_map(|object| Slider::new(object, &timing, difficulty.slider_multiplier, difficulty.slider_tick_rate))
+        .collect()
+}
+
+fn assert_near(actual: Position, expected: Position, epsilon: f64) {
+    assert!(actual.distance(expected) < epsilon, "expected {:?}, found {:?}", expected, actual);
+}
+
+#[test]
+fn test_linear() {
+    let points = [Position::new(0.0, 0.0), Position::new(100.0, 0.0), Position::new(100.0, 100.0)];
+    let path = SliderPath::new(CurveType::Linear, &points, Some(150.0));
+
+    assert_eq!(path.length(), 150.0);
+    assert_eq!(path.points().len(), 3);
+    assert_eq!(path.position_at(0.5), Position::new(75.0, 0.0));
+    assert_eq!(path.position_at(1.0), Position::new(100.0, 50.0));
+
+    // A longer pixel length extends the last segment.
+    let path = SliderPath::new(CurveType::Linear, &points, Some(250.0));
+    assert_eq!(path.position_at(1.0), Position::new(100.0, 150.0));
+
+    // Without a pixel length, the whole path is used.
+    let path = SliderPath::new(CurveType::Linear, &points, None);
+    assert_eq!(path.length(), 200.0);
+}
+
+#[test]
+fn test_bezier() {
+    // A red anchor splits the path into two straight segments.
+    let points = [Position::new(100.0, 300.0), Position::new(200.0, 200.0),
+                  Position::new(200.0, 200.0), Position::new(300.0, 300.0)];
+    let path = SliderPath::new(CurveType::Bezier, &points, Some(200.0));
+
+    assert_eq!(path.length(), 200.0);
+    assert_near(path.position_at(50f64.hypot(50.0) * 2.0 / 200.0), Position::new(200.0, 200.0), 1e-9);
+    assert_near(path.position_at(1.0), Position::new(200.0 + 200.0 / 2f64.sqrt() - 100.0, 200.0 + 200.0 / 2f64.sqrt() - 100.0), 1e-9);
+
+    // A quadratic curve from (0, 0) to (200, 0) peaks at half its control
+    // point's height.
+    let points = [Position::new(0.0, 0.0), Position::new(100.0, 100.0), Position::new(200.0, 0.0)];
+    let path = SliderPath::new(CurveType::Bezier, &points, None);
+
+    assert_near(path.position_at(0.5), Position::new(100.0, 50.0), 0.5);
+    assert_near(path.position_at(1.0), Position::new(200.0, 0.0), 1e-9);
+    assert!(path.points().iter().all(|point| point.y <= 50.0 + 1e-9));
+}
+
+#[test]
+fn test_perfect_circle() {
+    let points = [Position::new(206.0, 192.0), Position::new(256.0, 142.0), Position::new(306.0, 192.0)];
+    let path = SliderPath::new(CurveType::PerfectCircle, &points, Some(PI * 50.0));
+    let centre = Position::new(256.0, 192.0);
+
+    assert!((path.length() - PI * 50.0).abs() < 1e-9);
+    assert!(path.points().iter().all(|point| (point.distance(centre) - 50.0).abs() < 0.2));
+    assert_near(path.position_at(0.5), Position::new(256.0, 142.0), 0.2);
+    assert_near(path.position_at(1.0), Position::new(306.0, 192.0), 0.2);
+
+    // Points in a line fall back to a Bézier curve.
+    let points = [Position::new(0.0, 0.0), Position::new(50.0, 0.0), Position::new(100.0, 0.0)];
+    let path = SliderPath::new(CurveType::PerfectCircle, &points, None);
+    assert!((path.length() - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_catmull() {
+    let points = [Position::new(64.0, 64.0), Position::new(128.0, 96.0),
+                  Position::new(192.0, 64.0), Position::new(256.0, 96.0)];
+    let path = SliderPath::new(CurveType::Catmull, &points, None);
+
+    // The spline passes through each of its points.
+    for point in &points {
+        assert!(path.points().contains(point));
+    }
+
+    assert!(path.length() > 3.0 * 32f64.hypot(64.0));
+}
+
+#[test]
+fn test_timing() {
+    let difficulty = difficulty();
+    let sliders = sliders(&difficulty);
+    let beat = 416.565418127;
+
+    assert_eq!(sliders.len(), 5);
+
+    // A slider one beat long has no ticks.
+    let linear = &sliders[0];
+    assert!((linear.span_duration() - beat).abs() < 1e-6);
+    assert!((linear.end_time() - (2666.0 + beat)).abs() < 1e-6);
+    assert!((linear.legacy_last_tick_time() - (2666.0 + beat - 36.0)).abs() < 1e-6);
+    assert_eq!(linear.max_combo(), 2);
+
+    let bezier = &sliders[1];
+    let kinds = bezier.events().iter().map(|event| event.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![SliderEventKind::Head, SliderEventKind::Tick, SliderEventKind::Tail]);
+    assert!((bezier.events()[1].time - (3499.0 + beat)).abs() < 1e-6);
+
+    // The semicircle has a repeat, and a tick on each span.
+    let circle = &sliders[2];
+    let events = circle.events();
+    let span = PI * 50.0 / 140.0 * beat;
+    let kinds = events.iter().map(|event| event.kind).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![SliderEventKind::Head, SliderEventKind::Tick, SliderEventKind::Repeat,
+                           SliderEventKind::Tick, SliderEventKind::Tail]);
+    assert!((events[1].time - (4333.0 + beat)).abs() < 1e-6);
+    assert!((events[2].time - (4333.0 + span)).abs() < 1e-6);
+    assert!((events[3].time - (4333.0 + 2.0 * span - beat)).abs() < 1e-6);
+    assert!((circle.end_time() - (4333.0 + 2.0 * span)).abs() < 1e-6);
+    assert_eq!(events[1].position, events[3].position);
+    assert_eq!(events[2].progress, 1.0);
+    assert_eq!(events[4].progress, 0.0);
+    assert!((events[1].progress - events[3].progress).abs() < 1e-9);
+    assert_near(circle.end_position(), Position::new(206.0, 192.0), 1e-9);
+    assert_near(circle.position_at_time(4333.0 + span * 1.5), Position::new(256.0, 142.0), 0.2);
+
+    // The other two sliders are a beat or less long, without ticks.
+    let total = sliders.iter().map(Slider::max_combo).sum::<u32>();
+    let others = difficulty.hit_objects.iter().filter(|object| !object.is_slider()).count() as u32;
+    assert_eq!(total, 2 + 3 + 5 + 2 + 2);
+    assert_eq!(total + others, 55);
+}
+
+#[test]
+fn test_edge_sounds() {
+    let object = "100,100,2666,2,0,L|300:100,2,200,2|0|8".parse::<HitObject>().unwrap();
+    assert_eq!((object.edge_sound(0), object.edge_sound(1), object.edge_sound(2)), (2, 0, 8));
+
+    let object = "100,100,2666,2,4,L|300:100,2,200".parse::<HitObject>().unwrap();
+    assert_eq!(object.edge_sound(1), 4);
+
+    assert!("100,100,2666,2,0,L|300:100,2,200,2|x".parse::<HitObject>().is_err());
+}
+
+#[test]
+fn test_slider_velocity() {
+    let object = "0,0,1000,2,0,L|280:0,1,280".parse::<HitObject>().unwrap();
+    let timing = Timing::new(vec![
+        "0,500,4,2,1,60,1,0".parse().unwrap(),
+        "1000,-50,4,2,1,60,0,0".parse().unwrap(),
+    ]);
+    let slider = Slider::new(&object, &timing, 1.4, 2.0).unwrap();
+
+    // Twice the velocity covers 280 osu!pixels in a beat, with a tick every
+    // half beat.
+    assert_eq!(slider.velocity, 280.0 / 500.0);
+    assert_eq!(slider.tick_distance, 140.0);
+    assert!((slider.duration() - 500.0).abs() < 1e-9);
+    assert_eq!(slider.events().iter().filter(|event| event.kind == SliderEventKind::Tick).count(), 1);
+    assert_near(slider.position_at_time(1250.0), Position::new(140.0, 0.0), 1e-9);
+
+    let circle = "0,0,1000,1,0".parse::<HitObject>().unwrap();
+    assert!(Slider::new(&circle, &timing, 1.4, 1.0).is_none());
+}
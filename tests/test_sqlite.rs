@@ -0,0 +1,109 @@
+#![cfg(feature = "sqlite-support")]
+
+extern crate osu;
+extern crate serde;
+extern crate serde_json;
+
+use osu::sqlite::Store;
+use osu::{Approval, Beatmap, Game, Match, Mods, Performance, PlayMode, RecentPlay, User};
+use std::fs::File;
+
+fn resource<T: serde::de::DeserializeOwned>(name: &str) -> T {
+    let f = File::open(format!("./tests/resources/{}", name)).unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+#[test]
+fn test_migrations() {
+    let store = Store::open_in_memory().unwrap();
+    let version = store.version().unwrap();
+    assert!(version > 0);
+
+    // Reopening an up-to-date database applies nothing.
+    let store = Store::from_connection(store.into_connection()).unwrap();
+    assert_eq!(store.version().unwrap(), version);
+}
+
+#[test]
+fn test_beatmaps() {
+    let mut store = Store::open_in_memory().unwrap();
+    let mut beatmaps = resource::<Vec<Beatmap>>("beatmaps_01.json");
+    store.upsert_beatmaps(&beatmaps).unwrap();
+
+    let first = beatmaps[0].clone();
+    let stored = store.get_beatmap(first.beatmap_id).unwrap().unwrap();
+    assert_eq!(stored.title, first.title);
+    assert_eq!(stored.approved, first.approved);
+    assert_eq!(stored.max_combo, first.max_combo);
+    assert_eq!(store.get_beatmapset(first.beatmapset_id).unwrap().len(),
+               beatmaps.iter().filter(|b| b.beatmapset_id == first.beatmapset_id).count());
+    assert!(store.get_beatmap(-1).unwrap().is_none());
+
+    beatmaps[0].approved = Approval::Loved;
+    beatmaps[0].play_count += 1;
+    store.upsert_beatmap(&beatmaps[0]).unwrap();
+
+    let stored = store.get_beatmap(first.beatmap_id).unwrap().unwrap();
+    assert_eq!(stored.approved, Approval::Loved);
+    assert_eq!(stored.play_count, beatmaps[0].play_count);
+}
+
+#[test]
+fn test_users_and_plays() {
+    let mut store = Store::open_in_memory().unwrap();
+    let mut user = resource::<User>("user_01.json");
+    store.upsert_user(&user, PlayMode::Standard).unwrap();
+
+    user.pp_raw += 10.0;
+    store.upsert_user(&user, PlayMode::Standard).unwrap();
+
+    let stored = store.get_user(user.id, PlayMode::Standard).unwrap().unwrap();
+    assert_eq!(stored.username, user.username);
+    assert_eq!(stored.pp_raw, user.pp_raw);
+    assert_eq!(stored.events.len(), user.events.len());
+    assert!(store.get_user(user.id, PlayMode::Taiko).unwrap().is_none());
+
+    let best = resource::<Vec<Performance>>("user_best_01.json");
+    store.upsert_performances(PlayMode::Standard, &best).unwrap();
+    store.upsert_performances(PlayMode::Standard, &best).unwrap();
+
+    let stored = store.get_performances(user.id, PlayMode::Standard).unwrap();
+    assert_eq!(stored.len(), best.len());
+    assert_eq!(stored[0].pp, best[0].pp);
+    assert_eq!(stored[0].enabled_mods, best[0].enabled_mods);
+
+    let recent = resource::<Vec<RecentPlay>>("user_recent_01.json");
+    store.upsert_recent_plays(PlayMode::Standard, &recent).unwrap();
+
+    let stored = store.get_recent_plays(user.id, PlayMode::Standard).unwrap();
+    assert_eq!(stored.len(), recent.len());
+    assert_eq!(stored[0].date, recent[0].date);
+}
+
+#[test]
+fn test_matches() {
+    let mut store = Store::open_in_memory().unwrap();
+    let m = Match {
+        end_time: None,
+        match_id: 41_120_871,
+        name: "OWC: (South Korea) vs (Poland)".to_owned(),
+        start_time: "2018-03-10 17:00:00".to_owned(),
+    };
+    store.upsert_match(&m).unwrap();
+
+    let games = resource::<Vec<Game>>("games_01.json");
+    store.upsert_games(m.match_id, &games).unwrap();
+    store.upsert_games(m.match_id, &games).unwrap();
+
+    assert_eq!(store.get_match(m.match_id).unwrap().unwrap().name, m.name);
+
+    let stored = store.get_games(m.match_id).unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0].game_id, 204_310);
+    assert_eq!(stored[0].mods, Mods::NO_FAIL);
+    assert_eq!(stored[0].scores.len(), 2);
+    assert_eq!(stored[0].scores[0].username, "Rafis");
+    assert_eq!(stored[1].play_mode, PlayMode::Standard);
+    assert!(stored[1].scores.is_empty());
+}
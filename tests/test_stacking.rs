@@ -0,0 +1,60 @@
+extern crate osu;
+
+use osu::hitobject::{HitObject, Position};
+use osu::slider::Slider;
+use osu::stacking;
+use osu::timing::Timing;
+
+/// Three circles stacked by time, a slider with a repeat followed by
+/// circles on its head and the end of its path, and two circles 300ms apart.
+fn objects() -> (Vec<HitObject>, Vec<Option<Slider>>) {
+    let objects = [
+        "100,100,0,5,0",
+        "100,100,200,1,0",
+        "100,100,400,1,0",
+        "300,100,1000,2,0,L|400:100,2,100",
+        "400,100,2000,1,0",
+        "300,100,2200,1,0",
+        "200,300,3000,5,0",
+        "200,300,3300,1,0",
+    ].iter().map(|line| line.parse::<HitObject>().unwrap()).collect::<Vec<_>>();
+    let timing = Timing::new(vec!["0,500,4,2,1,60,1,0".parse().unwrap()]);
+    let sliders = objects.iter().map(|object| Slider::new(object, &timing, 1.4, 1.0)).collect();
+
+    (objects, sliders)
+}
+
+#[test]
+fn test_new_stacking() {
+    let (objects, sliders) = objects();
+
+    // The slider ends on its head, so the circle there is moved below it.
+    let heights = stacking::stack_heights(&objects, &sliders, 5.0, 0.7, 14);
+    assert_eq!(heights, vec![2, 1, 0, 0, 0, -1, 1, 0]);
+
+    // A 240ms threshold only stacks the circles 200ms apart.
+    let heights = stacking::stack_heights(&objects, &sliders, 5.0, 0.2, 14);
+    assert_eq!(heights, vec![2, 1, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_old_stacking() {
+    let (objects, sliders) = objects();
+
+    // Old stacking looks at the end of the slider's path, whatever its
+    // repeats, and stacks the slider itself on the circle at its head.
+    let heights = stacking::stack_heights(&objects, &sliders, 5.0, 0.7, 5);
+    assert_eq!(heights, vec![2, 1, 0, 1, -1, 0, 1, 0]);
+
+    let heights = stacking::stack_heights(&objects, &sliders, 5.0, 0.2, 5);
+    assert_eq!(heights, vec![2, 1, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_offset() {
+    assert_eq!(stacking::stack_offset(0, 4.0), Position::new(0.0, 0.0));
+    assert_eq!(stacking::stack_offset(1, 5.0), Position::new(-3.2, -3.2));
+    assert_eq!(stacking::stack_offset(-1, 5.0), Position::new(3.2, 3.2));
+    assert_eq!(stacking::preempt(5.0), 1200.0);
+    assert_eq!(stacking::preempt(9.0), 600.0);
+}
@@ -0,0 +1,112 @@
+extern crate osu;
+
+use osu::storyboard::*;
+use std::fs::File;
+use std::io::Read;
+
+fn storyboard() -> Storyboard {
+    let mut content = String::new();
+    let mut f = File::open("./tests/resources/storyboard_01.osb").unwrap();
+    f.read_to_string(&mut content).unwrap();
+
+    Storyboard::parse(&content).unwrap()
+}
+
+#[test]
+fn test_parse() {
+    let storyboard = storyboard();
+
+    assert_eq!(storyboard.sprites.len(), 2);
+    assert_eq!(storyboard.samples.len(), 1);
+    assert_eq!(storyboard.samples[0].layer, Layer::Foreground);
+    assert_eq!(storyboard.samples[0].volume, 70);
+
+    let background = &storyboard.sprites[0];
+    assert_eq!(background.origin, Origin::Centre);
+    assert_eq!(background.path, "sb/bg.jpg");
+    assert_eq!((background.x, background.y), (320.0, 240.0));
+    // One fade, one scale and a fade shorthand expanded into two steps.
+    assert_eq!(background.commands.len(), 4);
+
+    let star = &storyboard.sprites[1];
+    assert_eq!(star.animation.as_ref().unwrap().loop_type, LoopType::LoopOnce);
+    assert_eq!(star.frame_path(3), "sb/star3.png");
+
+    match star.commands[3] {
+        Command::Trigger { ref trigger, ref events, .. } => {
+            assert_eq!(trigger, "HitSoundClap");
+            assert_eq!(events.len(), 1);
+        },
+        ref other => panic!("expected a trigger, found {:?}", other),
+    }
+}
+
+#[test]
+fn test_state() {
+    let storyboard = storyboard();
+    let background = &storyboard.sprites[0];
+
+    assert_eq!(background.lifetime(), Some((0, 2000)));
+    assert!(background.state_at(2001).is_none());
+
+    let state = background.state_at(500).unwrap();
+    assert!((state.opacity - 0.5).abs() < 1e-9);
+    assert_eq!(state.scale, 0.5);
+
+    let state = background.state_at(1250).unwrap();
+    assert!((state.opacity - 0.75).abs() < 1e-9);
+
+    let state = background.state_at(1750).unwrap();
+    assert!((state.opacity - 0.75).abs() < 1e-9);
+}
+
+#[test]
+fn test_loops_and_animation() {
+    let storyboard = storyboard();
+    let star = &storyboard.sprites[1];
+
+    // The loop runs three times for 500ms each, ignoring the trigger.
+    assert_eq!(star.lifetime(), Some((0, 3500)));
+
+    let state = star.state_at(2750).unwrap();
+    assert!((state.rotation - 1.0).abs() < 1e-9);
+    assert!(state.flip_horizontal);
+    assert_eq!(state.colour, [255.0, 255.0, 255.0]);
+    assert_eq!(state.frame, Some(3));
+    assert_eq!(star.state_at(250).unwrap().frame, Some(2));
+}
+
+#[test]
+fn test_loop_duration() {
+    let storyboard = Storyboard::parse("[Events]
+Sprite,Foreground,Centre,\"a.png\",0,0
+ L,1000,3
+  F,0,500,1000,0,1
+  F,0,1000,1500,1,0").unwrap();
+    let sprite = &storyboard.sprites[0];
+
+    // The inner events span 500 to 1500, so each iteration lasts 1000ms.
+    let starts = sprite.events().iter().map(|event| event.start_time).collect::<Vec<_>>();
+    assert_eq!(starts, vec![1500, 2000, 2500, 3000, 3500, 4000]);
+    assert_eq!(sprite.lifetime(), Some((1500, 4500)));
+    assert!((sprite.state_at(3250).unwrap().opacity - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_errors() {
+    assert!(Storyboard::parse("[Events]\n F,0,0,0,1").is_err());
+    assert!(Storyboard::parse("[Events]\nSprite,Foreground,Centre,\"a.png\",0,0\n Q,0,0,0,1").is_err());
+    assert!(Storyboard::parse("[Events]\nSprite,Foreground,Centre,\"a.png\",0,0\n F,0,0,0,1,2,3,x").is_err());
+}
+
+#[test]
+fn test_easing() {
+    for id in 0..35 {
+        let easing = Easing::from_id(id).unwrap();
+
+        assert!(easing.apply(0.0).abs() < 1e-3, "{:?}", easing);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-3, "{:?}", easing);
+    }
+
+    assert!(Easing::from_id(35).is_none());
+}
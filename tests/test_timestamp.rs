@@ -0,0 +1,99 @@
+extern crate osu;
+
+use osu::hitobject::{self, HitObject};
+use osu::timestamp::*;
+use std::fs;
+
+#[test]
+fn test_parse() {
+    let timestamp = "00:45:123 (1,2,3) - ".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.time, 45_123);
+    assert_eq!(timestamp.selection, vec![
+        Selection::Combo(1),
+        Selection::Combo(2),
+        Selection::Combo(3),
+    ]);
+
+    let timestamp = "01:02:003 (62003|0,62003|3) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.time, 62_003);
+    assert_eq!(timestamp.selection[1], Selection::Mania { time: 62_003, column: 3 });
+
+    assert_eq!("123:00:000".parse::<Timestamp>().unwrap().time, 7_380_000);
+    assert_eq!("osu://edit/00:45:123%20(1)%20-".parse::<Timestamp>().unwrap().selection.len(), 1);
+
+    assert!("00:60:000".parse::<Timestamp>().is_err());
+    assert!("00:45:12".parse::<Timestamp>().is_err());
+    assert!("00:45:123 and more".parse::<Timestamp>().is_err());
+}
+
+#[test]
+fn test_format() {
+    let timestamp = Timestamp {
+        time: 62_003,
+        selection: vec![Selection::Mania { time: 62_003, column: 0 }, Selection::Mania { time: 62_100, column: 2 }],
+    };
+
+    assert_eq!(timestamp.to_string(), "01:02:003 (62003|0,62100|2) -");
+    assert_eq!(Timestamp::new(5).to_string(), "00:00:005 -");
+    assert_eq!(Timestamp::new(5).edit_link(), "osu://edit/00:00:005%20-");
+}
+
+#[test]
+fn test_find_all() {
+    let comment = "00:12:345 (1,2) - stack these\n\
+                   02:00:000 - kiai should start here, see 1234 and 10:00:00é\n\
+                   03:04:567 (1,x) - broken selection";
+    let timestamps = Timestamp::find_all(comment);
+
+    assert_eq!(timestamps.len(), 3);
+    assert_eq!(timestamps[0].selection.len(), 2);
+    assert_eq!(timestamps[1], Timestamp::new(120_000));
+    assert_eq!(timestamps[2].time, 184_567);
+    assert!(timestamps[2].selection.is_empty());
+}
+
+fn hit_objects(path: &str) -> Vec<HitObject> {
+    let content = fs::read_to_string(path).unwrap();
+
+    content.lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "[HitObjects]")
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn test_resolve_combo() {
+    let objects = hit_objects("./tests/resources/beatmap_01.osu");
+
+    let timestamp = "00:06:207 (2,3,4) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.resolve(&objects, 0), vec![Some(9), Some(10), Some(11)]);
+
+    // The first object of the next combo follows the last of this one.
+    let timestamp = "00:02:250 (4,1,2) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.resolve(&objects, 0), vec![Some(3), Some(4), Some(5)]);
+
+    // The object after a spinner starts a new combo.
+    assert_eq!(hitobject::combo_numbers(&objects)[19..23], [8, 1, 1, 2]);
+
+    let timestamp = "00:06:207 (2,8) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.resolve(&objects, 0), vec![Some(9), Some(19)]);
+
+    let timestamp = "00:23:662 (1,2) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.resolve(&objects, 0), vec![Some(45), None]);
+}
+
+#[test]
+fn test_resolve_mania() {
+    let objects = hit_objects("./tests/resources/beatmap_02.osu");
+
+    let timestamp = "00:01:167 (1167|0,1167|5) -".parse::<Timestamp>().unwrap();
+    assert_eq!(timestamp.resolve(&objects, 7), vec![Some(6), Some(5)]);
+
+    let timestamp = "00:01:333 (1333|1,1333|2) -".parse::<Timestamp>().unwrap();
+    let resolved = timestamp.resolve(&objects, 7);
+    assert_eq!(resolved[1], None);
+    assert_eq!(objects[resolved[0].unwrap()].end_time(), 1666.0);
+}
@@ -0,0 +1,108 @@
+#![cfg(feature = "osz-support")]
+
+extern crate osu;
+extern crate serde_json;
+
+use osu::osz::Difficulty;
+use osu::timing::*;
+use osu::Beatmap;
+use std::fs::File;
+use std::io::Read;
+
+fn difficulty() -> Difficulty {
+    let mut bytes = vec![];
+    File::open("./tests/resources/beatmap_01.osu").unwrap().read_to_end(&mut bytes).unwrap();
+
+    Difficulty::parse("beatmap_01.osu", &bytes).unwrap()
+}
+
+#[test]
+fn test_bpm_and_slider_velocity() {
+    let timing = difficulty().timing();
+
+    assert_eq!(timing.points().len(), 6);
+    assert_eq!(timing.bpm_at(0.0).map(f64::round), Some(144.0));
+    assert_eq!(timing.bpm_at(15_000.0).map(f64::round), Some(180.0));
+    assert_eq!(timing.bpm_at(20_000.0).map(f64::round), Some(144.0));
+    assert_eq!(timing.beat_length_at(14_330.093), Some(333.333333333333));
+
+    assert_eq!(timing.slider_velocity_at(3000.0), 1.0);
+    assert_eq!(timing.slider_velocity_at(6000.0), 0.5);
+    assert_eq!(timing.slider_velocity_at(8000.0), 1.0);
+    // Each red point resets the velocity.
+    assert_eq!(timing.slider_velocity_at(15_000.0), 1.0);
+}
+
+#[test]
+fn test_snap() {
+    let timing = difficulty().timing();
+
+    assert_eq!(timing.snap(1400.0, 1).unwrap().round(), 1417.0);
+    assert_eq!(timing.snap(1100.0, 4).unwrap().round(), 1104.0);
+    assert_eq!(timing.snap(1150.0, 3).unwrap().round(), 1139.0);
+    assert_eq!(timing.snap(1000.0, 0), None);
+    assert_eq!(timing.snap(1000.0, 17), None);
+
+    let snap = timing.nearest_snap(6839.0).unwrap();
+    assert_eq!(snap.divisor, 1);
+    assert_eq!(snap.time.round(), 6832.0);
+    assert_eq!(snap.offset().round(), 7.0);
+
+    // A 1/2 beat is also a 1/4 beat, and the coarser divisor is preferred.
+    assert_eq!(timing.nearest_snap(1208.0).unwrap().divisor, 2);
+    assert_eq!(timing.nearest_snap(1174.0).unwrap().divisor, 12);
+}
+
+#[test]
+fn test_kiai_ranges() {
+    let timing = difficulty().timing();
+
+    assert!(!timing.kiai_at(7000.0));
+    assert!(timing.kiai_at(7665.0));
+    assert!(!timing.kiai_at(10_998.0));
+    assert_eq!(timing.kiai_ranges(23_662.0), vec![(7665.0, 10_998.0)]);
+
+    let open = Timing::new(vec!["0,500,4,2,1,60,1,1".parse().unwrap()]);
+    assert_eq!(open.kiai_ranges(2000.0), vec![(0.0, 2000.0)]);
+}
+
+#[test]
+fn test_unsnapped() {
+    let difficulty = difficulty();
+    let timing = difficulty.timing();
+    let unsnapped = timing.unsnapped(&difficulty.hit_objects, &difficulty.sliders(), 1.0);
+    let indices = unsnapped.iter().map(|object| object.index).collect::<Vec<_>>();
+
+    // A circle moved off the beat, and three sliders whose lengths do not
+    // end them on a snap.
+    assert_eq!(indices, vec![5, 6, 11, 45]);
+    assert_eq!(unsnapped[2].snap.unsnapped_time, 6839.0);
+
+    // The semicircle with a repeat is 2 * 157.08 / 140 beats long, ending
+    // 2ms before 10 1/4 beats from the red point.
+    let beat = 416.565418127;
+    assert!((unsnapped[1].snap.unsnapped_time - (4333.0 + 2.0 * 157.079632679 / 140.0 * beat)).abs() < 1e-6);
+    assert!((unsnapped[1].snap.time - (1000.0 + 10.25 * beat)).abs() < 1e-6);
+    assert_eq!(unsnapped[1].snap.divisor, 4);
+
+    // Without sliders, only start times and the ends of spinners are
+    // checked.
+    let unsnapped = timing.unsnapped(&difficulty.hit_objects, &[], 1.0);
+    assert_eq!(unsnapped.len(), 1);
+    assert_eq!(unsnapped[0].index, 11);
+    assert_eq!(difficulty.hit_objects[11].time, 6839.0);
+}
+
+#[test]
+fn test_dominant_bpm() {
+    let f = File::open("./tests/resources/beatmaps_01.json").unwrap();
+    let beatmaps = serde_json::from_reader::<File, Vec<Beatmap>>(f).unwrap();
+    let beatmap = beatmaps.iter().find(|beatmap| beatmap.beatmap_id == 191904).unwrap();
+
+    let difficulty = difficulty();
+    let last_time = difficulty.hit_objects.last().unwrap().end_time();
+    let bpm = difficulty.timing().dominant_bpm(last_time).unwrap();
+
+    assert_eq!((bpm * 1000.0).round() / 1000.0, beatmap.bpm);
+    assert_eq!(Timing::default().dominant_bpm(1000.0), None);
+}
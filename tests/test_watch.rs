@@ -0,0 +1,84 @@
+extern crate osu;
+extern crate serde_json;
+
+use osu::ratelimit::RateLimiter;
+use osu::watch::*;
+use osu::{Error, GetBeatmapUser, RecentPlay};
+use std::fs::File;
+use std::time::Duration;
+
+fn plays() -> Vec<RecentPlay> {
+    let f = File::open("./tests/resources/user_recent_01.json").unwrap();
+
+    serde_json::from_reader(f).unwrap()
+}
+
+#[test]
+fn test_process() {
+    let user = GetBeatmapUser::UserId(124493);
+    let plays = plays();
+    let mut watcher = RecentWatcher::new(vec![user.clone()]);
+
+    // The first plays seen are only recorded.
+    assert!(watcher.process(&user, plays[1..].to_vec()).is_empty());
+    assert_eq!(watcher.cursor().users["124493"].date, plays[1].date);
+
+    let mut repeated = plays.clone();
+    repeated.insert(0, plays[0].clone());
+    let new = watcher.process(&user, repeated);
+    assert_eq!(new.len(), 1);
+    assert_eq!(new[0].beatmap_id, plays[0].beatmap_id);
+
+    assert!(watcher.process(&user, plays.clone()).is_empty());
+
+    // A different play at the same date as the latest is still new.
+    let mut same_date = plays[0].clone();
+    same_date.beatmap_id = 1;
+    let new = watcher.process(&user, vec![same_date]);
+    assert_eq!(new.len(), 1);
+    assert_eq!(new[0].beatmap_id, 1);
+}
+
+#[test]
+fn test_cursor() {
+    let user = GetBeatmapUser::Username("Cookiezi".to_owned());
+    let plays = plays();
+    let mut watcher = RecentWatcher::new(vec![user.clone()]);
+    watcher.process(&user, plays[1..].to_vec());
+
+    let saved = serde_json::to_string(watcher.cursor()).unwrap();
+    let cursor = serde_json::from_str::<Cursor>(&saved).unwrap();
+    assert_eq!(&cursor, watcher.cursor());
+    assert!(cursor.users.contains_key("name:cookiezi"));
+
+    let mut restored = RecentWatcher::with_cursor(vec![user.clone()], cursor);
+    assert_eq!(restored.process(&user, plays).len(), 1);
+}
+
+#[test]
+fn test_poll() {
+    let plays = plays();
+    let users = vec![GetBeatmapUser::UserId(1), GetBeatmapUser::UserId(2)];
+    let mut watcher = RecentWatcher::new(users);
+
+    let results = watcher.poll(|_| Ok(plays[1..].to_vec()));
+    assert!(results.is_empty());
+
+    let results = watcher.poll(|user| match *user {
+        GetBeatmapUser::UserId(1) => Ok(plays.clone()),
+        _ => Err(Error::Parse("unavailable".to_owned())),
+    });
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().beatmap_id, plays[0].beatmap_id);
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn test_rate_limiter() {
+    let limiter = RateLimiter::new(60);
+
+    assert_eq!(limiter.reserve(), Duration::from_secs(0));
+
+    let delay = limiter.reserve();
+    assert!(delay > Duration::from_millis(900) && delay <= Duration::from_secs(1));
+}